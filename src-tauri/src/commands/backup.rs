@@ -0,0 +1,167 @@
+use crate::commands::settings::{AppSettings, SettingsState};
+use crate::managers::ThreadManager;
+use crate::services::backup::{self, BackupManifest};
+use crate::services::ProfileManager;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// Emitted after a restore so the frontend reloads threads and settings
+pub const BACKUP_RESTORED_EVENT: &str = "backup-restored";
+
+/// How often the scheduler checks whether a backup is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Back up the database and settings into a single archive. Without `path`
+/// a save dialog is shown; returns the written path, or `None` if the
+/// dialog was cancelled. API keys are not included.
+#[tauri::command]
+pub async fn create_backup(
+    path: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<Option<String>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let Some(path) = app_handle
+                .dialog()
+                .file()
+                .set_title("Back up Seeva")
+                .set_file_name(backup::backup_file_name(chrono::Utc::now()))
+                .add_filter("Seeva backup", &["zip"])
+                .blocking_save_file()
+            else {
+                println!("   ⚠️  Backup cancelled");
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?
+        }
+    };
+    println!("💾 Creating backup at {}", path.display());
+
+    let settings_json = settings.export_json().map_err(|e| e.to_string())?;
+    thread_manager
+        .create_backup(&settings_json, &path)
+        .map_err(|e| {
+            eprintln!("   ❌ Backup failed: {}", e);
+            e.to_string()
+        })?;
+
+    println!("   ✅ Backup written");
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Replace the database and settings with the contents of a backup. Without
+/// `path` a file dialog is shown; returns `None` if it was cancelled.
+/// Stored API keys are kept.
+#[tauri::command]
+pub async fn restore_backup(
+    path: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<Option<BackupManifest>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let Some(path) = app_handle
+                .dialog()
+                .file()
+                .set_title("Restore Seeva backup")
+                .add_filter("Seeva backup", &["zip"])
+                .blocking_pick_file()
+            else {
+                println!("   ⚠️  Restore cancelled");
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?
+        }
+    };
+    println!("♻️  Restoring backup from {}", path.display());
+
+    let extracted = backup::read_backup(&path).map_err(|e| {
+        eprintln!("   ❌ Failed to read backup: {}", e);
+        e.to_string()
+    })?;
+
+    // Parse settings before touching the database so a bad archive changes nothing
+    let restored_settings = extracted
+        .settings_json
+        .as_deref()
+        .map(serde_json::from_str::<AppSettings>)
+        .transpose()
+        .map_err(|e| format!("Invalid settings in backup: {}", e))?;
+
+    thread_manager
+        .restore_backup(&extracted)
+        .map_err(|e| format!("Failed to restore database: {}", e))?;
+    println!("   ✅ Database restored");
+
+    // Subscribers re-register the shortcut if it changed
+    if let Some(restored) = restored_settings {
+        settings.restore(restored).map_err(|e| e.to_string())?;
+        println!("   ✅ Settings restored");
+    }
+
+    if let Err(e) = app_handle.emit(BACKUP_RESTORED_EVENT, &extracted.manifest) {
+        eprintln!("   ⚠️  Failed to emit restore event: {}", e);
+    }
+    Ok(Some(extracted.manifest.clone()))
+}
+
+/// Take scheduled backups of the active profile in the background. Unless
+/// the settings name a folder, they go to `backups` in the profile directory.
+pub fn start_backup_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_scheduled_backup(&app_handle) {
+                eprintln!("⚠️  Scheduled backup failed: {}", e);
+            }
+            tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+fn run_scheduled_backup(app_handle: &AppHandle) -> Result<(), String> {
+    let settings = app_handle.state::<SettingsState>();
+    let config = settings.get().map_err(|e| e.to_string())?.backup;
+    if !config.enabled {
+        return Ok(());
+    }
+    // Not managed until an encrypted database has been unlocked
+    let Some(thread_manager) = app_handle.try_state::<Arc<ThreadManager>>() else {
+        return Ok(());
+    };
+
+    let folder = config
+        .folder
+        .as_deref()
+        .filter(|f| !f.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let profiles = app_handle.state::<Arc<ProfileManager>>();
+            profiles.profile_dir(&profiles.active().id).join("backups")
+        });
+    let now = chrono::Utc::now();
+    if !backup::is_backup_due(&folder, config.interval_hours, now).map_err(|e| e.to_string())? {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let path = folder.join(backup::scheduled_backup_file_name(now));
+    println!("💾 Taking scheduled backup: {}", path.display());
+
+    let settings_json = settings.export_json().map_err(|e| e.to_string())?;
+    thread_manager
+        .create_backup(&settings_json, &path)
+        .map_err(|e| e.to_string())?;
+
+    let removed = backup::rotate_backups(&folder, config.keep.max(1) as usize)
+        .map_err(|e| e.to_string())?;
+    println!("   ✅ Backup written, {} old backup(s) removed", removed.len());
+    Ok(())
+}
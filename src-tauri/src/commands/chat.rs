@@ -1,11 +1,110 @@
 use crate::managers::ThreadManager;
-use crate::models::{Message, MessageRole};
-use crate::services::ai::provider::{AIProvider, ChatMessage, ChatRequest, StreamEvent};
-use crate::services::ai::{AnthropicProvider, OpenAIProvider, OpenRouterProvider};
+use crate::models::{ImageData, Message, MessagePage, MessageRole};
+use crate::services::ai::provider::{
+    is_length_limit, AIProvider, ChatMessage, ChatRequest, StreamEvent, StreamResult, TokenUsage,
+};
+use crate::commands::settings::{build_client, SettingsState};
+use crate::services::{Database, SettingsManager};
 use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{Emitter, State};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+
+/// Tauri event carrying streamed chat output
+const CHAT_STREAM_EVENT: &str = "chat-stream";
+
+/// Messages returned by `get_messages` when no limit is given
+const MESSAGE_PAGE_SIZE: u32 = 50;
+
+/// A provider stream event tagged with the request it belongs to, so the
+/// frontend can route it to the right thread and message bubble
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatStreamPayload {
+    pub request_id: String,
+    pub thread_id: String,
+    pub message_id: String,
+    pub event: StreamEvent,
+}
+
+/// Identifiers returned by `send_message` and `continue_message` once the
+/// provider has accepted the request, before any content is streamed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMessageResponse {
+    pub request_id: String,
+    pub thread_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_message_id: Option<String>,
+    pub message_id: String,
+}
+
+/// One provider/model pair for `send_message_multi`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTarget {
+    pub provider: String,
+    pub model: String,
+    pub max_tokens: Option<u32>,
+    /// Optional caller-chosen correlation id, as for `send_message`
+    pub request_id: Option<String>,
+}
+
+/// Returned by `send_message_multi`: one entry per target, in request order
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMessageMultiResponse {
+    pub comparison_id: String,
+    pub thread_id: String,
+    pub user_message_id: String,
+    pub responses: Vec<SendMessageResponse>,
+}
+
+/// Correlation ids for a single streamed response
+#[derive(Debug, Clone)]
+struct StreamTarget {
+    request_id: String,
+    thread_id: String,
+    message_id: String,
+    /// When the request was sent, for latency reporting
+    started: Instant,
+}
+
+impl StreamTarget {
+    fn emit(&self, app_handle: &AppHandle, event: StreamEvent) {
+        let payload = ChatStreamPayload {
+            request_id: self.request_id.clone(),
+            thread_id: self.thread_id.clone(),
+            message_id: self.message_id.clone(),
+            event,
+        };
+
+        if let Err(e) = app_handle.emit(CHAT_STREAM_EVENT, payload) {
+            eprintln!("❌ Failed to emit stream event for request {}: {}", self.request_id, e);
+        }
+    }
+}
+
+const SYSTEM_PROMPT: &str = "You are Seeva AI Assistant, a helpful AI assistant. You are part of the Seeva AI Assistant application and should identify yourself as Seeva, not by the underlying AI model name. Provide helpful, accurate, and friendly assistance to users.";
+
+/// Instruction appended when resuming a partial assistant message
+const CONTINUE_PROMPT: &str = "Your previous response was cut off. Continue exactly where it stopped, without repeating any of it or adding an introduction.";
+
+/// Create a provider client using the API key stored in settings, so keys
+/// never have to pass through the webview
+fn create_provider(settings: &SettingsManager, provider: &str) -> Result<Box<dyn AIProvider>, String> {
+    let current = settings.get().map_err(|e| e.to_string())?;
+    let config = current.require_provider(provider).map_err(|e| e.to_string())?;
+
+    let api_key = settings
+        .get_api_key(provider)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("API key not configured for {}", provider))?;
+
+    build_client(provider, config, api_key)
+}
 
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
@@ -14,22 +113,31 @@ pub async fn send_message(
     content: String,
     images: Option<Vec<String>>,
     provider: String,
-    api_key: String,
     model: String,
     max_tokens: Option<u32>,
     _include_context: Option<bool>,
+    request_id: Option<String>,
     thread_manager: State<'_, Arc<ThreadManager>>,
-    app_handle: tauri::AppHandle,
-) -> Result<Message, String> {
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<SendMessageResponse, String> {
+    // Callers may supply their own request id so they can attribute events
+    // that arrive before this command returns
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
     println!("📨 Sending message to {} using model {}", provider, model);
+    println!("   Request ID: {}", request_id);
     println!("   Thread ID: {}", thread_id);
     println!("   Content length: {} chars", content.len());
     if let Some(ref imgs) = images {
         println!("   Images: {} attached", imgs.len());
     }
 
+    // Resolve the provider first so a missing key doesn't leave a dangling question
+    let ai_provider = create_provider(&settings, &provider)?;
+
     // Add user message to database
-    let _user_message = thread_manager
+    let user_message = thread_manager
         .add_message(
             thread_id.clone(),
             MessageRole::User,
@@ -44,116 +152,495 @@ pub async fn send_message(
         .map_err(|e| e.to_string())?;
 
     // Convert to API format
-    let api_messages: Vec<ChatMessage> = messages
+    let api_messages = to_chat_messages(&messages, None);
+
+    // Create chat request
+    let chat_request = build_request(api_messages, &model, max_tokens);
+
+    // Open the stream before returning so request-level failures (bad key,
+    // unknown model) still surface as a command error
+    let started = Instant::now();
+    let stream = match ai_provider.chat_stream(chat_request).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            // Nothing will answer the question, so don't leave it in the
+            // thread to be sent again alongside the next one
+            if let Err(discard_error) = thread_manager.discard_message(&user_message) {
+                eprintln!("⚠️  Failed to remove unanswered message: {}", discard_error);
+            }
+            return Err(e.to_string());
+        }
+    };
+
+    // Pre-allocate the assistant message id so every event can reference it
+    let target = StreamTarget {
+        request_id,
+        thread_id,
+        message_id: uuid::Uuid::new_v4().to_string(),
+        started,
+    };
+
+    let response = SendMessageResponse {
+        request_id: target.request_id.clone(),
+        thread_id: target.thread_id.clone(),
+        user_message_id: Some(user_message.id),
+        message_id: target.message_id.clone(),
+    };
+
+    let assistant_message = new_assistant_message(&target, &provider, &model);
+
+    let store = thread_manager.thread_store(&target.thread_id).map_err(|e| e.to_string())?;
+    let thread_manager = Arc::clone(thread_manager.inner());
+    tauri::async_runtime::spawn(async move {
+        stream_response(stream, target, assistant_message, false, thread_manager, store, app_handle).await;
+    });
+
+    Ok(response)
+}
+
+/// Send one user message to several provider/model pairs at once. Each answer
+/// streams under its own request id and is stored as a sibling assistant
+/// message sharing a `comparisonId`, tagged with its provider, model, latency
+/// and token usage. A target that fails to start reports an `error` event on
+/// its own request id without affecting the others.
+#[tauri::command]
+pub async fn send_message_multi(
+    thread_id: String,
+    content: String,
+    images: Option<Vec<String>>,
+    targets: Vec<ModelTarget>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<SendMessageMultiResponse, String> {
+    if targets.is_empty() {
+        return Err("At least one model must be selected".to_string());
+    }
+
+    println!("📨 Sending message to {} models", targets.len());
+    println!("   Thread ID: {}", thread_id);
+
+    // Reject unknown providers and missing keys before anything is saved
+    let providers = targets
         .iter()
-        .filter(|m| m.role != MessageRole::System)
-        .map(|m| ChatMessage {
-            role: m.role.as_str().to_string(),
-            content: m.content.clone(),
-            images: m.images.clone(),
-        })
-        .collect();
+        .map(|t| create_provider(&settings, &t.provider))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let user_message = thread_manager
+        .add_message(thread_id.clone(), MessageRole::User, content, images)
+        .map_err(|e| e.to_string())?;
+
+    let messages = thread_manager
+        .get_messages(&thread_id)
+        .map_err(|e| e.to_string())?;
+    let api_messages = to_chat_messages(&messages, None);
+    let store = thread_manager.thread_store(&thread_id).map_err(|e| e.to_string())?;
+
+    let comparison_id = uuid::Uuid::new_v4().to_string();
+    let mut responses = Vec::with_capacity(targets.len());
+
+    for (model_target, ai_provider) in targets.into_iter().zip(providers) {
+        println!("   → {} ({})", model_target.model, model_target.provider);
+
+        let target = StreamTarget {
+            request_id: model_target
+                .request_id
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            thread_id: thread_id.clone(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            started: Instant::now(),
+        };
+
+        responses.push(SendMessageResponse {
+            request_id: target.request_id.clone(),
+            thread_id: thread_id.clone(),
+            user_message_id: Some(user_message.id.clone()),
+            message_id: target.message_id.clone(),
+        });
 
-    // Create AI provider
-    let ai_provider: Box<dyn AIProvider> = match provider.as_str() {
-        "anthropic" => Box::new(AnthropicProvider::new(api_key)),
-        "openai" => Box::new(OpenAIProvider::new(api_key)),
-        "openrouter" => Box::new(OpenRouterProvider::new(api_key)),
-        _ => return Err(format!("Unsupported provider: {}", provider)),
+        let mut assistant_message =
+            new_assistant_message(&target, &model_target.provider, &model_target.model);
+        if let Some(metadata) = assistant_message.metadata.as_mut() {
+            metadata.insert("comparisonId".to_string(), serde_json::json!(comparison_id));
+        }
+
+        let chat_request = build_request(
+            api_messages.clone(),
+            &model_target.model,
+            model_target.max_tokens,
+        );
+        let thread_manager = Arc::clone(thread_manager.inner());
+        let store = Arc::clone(&store);
+        let app_handle = app_handle.clone();
+
+        tauri::async_runtime::spawn(async move {
+            match ai_provider.chat_stream(chat_request).await {
+                Ok(stream) => {
+                    stream_response(
+                        stream,
+                        target,
+                        assistant_message,
+                        false,
+                        thread_manager,
+                        store,
+                        app_handle,
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    eprintln!("❌ Failed to start {} stream: {}", model_target.model, e);
+                    target.emit(&app_handle, StreamEvent::Error { error: e.to_string() });
+                }
+            }
+        });
+    }
+
+    Ok(SendMessageMultiResponse {
+        comparison_id,
+        thread_id,
+        user_message_id: user_message.id,
+        responses,
+    })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn continue_message(
+    message_id: String,
+    provider: String,
+    model: String,
+    max_tokens: Option<u32>,
+    request_id: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<SendMessageResponse, String> {
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    println!("⏩ Continuing message {} with {} ({})", message_id, provider, model);
+
+    let message = thread_manager
+        .get_message(&message_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Message not found: {}", message_id))?;
+
+    if message.role != MessageRole::Assistant {
+        return Err("Only assistant messages can be continued".to_string());
+    }
+    if !is_incomplete(&message) && !is_truncated(&message) {
+        return Err("Message is already complete".to_string());
+    }
+
+    // Replay the conversation up to and including the partial answer, then
+    // ask the model to pick up where it stopped
+    let history = thread_manager
+        .get_messages(&message.thread_id)
+        .map_err(|e| e.to_string())?;
+    let cutoff = history
+        .iter()
+        .position(|m| m.id == message.id)
+        .map(|idx| idx + 1)
+        .unwrap_or(history.len());
+
+    let mut api_messages = to_chat_messages(&history[..cutoff], Some(&message.id));
+    api_messages.push(ChatMessage {
+        role: MessageRole::User.as_str().to_string(),
+        content: CONTINUE_PROMPT.to_string(),
+        images: None,
+    });
+
+    let ai_provider = create_provider(&settings, &provider)?;
+
+    let chat_request = build_request(api_messages, &model, max_tokens);
+
+    let started = Instant::now();
+    let stream = ai_provider
+        .chat_stream(chat_request)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let target = StreamTarget {
+        request_id,
+        thread_id: message.thread_id.clone(),
+        message_id: message.id.clone(),
+        started,
     };
 
-    // Build system prompt
-    let system_prompt = "You are Seeva AI Assistant, a helpful AI assistant. You are part of the Seeva AI Assistant application and should identify yourself as Seeva, not by the underlying AI model name. Provide helpful, accurate, and friendly assistance to users.".to_string();
+    let response = SendMessageResponse {
+        request_id: target.request_id.clone(),
+        thread_id: target.thread_id.clone(),
+        user_message_id: None,
+        message_id: target.message_id.clone(),
+    };
 
-    // Create chat request
-    let chat_request = ChatRequest {
-        messages: api_messages,
-        model: model.clone(),
-        system: Some(system_prompt),
+    let store = thread_manager.thread_store(&target.thread_id).map_err(|e| e.to_string())?;
+    let thread_manager = Arc::clone(thread_manager.inner());
+    tauri::async_runtime::spawn(async move {
+        stream_response(stream, target, message, true, thread_manager, store, app_handle).await;
+    });
+
+    Ok(response)
+}
+
+/// Whether an assistant message was left unfinished by a failed stream
+fn is_incomplete(message: &Message) -> bool {
+    message
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("status"))
+        .and_then(|s| s.as_str())
+        == Some("incomplete")
+}
+
+/// Whether an assistant message stopped because it hit the output token limit
+fn is_truncated(message: &Message) -> bool {
+    message
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("stopReason"))
+        .and_then(|s| s.as_str())
+        .is_some_and(is_length_limit)
+}
+
+fn build_request(messages: Vec<ChatMessage>, model: &str, max_tokens: Option<u32>) -> ChatRequest {
+    ChatRequest {
+        messages,
+        model: model.to_string(),
+        system: Some(SYSTEM_PROMPT.to_string()),
         temperature: Some(0.7),
         max_tokens: max_tokens.or(Some(4096)), // Use provided max_tokens or default to 4096
         stream: true,
+    }
+}
+
+/// An empty assistant message under the target's pre-allocated id, ready for
+/// `stream_response` to fill in
+fn new_assistant_message(target: &StreamTarget, provider: &str, model: &str) -> Message {
+    let mut metadata = HashMap::new();
+    metadata.insert("model".to_string(), serde_json::json!(model));
+    metadata.insert("provider".to_string(), serde_json::json!(provider));
+
+    let mut message = Message::new(target.thread_id.clone(), MessageRole::Assistant, String::new());
+    message.id = target.message_id.clone();
+    message.metadata = Some(metadata);
+    message
+}
+
+/// Convert stored messages into the provider-neutral request format.
+/// Of the sibling answers from a `send_message_multi` comparison only one
+/// is replayed, so follow-up turns see a single assistant reply: `keep`
+/// if it is one of them (e.g. the answer being continued), else the first.
+fn to_chat_messages(messages: &[Message], keep: Option<&str>) -> Vec<ChatMessage> {
+    let comparison_id = |m: &Message| {
+        m.metadata
+            .as_ref()
+            .and_then(|meta| meta.get("comparisonId"))
+            .and_then(|id| id.as_str())
+            .map(str::to_string)
     };
 
-    // Stream response
-    let mut stream = ai_provider
-        .chat_stream(chat_request)
-        .await
-        .map_err(|e| e.to_string())?;
+    let replayable: Vec<&Message> = messages
+        .iter()
+        .filter(|m| m.role != MessageRole::System)
+        // Providers reject empty assistant turns
+        .filter(|m| !(m.role == MessageRole::Assistant && m.content.is_empty()))
+        .collect();
+
+    let mut chosen: HashMap<String, &str> = HashMap::new();
+    for m in &replayable {
+        if let Some(comparison_id) = comparison_id(m) {
+            let sibling = chosen.entry(comparison_id).or_insert(&m.id);
+            if keep == Some(m.id.as_str()) {
+                *sibling = &m.id;
+            }
+        }
+    }
+
+    replayable
+        .into_iter()
+        .filter(|m| match comparison_id(m) {
+            Some(comparison_id) => chosen.get(&comparison_id) == Some(&m.id.as_str()),
+            None => true,
+        })
+        .map(|m| ChatMessage {
+            role: m.role.as_str().to_string(),
+            content: m.content.clone(),
+            images: m.images.clone(),
+        })
+        .collect()
+}
 
-    let mut full_content = String::new();
+/// Add a stream's token usage to the running totals in a message's metadata
+fn record_usage(metadata: &mut HashMap<String, serde_json::Value>, usage: &TokenUsage) {
+    let previous = |key: &str| {
+        metadata
+            .get("tokens")
+            .and_then(|t| t.get(key))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+    };
+    let input = previous("input") + usage.input_tokens as u64;
+    let output = previous("output") + usage.output_tokens as u64;
+
+    metadata.insert(
+        "tokens".to_string(),
+        serde_json::json!({
+            "input": input,
+            "output": output,
+        }),
+    );
+}
+
+/// Forward a provider stream to the frontend and persist the assistant message
+/// once it ends. Deltas are appended to `message.content`, so a continuation
+/// passes the existing message with `exists` set and it is updated in place.
+///
+/// `MessageStop` is held back until the message is saved so the frontend can
+/// reload the thread as soon as it sees it. If the stream fails, whatever was
+/// received is still saved with `status: "incomplete"` before the error is
+/// emitted, so `continue_message` can resume it. The provider's stop reason is
+/// stored as `stopReason`, which lets answers cut off by `max_tokens` be
+/// continued the same way.
+///
+/// `store` is the database the thread was in when the stream started, so a
+/// profile switch mid-stream doesn't lose the reply.
+async fn stream_response(
+    mut stream: StreamResult,
+    target: StreamTarget,
+    mut message: Message,
+    exists: bool,
+    thread_manager: Arc<ThreadManager>,
+    store: Arc<Database>,
+    app_handle: AppHandle,
+) {
+    let initial_len = message.content.len();
     let mut token_usage = None;
+    let mut stop_reason = None;
+    let mut stream_error = None;
 
     while let Some(event_result) = stream.next().await {
         match event_result {
+            Ok(StreamEvent::MessageStop { usage, stop_reason: reason }) => {
+                // Providers may send several stop events; only some carry
+                // usage or a stop reason
+                if usage.is_some() {
+                    token_usage = usage;
+                }
+                if reason.is_some() {
+                    stop_reason = reason;
+                }
+            }
+            Ok(StreamEvent::Error { error }) => {
+                stream_error = Some(error);
+                break;
+            }
             Ok(event) => {
-                // Emit event to frontend
-                app_handle
-                    .emit("chat-stream", &event)
-                    .map_err(|e| e.to_string())?;
-
-                // Handle event
-                match event {
-                    StreamEvent::ContentDelta { delta } => {
-                        full_content.push_str(&delta);
-                    }
-                    StreamEvent::MessageStop { usage } => {
-                        token_usage = usage;
-                    }
-                    StreamEvent::Error { error } => {
-                        eprintln!("❌ Stream error: {}", error);
-                        return Err(error);
-                    }
-                    _ => {}
+                if let StreamEvent::ContentDelta { delta } = &event {
+                    message.content.push_str(delta);
                 }
+                target.emit(&app_handle, event);
             }
             Err(e) => {
-                eprintln!("❌ Stream processing error: {}", e);
-                return Err(e.to_string());
+                stream_error = Some(e.to_string());
+                break;
             }
         }
     }
 
-    println!("✅ Received complete response ({} chars)", full_content.len());
+    let received = message.content.len() - initial_len;
+    let metadata = message.metadata.get_or_insert_with(HashMap::new);
+
     if let Some(usage) = &token_usage {
-        println!("   Tokens used: {} input, {} output", usage.input_tokens, usage.output_tokens);
+        record_usage(metadata, usage);
     }
+    metadata.insert(
+        "latencyMs".to_string(),
+        serde_json::json!(target.started.elapsed().as_millis() as u64),
+    );
+    match &stop_reason {
+        Some(reason) => metadata.insert("stopReason".to_string(), serde_json::json!(reason)),
+        None => metadata.remove("stopReason"),
+    };
 
-    // Create metadata
-    let mut metadata = HashMap::new();
-    metadata.insert("model".to_string(), serde_json::json!(model));
-    metadata.insert("provider".to_string(), serde_json::json!(provider));
-    if let Some(usage) = token_usage {
-        metadata.insert(
-            "tokens".to_string(),
-            serde_json::json!({
-                "input": usage.input_tokens,
-                "output": usage.output_tokens,
-            }),
-        );
+    match &stream_error {
+        Some(error) => {
+            eprintln!("❌ Stream failed after {} bytes: {}", received, error);
+
+            // Nothing worth keeping from a brand new message
+            if !exists && message.content.is_empty() {
+                target.emit(&app_handle, StreamEvent::Error { error: error.clone() });
+                return;
+            }
+
+            metadata.insert("status".to_string(), serde_json::json!("incomplete"));
+            metadata.insert("error".to_string(), serde_json::json!(error));
+        }
+        None => {
+            println!(
+                "✅ Received complete response ({} bytes, stop reason: {})",
+                received,
+                stop_reason.as_deref().unwrap_or("unknown")
+            );
+            if let Some(usage) = &token_usage {
+                println!("   Tokens used: {} input, {} output", usage.input_tokens, usage.output_tokens);
+            }
+
+            metadata.remove("status");
+            metadata.remove("error");
+        }
     }
 
-    // Add assistant message to database
-    let mut assistant_message = Message::new(thread_id, MessageRole::Assistant, full_content);
-    assistant_message.metadata = Some(metadata);
+    // Into the database the stream started in, even if the profile changed since
+    let saved = if exists {
+        thread_manager.update_message_in(&store, &message)
+    } else {
+        thread_manager.create_message_in(&store, &message)
+    };
 
-    thread_manager
-        .create_message(&assistant_message)
-        .map_err(|e| e.to_string())?;
+    if let Err(e) = saved {
+        eprintln!("❌ Failed to save assistant message: {}", e);
+        target.emit(&app_handle, StreamEvent::Error { error: e.to_string() });
+        return;
+    }
 
-    Ok(assistant_message)
+    match stream_error {
+        Some(error) => target.emit(&app_handle, StreamEvent::Error { error }),
+        None => target.emit(
+            &app_handle,
+            StreamEvent::MessageStop {
+                usage: token_usage,
+                stop_reason,
+            },
+        ),
+    }
 }
 
+/// Load a page of messages, newest first by page but oldest first within
+/// it. Pass the first message id of a page as `before` to load the one
+/// preceding it. Images come as references; load them with `get_image`.
 #[tauri::command]
 pub async fn get_messages(
     thread_id: String,
+    before: Option<String>,
+    limit: Option<u32>,
     thread_manager: State<'_, Arc<ThreadManager>>,
-) -> Result<Vec<Message>, String> {
+) -> Result<MessagePage, String> {
     thread_manager
-        .get_messages(&thread_id)
+        .get_messages_page(&thread_id, before.as_deref(), limit.unwrap_or(MESSAGE_PAGE_SIZE))
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_image(
+    id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<ImageData, String> {
+    thread_manager
+        .get_image(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Image not found: {}", id))
+}
+
 #[tauri::command]
 pub async fn delete_message(
     id: String,
@@ -161,3 +648,32 @@ pub async fn delete_message(
 ) -> Result<(), String> {
     thread_manager.delete_message(&id).map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sibling(content: &str) -> Message {
+        let mut message = Message::new("t".to_string(), MessageRole::Assistant, content.to_string());
+        message.metadata = Some(HashMap::from([(
+            "comparisonId".to_string(),
+            serde_json::Value::from("c1"),
+        )]));
+        message
+    }
+
+    #[test]
+    fn test_continue_replays_the_continued_sibling() {
+        let question = Message::new("t".to_string(), MessageRole::User, "Hi".to_string());
+        let first = sibling("From the first model");
+        let second = sibling("Partial answer from the sec");
+        let history = vec![question, first, second.clone()];
+
+        let contents = |messages: Vec<ChatMessage>| messages.into_iter().map(|m| m.content).collect::<Vec<_>>();
+        assert_eq!(contents(to_chat_messages(&history, None)), ["Hi", "From the first model"]);
+        assert_eq!(
+            contents(to_chat_messages(&history, Some(&second.id))),
+            ["Hi", "Partial answer from the sec"]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::managers::ThreadManager;
+use crate::services::database::{self, Database};
+use crate::services::ProfileManager;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStatus {
+    pub encrypted: bool,
+    /// False until `unlock_database` succeeds; thread and chat commands
+    /// are unavailable until then
+    pub unlocked: bool,
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.trim().is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_database_status(
+    profiles: State<'_, Arc<ProfileManager>>,
+    app_handle: AppHandle,
+) -> Result<DatabaseStatus, String> {
+    Ok(match app_handle.try_state::<Arc<ThreadManager>>() {
+        Some(thread_manager) => DatabaseStatus {
+            encrypted: thread_manager.is_encrypted(),
+            unlocked: true,
+        },
+        None => DatabaseStatus {
+            encrypted: Database::file_is_encrypted(&profiles.database_path(&profiles.active().id)),
+            unlocked: false,
+        },
+    })
+}
+
+/// Open the active profile's encrypted database with `passphrase`
+#[tauri::command]
+pub async fn unlock_database(
+    passphrase: String,
+    profiles: State<'_, Arc<ProfileManager>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    if app_handle.try_state::<Arc<ThreadManager>>().is_some() {
+        return Ok(());
+    }
+    println!("🔓 Unlocking database...");
+
+    let db_path = profiles.database_path(&profiles.active().id);
+    let database = Database::new(db_path, Some(&passphrase)).map_err(|e| {
+        if database::is_wrong_passphrase(&e) {
+            eprintln!("   ❌ Incorrect passphrase");
+            "Incorrect passphrase".to_string()
+        } else {
+            eprintln!("   ❌ Failed to open database: {}", e);
+            e.to_string()
+        }
+    })?;
+    app_handle.manage(Arc::new(ThreadManager::new(Arc::new(database))));
+
+    println!("   ✅ Database unlocked");
+    Ok(())
+}
+
+/// Encrypt the existing plaintext database in place. From the next launch
+/// on, `passphrase` is needed to open it.
+#[tauri::command]
+pub async fn encrypt_database(
+    passphrase: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    validate_passphrase(&passphrase)?;
+    if thread_manager.is_encrypted() {
+        return Err("Database is already encrypted".to_string());
+    }
+    println!("🔒 Encrypting database...");
+
+    thread_manager.encrypt_database(&passphrase).map_err(|e| {
+        eprintln!("   ❌ Encryption failed: {}", e);
+        e.to_string()
+    })?;
+
+    println!("   ✅ Database encrypted");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn change_database_passphrase(
+    current_passphrase: String,
+    new_passphrase: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    validate_passphrase(&new_passphrase)?;
+    println!("🔑 Changing database passphrase...");
+
+    thread_manager
+        .change_database_passphrase(&current_passphrase, &new_passphrase)
+        .map_err(|e| {
+            if database::is_wrong_passphrase(&e) {
+                "Incorrect passphrase".to_string()
+            } else {
+                e.to_string()
+            }
+        })?;
+
+    println!("   ✅ Passphrase changed");
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::managers::ThreadManager;
+use crate::models::ThreadFilters;
+use crate::services::export::{self, ExportBundle, ExportFormat, ExportedThread, ImageMode};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// Export one thread. Asks for a save location and returns the written
+/// path, or `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn export_thread(
+    thread_id: String,
+    format: ExportFormat,
+    image_mode: Option<ImageMode>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    app_handle: AppHandle,
+) -> Result<Option<String>, String> {
+    println!("📤 Exporting thread {} as {}", thread_id, format.label());
+
+    let exported = load_thread(&thread_manager, &thread_id)?;
+    let file_name = format!("{}.{}", export::safe_file_name(&exported.thread.name), format.extension());
+
+    let Some(path) = app_handle
+        .dialog()
+        .file()
+        .set_title("Export conversation")
+        .set_file_name(file_name)
+        .add_filter(format.label(), &[format.extension()])
+        .blocking_save_file()
+    else {
+        println!("   ⚠️  Export cancelled");
+        return Ok(None);
+    };
+    let path = path.into_path().map_err(|e| e.to_string())?;
+
+    if format == ExportFormat::Json {
+        let tags = thread_manager.list_tags().map_err(|e| e.to_string())?;
+        let tags = tags.into_iter().filter(|t| exported.thread.tags.contains(&t.id)).collect();
+        let bundle = ExportBundle::new(vec![exported], Vec::new(), tags);
+        std::fs::write(&path, bundle.to_json().map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+    } else {
+        export::write_thread(&path, &exported, format, image_mode.unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+    }
+
+    println!("   ✅ Exported to {}", path.display());
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Export every thread, archived ones included. JSON produces a single
+/// file; Markdown and HTML write one file per thread into a chosen folder.
+/// Returns the written path, or `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn export_all(
+    format: ExportFormat,
+    image_mode: Option<ImageMode>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    app_handle: AppHandle,
+) -> Result<Option<String>, String> {
+    println!("📤 Exporting all threads as {}", format.label());
+
+    let mut threads = Vec::new();
+    for archived in [false, true] {
+        let filters = ThreadFilters { archived, ..Default::default() };
+        for thread in thread_manager.list_threads(&filters).map_err(|e| e.to_string())? {
+            threads.push(load_thread(&thread_manager, &thread.id)?);
+        }
+    }
+
+    let count = threads.len();
+    let dialog = app_handle.dialog().file().set_title("Export all conversations");
+
+    let path = if format == ExportFormat::Json {
+        let Some(path) = dialog
+            .set_file_name(format!("seeva-export-{}.json", chrono::Local::now().format("%Y-%m-%d")))
+            .add_filter(format.label(), &["json"])
+            .blocking_save_file()
+        else {
+            return Ok(None);
+        };
+        let path = path.into_path().map_err(|e| e.to_string())?;
+
+        let folders = thread_manager.list_folders().map_err(|e| e.to_string())?;
+        let tags = thread_manager.list_tags().map_err(|e| e.to_string())?;
+        let bundle = ExportBundle::new(threads, folders, tags);
+        std::fs::write(&path, bundle.to_json().map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        path
+    } else {
+        let Some(dir) = dialog.blocking_pick_folder() else {
+            return Ok(None);
+        };
+        let dir = dir.into_path().map_err(|e| e.to_string())?;
+
+        let image_mode = image_mode.unwrap_or_default();
+        for exported in &threads {
+            let path = unique_path(&dir, &export::safe_file_name(&exported.thread.name), format.extension());
+            export::write_thread(&path, exported, format, image_mode).map_err(|e| e.to_string())?;
+        }
+        dir
+    };
+
+    println!("   ✅ Exported {} thread(s) to {}", count, path.display());
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+fn load_thread(thread_manager: &ThreadManager, thread_id: &str) -> Result<ExportedThread, String> {
+    let thread = thread_manager
+        .get_thread(thread_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Thread not found: {}", thread_id))?;
+    let messages = thread_manager.get_messages(thread_id).map_err(|e| e.to_string())?;
+
+    Ok(ExportedThread { thread, messages })
+}
+
+/// `dir/name.ext`, numbered if threads share a name
+fn unique_path(dir: &std::path::Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.{}", name, extension));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}).{}", name, n, extension));
+        n += 1;
+    }
+    path
+}
@@ -0,0 +1,47 @@
+use crate::managers::ThreadManager;
+use crate::services::import::{self, ImportReport};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// Import conversations from a ChatGPT or Claude export (`conversations.json`
+/// or the downloaded `.zip`) or a Seeva JSON export. Without `path` a file
+/// dialog is shown; returns `None` if it was cancelled.
+#[tauri::command]
+pub async fn import_conversations(
+    path: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    app_handle: AppHandle,
+) -> Result<Option<ImportReport>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let Some(path) = app_handle
+                .dialog()
+                .file()
+                .set_title("Import conversations")
+                .add_filter("Conversation export", &["json", "zip"])
+                .blocking_pick_file()
+            else {
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?
+        }
+    };
+    println!("📥 Importing conversations from {}", path.display());
+
+    let parsed = import::read_file(&path).map_err(|e| {
+        eprintln!("   ❌ Failed to read import: {}", e);
+        e.to_string()
+    })?;
+    let report = thread_manager
+        .import_conversations(parsed)
+        .map_err(|e| e.to_string())?;
+
+    println!(
+        "   ✅ Imported {}, skipped {}, failed {}",
+        report.imported, report.skipped, report.failed
+    );
+    Ok(Some(report))
+}
@@ -4,6 +4,14 @@ pub mod settings;
 pub mod screenshot;
 pub mod shortcut;
 pub mod context;
+pub mod search;
+pub mod export;
+pub mod import;
+pub mod backup;
+pub mod retention;
+pub mod trash;
+pub mod encryption;
+pub mod profiles;
 
 pub use threads::*;
 pub use chat::*;
@@ -11,3 +19,11 @@ pub use settings::*;
 pub use screenshot::*;
 pub use shortcut::*;
 pub use context::*;
+pub use search::*;
+pub use export::*;
+pub use import::*;
+pub use backup::*;
+pub use retention::*;
+pub use trash::*;
+pub use encryption::*;
+pub use profiles::*;
@@ -0,0 +1,133 @@
+use crate::commands::settings::SettingsState;
+use crate::managers::ThreadManager;
+use crate::services::database::{self, Database};
+use crate::services::profile_manager::Profile;
+use crate::services::{secret_store, ProfileManager, SettingsManager};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Emitted after `switch_profile` so the frontend reloads threads and settings
+pub const PROFILE_SWITCHED_EVENT: &str = "profile-switched";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+    pub active: String,
+}
+
+#[tauri::command]
+pub async fn list_profiles(profiles: State<'_, Arc<ProfileManager>>) -> Result<ProfileList, String> {
+    Ok(ProfileList {
+        profiles: profiles.list(),
+        active: profiles.active().id,
+    })
+}
+
+#[tauri::command]
+pub async fn create_profile(
+    name: String,
+    profiles: State<'_, Arc<ProfileManager>>,
+) -> Result<Profile, String> {
+    println!("👤 Creating profile '{}'", name);
+    profiles.create(&name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_profile(
+    id: String,
+    name: String,
+    profiles: State<'_, Arc<ProfileManager>>,
+) -> Result<Profile, String> {
+    profiles.rename(&id, &name).map_err(|e| e.to_string())
+}
+
+/// Delete a profile with its conversations, settings and API keys
+#[tauri::command]
+pub async fn delete_profile(
+    id: String,
+    profiles: State<'_, Arc<ProfileManager>>,
+) -> Result<(), String> {
+    profiles.get(&id).map_err(|e| e.to_string())?;
+    println!("🗑️  Deleting profile '{}'", id);
+
+    // Keyring entries live outside the profile directory
+    match secret_store::default_store(&profiles.profile_dir(&id), &profiles.keyring_service(&id)) {
+        Ok(secrets) => {
+            if let Err(e) = SettingsManager::delete_secrets(&profiles.settings_path(&id), secrets.as_ref()) {
+                eprintln!("   ⚠️  Failed to delete API keys: {}", e);
+            }
+        }
+        Err(e) => eprintln!("   ⚠️  Failed to open secret store: {}", e),
+    }
+
+    profiles.delete(&id).map_err(|e| e.to_string())?;
+    println!("   ✅ Profile deleted");
+    Ok(())
+}
+
+/// Make `id` the active profile: its database, settings and keys replace
+/// the current ones without a restart. `passphrase` opens an encrypted
+/// database; without it the profile stays locked until `unlock_database`,
+/// which is only possible while no database is open yet.
+#[tauri::command]
+pub async fn switch_profile(
+    id: String,
+    passphrase: Option<String>,
+    profiles: State<'_, Arc<ProfileManager>>,
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<Profile, String> {
+    let profile = profiles.get(&id).map_err(|e| e.to_string())?;
+    if profile.id == profiles.active().id {
+        return Ok(profile);
+    }
+    println!("👤 Switching to profile '{}'", profile.name);
+
+    let dir = profiles.profile_dir(&profile.id);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    // Open the database first so a wrong passphrase leaves everything as it was
+    let db_path = profiles.database_path(&profile.id);
+    let thread_manager = app_handle.try_state::<Arc<ThreadManager>>();
+    let database = if Database::file_is_encrypted(&db_path) && passphrase.is_none() {
+        if thread_manager.is_some() {
+            return Err("Passphrase required to open this profile".to_string());
+        }
+        None
+    } else {
+        let database = Database::new(db_path, passphrase.as_deref()).map_err(|e| {
+            if database::is_wrong_passphrase(&e) {
+                "Incorrect passphrase".to_string()
+            } else {
+                eprintln!("   ❌ Failed to open database: {}", e);
+                e.to_string()
+            }
+        })?;
+        Some(Arc::new(database))
+    };
+
+    let secrets = secret_store::default_store(&dir, &profiles.keyring_service(&profile.id))
+        .map_err(|e| e.to_string())?;
+    // Subscribers re-register the shortcut and the frontend reloads settings
+    settings
+        .switch_to(profiles.settings_path(&profile.id), secrets)
+        .map_err(|e| e.to_string())?;
+
+    match (database, thread_manager) {
+        (Some(database), Some(thread_manager)) => thread_manager.switch_database(database),
+        (Some(database), None) => {
+            app_handle.manage(Arc::new(ThreadManager::new(database)));
+        }
+        (None, _) => println!("   🔒 Database is encrypted, waiting for passphrase"),
+    }
+
+    profiles.set_active(&profile.id).map_err(|e| e.to_string())?;
+    if let Err(e) = app_handle.emit(PROFILE_SWITCHED_EVENT, &profile) {
+        eprintln!("   ⚠️  Failed to emit profile switch: {}", e);
+    }
+
+    println!("   ✅ Profile switched");
+    Ok(profile)
+}
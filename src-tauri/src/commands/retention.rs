@@ -0,0 +1,141 @@
+use crate::commands::settings::SettingsState;
+use crate::managers::ThreadManager;
+use crate::services::retention::{RetentionReport, RetentionRules};
+use crate::services::ProfileManager;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Emitted after a retention pass removed something, so the frontend
+/// reloads threads
+pub const RETENTION_APPLIED_EVENT: &str = "retention-applied";
+
+/// Retention runs once a day
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How often the scheduler checks whether a run is due, e.g. because the
+/// database was locked at startup
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+fn apply_retention(
+    thread_manager: &ThreadManager,
+    settings: &SettingsState,
+    dry_run: bool,
+) -> Result<RetentionReport, String> {
+    let config = settings.get().map_err(|e| e.to_string())?.retention;
+    let rules = RetentionRules::from_settings(&config, Utc::now().timestamp_millis());
+    if rules.is_empty() {
+        return Ok(RetentionReport {
+            dry_run,
+            ..Default::default()
+        });
+    }
+
+    thread_manager
+        .apply_retention(&rules, dry_run)
+        .map_err(|e| {
+            eprintln!("   ❌ Retention failed: {}", e);
+            e.to_string()
+        })
+}
+
+/// Report what the retention rules in settings would remove right now,
+/// without removing anything
+#[tauri::command]
+pub async fn preview_retention(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    settings: State<'_, SettingsState>,
+) -> Result<RetentionReport, String> {
+    apply_retention(&thread_manager, &settings, true)
+}
+
+/// Apply the retention rules now instead of waiting for the daily run
+#[tauri::command]
+pub async fn run_retention(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+    settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<RetentionReport, String> {
+    println!("🧹 Applying retention rules");
+    let report = apply_retention(&thread_manager, &settings, false)?;
+    report_removed(&app_handle, &report);
+    Ok(report)
+}
+
+fn report_removed(app_handle: &AppHandle, report: &RetentionReport) {
+    if !report.removed_anything() {
+        return;
+    }
+    println!(
+        "   ✅ Deleted {} thread(s) and {} image(s), {} KB -> {} KB",
+        report.deleted_threads.len(),
+        report.stripped_images,
+        report.bytes_before / 1024,
+        report.bytes_after / 1024
+    );
+    if let Err(e) = app_handle.emit(RETENTION_APPLIED_EVENT, report) {
+        eprintln!("   ⚠️  Failed to emit retention event: {}", e);
+    }
+}
+
+/// Whether a retention run last made at `last_run` is due again at `now`.
+/// Wall-clock time, so a day spent asleep still counts as a day.
+fn is_retention_due(last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    last_run.is_none_or(|at| {
+        (now - at)
+            .to_std()
+            .is_ok_and(|elapsed| elapsed >= RETENTION_INTERVAL)
+    })
+}
+
+/// Apply retention rules in the background at startup and then daily, for
+/// whichever profile is active
+pub fn start_retention_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Keyed by profile id, so switching profiles runs the new one's rules
+        let mut last_runs: HashMap<String, DateTime<Utc>> = HashMap::new();
+        loop {
+            let profile_id = app_handle.state::<Arc<ProfileManager>>().active().id;
+            let now = Utc::now();
+            let due = is_retention_due(last_runs.get(&profile_id).copied(), now);
+            // Not managed until an encrypted database has been unlocked
+            if let (true, Some(thread_manager)) =
+                (due, app_handle.try_state::<Arc<ThreadManager>>())
+            {
+                let settings = app_handle.state::<SettingsState>();
+                match apply_retention(&thread_manager, &settings, false) {
+                    Ok(report) => report_removed(&app_handle, &report),
+                    Err(e) => eprintln!("⚠️  Scheduled retention failed: {}", e),
+                }
+                last_runs.insert(profile_id, now);
+            }
+            tokio::time::sleep(RETENTION_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_due_after_a_day_of_wall_clock_time() {
+        let now = Utc::now();
+        assert!(is_retention_due(None, now));
+        assert!(!is_retention_due(
+            Some(now - chrono::Duration::hours(23)),
+            now
+        ));
+        assert!(is_retention_due(
+            Some(now - chrono::Duration::hours(24)),
+            now
+        ));
+        // A clock set back doesn't trigger extra runs
+        assert!(!is_retention_due(
+            Some(now + chrono::Duration::hours(1)),
+            now
+        ));
+    }
+}
@@ -1,13 +1,27 @@
+use crate::commands::settings::SettingsState;
 use crate::services::ScreenshotService;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager, State};
 
+/// Refuse to capture when screenshots are turned off in settings or by policy
+fn ensure_screenshots_enabled(settings: &SettingsState) -> Result<(), String> {
+    let enabled = settings.get().map_err(|e| e.to_string())?.enable_screenshots;
+    if enabled {
+        Ok(())
+    } else {
+        eprintln!("⛔ [SCREENSHOT] Screenshots are disabled in settings");
+        Err("Screenshots are disabled".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn capture_screenshot(
     app_handle: AppHandle,
     screenshot_service: State<'_, Arc<ScreenshotService>>,
+    settings: State<'_, SettingsState>,
 ) -> Result<String, String> {
     println!("🚀 [SCREENSHOT] Command invoked - starting screenshot capture process");
+    ensure_screenshots_enabled(&settings)?;
 
     // Get main window
     println!("🔍 [SCREENSHOT] Attempting to get main window reference...");
@@ -106,7 +120,9 @@ pub async fn capture_screenshot(
 #[tauri::command]
 pub async fn capture_all_screenshots(
     screenshot_service: State<'_, Arc<ScreenshotService>>,
+    settings: State<'_, SettingsState>,
 ) -> Result<Vec<String>, String> {
+    ensure_screenshots_enabled(&settings)?;
     screenshot_service
         .capture_all_screens()
         .map_err(|e| e.to_string())
@@ -116,7 +132,9 @@ pub async fn capture_all_screenshots(
 pub async fn capture_screen_by_index(
     index: usize,
     screenshot_service: State<'_, Arc<ScreenshotService>>,
+    settings: State<'_, SettingsState>,
 ) -> Result<String, String> {
+    ensure_screenshots_enabled(&settings)?;
     screenshot_service
         .capture_screen(index)
         .map_err(|e| e.to_string())
@@ -0,0 +1,24 @@
+use crate::managers::ThreadManager;
+use crate::models::{SearchFilters, SearchHit};
+use std::sync::Arc;
+use tauri::State;
+
+#[tauri::command]
+pub async fn search_messages(
+    query: String,
+    filters: Option<SearchFilters>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Vec<SearchHit>, String> {
+    println!("🔍 Searching messages for: {}", query);
+
+    match thread_manager.search_messages(&query, &filters.unwrap_or_default()) {
+        Ok(hits) => {
+            println!("   ✅ Found {} hit(s)", hits.len());
+            Ok(hits)
+        }
+        Err(e) => {
+            eprintln!("   ❌ Search failed: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
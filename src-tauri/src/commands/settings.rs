@@ -1,30 +1,55 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
+use crate::commands::shortcut::{register_global_shortcut, unregister_global_shortcut};
 use crate::services::ai::{AnthropicProvider, OpenAIProvider, OpenRouterProvider};
 use crate::services::ai::provider::AIProvider;
+use crate::services::settings_manager::{SettingsChange, SettingsError, SettingsWarning, SETTINGS_VERSION};
+use crate::services::settings_policy::PolicyInfo;
+use crate::services::settings_validator::{self, FieldError};
 use crate::services::SettingsManager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
+    /// Format version of `settings.json`; see `settings_manager::SETTINGS_VERSION`
+    #[serde(default)]
+    pub version: u32,
     pub default_provider: String,
-    pub anthropic: ProviderSettings,
-    pub openai: ProviderSettings,
-    pub openrouter: ProviderSettings,
-    pub gemini: ProviderSettings,
-    pub ollama: ProviderSettings,
+    /// Keyed by provider id: the built-in ids plus any the user added
+    pub providers: BTreeMap<String, ProviderSettings>,
     pub theme: String,
     pub shortcut: String,
     #[serde(default = "default_enable_context_detection")]
     pub enable_context_detection: bool,
+    /// Off blocks screen capture entirely, e.g. when locked by a policy
+    #[serde(default = "default_enable_screenshots")]
+    pub enable_screenshots: bool,
+    #[serde(default)]
+    pub backup: BackupSettings,
+    #[serde(default)]
+    pub retention: RetentionSettings,
 }
 
 fn default_enable_context_detection() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_enable_screenshots() -> bool {
+    true
+}
+
+/// Wire protocol a provider speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderApi {
+    Anthropic,
+    OpenAi,
+    OpenRouter,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProviderSettings {
     pub enabled: bool,
@@ -33,71 +58,313 @@ pub struct ProviderSettings {
     pub temperature: f32,
     pub max_tokens: u32,
     pub is_validated: bool,
+    /// Protocol to use; required for user-added providers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<ProviderApi>,
+    /// Overrides the provider's default API base URL, e.g. for a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Extra HTTP headers sent with every request
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Models offered besides the built-in list; the only ones for
+    /// user-added providers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// Set on settings sent to the frontend, where `api_key` is masked
+    #[serde(default, skip_deserializing, skip_serializing_if = "std::ops::Not::not")]
+    pub has_key: bool,
 }
 
-impl Default for AppSettings {
+/// Scheduled backups. Manual backups via `create_backup` work regardless.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSettings {
+    pub enabled: bool,
+    /// Defaults to `backups` in the app data directory
+    pub folder: Option<String>,
+    pub interval_hours: u32,
+    /// Number of scheduled backups to keep; older ones are deleted
+    pub keep: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: None,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+/// Automatic cleanup of old conversations; every rule is off when `None`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionSettings {
+    /// Delete threads not updated for this many days. Pinned threads are kept.
+    pub delete_threads_after_days: Option<u32>,
+    /// Remove images older than this many days, keeping the message text
+    pub strip_images_after_days: Option<u32>,
+    /// Delete the least recently updated threads while the database is larger
+    pub max_database_mb: Option<u32>,
+    /// Permanently delete items that have been in the trash this many days
+    #[serde(default = "default_purge_trash_after_days")]
+    pub purge_trash_after_days: Option<u32>,
+}
+
+fn default_purge_trash_after_days() -> Option<u32> {
+    Some(30)
+}
+
+impl Default for RetentionSettings {
     fn default() -> Self {
         Self {
+            delete_threads_after_days: None,
+            strip_images_after_days: None,
+            max_database_mb: None,
+            purge_trash_after_days: default_purge_trash_after_days(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Ids of the built-in providers
+    pub const PROVIDER_IDS: [&'static str; 5] = ["anthropic", "openai", "openrouter", "gemini", "ollama"];
+
+    /// Look up a provider's settings by id
+    pub fn provider(&self, id: &str) -> Option<&ProviderSettings> {
+        self.providers.get(id)
+    }
+
+    pub fn provider_mut(&mut self, id: &str) -> Option<&mut ProviderSettings> {
+        self.providers.get_mut(id)
+    }
+
+    /// Like `provider`, but an unknown id is an error
+    pub fn require_provider(&self, id: &str) -> Result<&ProviderSettings, SettingsError> {
+        self.provider(id)
+            .ok_or_else(|| SettingsError::UnknownProvider(id.to_string()))
+    }
+
+    /// Add defaults for any built-in provider missing from the map
+    pub fn fill_builtin_providers(&mut self) {
+        for (id, defaults) in ProviderSettings::builtin_defaults() {
+            self.providers.entry(id.to_string()).or_insert(defaults);
+        }
+    }
+
+    /// Copy safe to hand to the webview: API keys are replaced by a mask and
+    /// `has_key` says whether one is stored
+    pub fn masked(&self) -> AppSettings {
+        let mut masked = self.clone();
+        for provider in masked.providers.values_mut() {
+            *provider = provider.masked();
+        }
+        masked
+    }
+
+    /// Settings coming back from the webview carry masked keys; keep the
+    /// stored key wherever the mask was returned unchanged
+    pub fn restore_masked_keys(&mut self, current: &AppSettings) {
+        for (id, incoming) in self.providers.iter_mut() {
+            let Some(existing) = current.provider(id) else {
+                continue;
+            };
+            if !existing.api_key.is_empty() && incoming.api_key == mask_api_key(&existing.api_key) {
+                incoming.api_key = existing.api_key.clone();
+            }
+        }
+    }
+}
+
+impl ProviderSettings {
+    fn builtin(enabled: bool, default_model: &str) -> Self {
+        Self {
+            enabled,
+            api_key: String::new(),
+            default_model: default_model.to_string(),
+            temperature: 0.7,
+            max_tokens: 4096,
+            is_validated: false,
+            api: None,
+            base_url: None,
+            headers: BTreeMap::new(),
+            models: Vec::new(),
+            has_key: false,
+        }
+    }
+
+    fn builtin_defaults() -> [(&'static str, ProviderSettings); 5] {
+        [
+            ("anthropic", Self::builtin(true, "claude-sonnet-4-5-20250929")),
+            ("openai", Self::builtin(false, "gpt-5-mini")),
+            ("openrouter", Self::builtin(false, "anthropic/claude-3.5-sonnet")),
+            ("gemini", Self::builtin(false, "gemini-pro")),
+            ("ollama", Self::builtin(false, "llama2")),
+        ]
+    }
+
+    /// The API to talk to provider `id` with: the configured one, else the
+    /// built-in provider's own. `None` if there is no client for it.
+    pub fn api_for(&self, id: &str) -> Option<ProviderApi> {
+        self.api.or(match id {
+            "anthropic" => Some(ProviderApi::Anthropic),
+            "openai" => Some(ProviderApi::OpenAi),
+            "openrouter" => Some(ProviderApi::OpenRouter),
+            _ => None,
+        })
+    }
+
+    /// See `AppSettings::masked`
+    pub fn masked(&self) -> ProviderSettings {
+        ProviderSettings {
+            has_key: !self.api_key.is_empty(),
+            api_key: mask_api_key(&self.api_key),
+            ..self.clone()
+        }
+    }
+}
+
+/// Build a client for provider `id` from its settings
+pub fn build_client(
+    id: &str,
+    provider: &ProviderSettings,
+    api_key: String,
+) -> Result<Box<dyn AIProvider>, String> {
+    let base_url = provider.base_url.as_deref();
+    match provider.api_for(id) {
+        Some(ProviderApi::Anthropic) => Ok(Box::new(
+            AnthropicProvider::new(api_key).with_endpoint(base_url, &provider.headers),
+        )),
+        Some(ProviderApi::OpenAi) => Ok(Box::new(
+            OpenAIProvider::new(api_key).with_endpoint(base_url, &provider.headers),
+        )),
+        Some(ProviderApi::OpenRouter) => Ok(Box::new(
+            OpenRouterProvider::new(api_key).with_endpoint(base_url, &provider.headers),
+        )),
+        None => Err(format!("Unsupported provider: {}", id)),
+    }
+}
+
+/// Hide all but the first and last four characters of an API key
+pub fn mask_api_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.is_empty() {
+        return String::new();
+    }
+    if chars.len() <= 12 {
+        return "•".repeat(8);
+    }
+
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        let mut settings = Self {
+            version: SETTINGS_VERSION,
             default_provider: "anthropic".to_string(),
-            anthropic: ProviderSettings {
-                enabled: true,
-                api_key: String::new(),
-                default_model: "claude-sonnet-4-5-20250929".to_string(),
-                temperature: 0.7,
-                max_tokens: 4096,
-                is_validated: false,
-            },
-            openai: ProviderSettings {
-                enabled: false,
-                api_key: String::new(),
-                default_model: "gpt-5-mini".to_string(),
-                temperature: 0.7,
-                max_tokens: 4096,
-                is_validated: false,
-            },
-            openrouter: ProviderSettings {
-                enabled: false,
-                api_key: String::new(),
-                default_model: "anthropic/claude-3.5-sonnet".to_string(),
-                temperature: 0.7,
-                max_tokens: 4096,
-                is_validated: false,
-            },
-            gemini: ProviderSettings {
-                enabled: false,
-                api_key: String::new(),
-                default_model: "gemini-pro".to_string(),
-                temperature: 0.7,
-                max_tokens: 4096,
-                is_validated: false,
-            },
-            ollama: ProviderSettings {
-                enabled: false,
-                api_key: String::new(),
-                default_model: "llama2".to_string(),
-                temperature: 0.7,
-                max_tokens: 4096,
-                is_validated: false,
-            },
+            providers: BTreeMap::new(),
             theme: "dark".to_string(),
             shortcut: "Control+Shift+Space".to_string(),
             enable_context_detection: true,
-        }
+            enable_screenshots: true,
+            backup: BackupSettings::default(),
+            retention: RetentionSettings::default(),
+        };
+        settings.fill_builtin_providers();
+        settings
     }
 }
 
 pub type SettingsState = Arc<SettingsManager>;
 
+/// Emitted when settings had to be reset at startup, or an edited
+/// settings file couldn't be reloaded
+pub const SETTINGS_WARNING_EVENT: &str = "settings-warning";
+
+/// Emitted with the changed sections, API keys masked
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Subscribed to `SettingsManager` at startup: applies changes that need
+/// the backend to act and forwards them to the frontend
+pub fn on_settings_changed(app_handle: &AppHandle, changes: &[SettingsChange]) {
+    for change in changes {
+        if let SettingsChange::Shortcut { new, .. } = change {
+            let shortcut = new.clone();
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = if shortcut.is_empty() {
+                    unregister_global_shortcut(app_handle).await
+                } else {
+                    register_global_shortcut(shortcut, app_handle).await
+                };
+                if let Err(e) = result {
+                    eprintln!("Failed to apply shortcut change: {}", e);
+                }
+            });
+        }
+    }
+
+    let masked: Vec<SettingsChange> = changes.iter().map(SettingsChange::masked).collect();
+    if let Err(e) = app_handle.emit(SETTINGS_CHANGED_EVENT, masked) {
+        eprintln!("Failed to emit settings change: {}", e);
+    }
+}
+
+/// Warning from loading settings at startup, if any. The event is emitted
+/// before the webview is listening, so the frontend also asks on mount.
+/// Returns the warning only once.
+#[tauri::command]
+pub async fn take_settings_warning(
+    settings: State<'_, SettingsState>,
+) -> Result<Option<SettingsWarning>, String> {
+    Ok(settings.take_load_warning())
+}
+
+/// Returns settings with API keys masked; raw keys never leave the backend
 #[tauri::command]
 pub async fn get_settings(settings: State<'_, SettingsState>) -> Result<AppSettings, String> {
-    settings.get().map_err(|e| e.to_string())
+    settings
+        .get()
+        .map(|s| s.masked())
+        .map_err(|e| e.to_string())
 }
 
+/// Check settings without saving them, so the UI can flag individual fields
+#[tauri::command]
+pub async fn validate_settings(
+    mut new_settings: AppSettings,
+    settings: State<'_, SettingsState>,
+) -> Result<Vec<FieldError>, String> {
+    let current = settings.get().map_err(|e| e.to_string())?;
+    new_settings.restore_masked_keys(&current);
+    new_settings.fill_builtin_providers();
+    let mut errors = settings.policy_errors(&new_settings);
+    errors.extend(settings_validator::new_errors(&current, &new_settings));
+    Ok(errors)
+}
+
+/// Fields locked by the administrator's policy file, so the UI can disable them
+#[tauri::command]
+pub async fn get_settings_policy(settings: State<'_, SettingsState>) -> Result<PolicyInfo, String> {
+    Ok(settings.policy_info())
+}
+
+/// Replace all settings. Built-in providers missing from `new_settings` are
+/// added back. Invalid settings are refused; the error lists each field.
 #[tauri::command]
 pub async fn update_settings(
-    new_settings: AppSettings,
+    mut new_settings: AppSettings,
     settings: State<'_, SettingsState>,
 ) -> Result<(), String> {
+    let current = settings.get().map_err(|e| e.to_string())?;
+    new_settings.restore_masked_keys(&current);
+    new_settings.fill_builtin_providers();
     settings.update(new_settings).map_err(|e| e.to_string())
 }
 
@@ -106,6 +373,9 @@ pub async fn set_default_provider(
     provider: String,
     settings: State<'_, SettingsState>,
 ) -> Result<(), String> {
+    let current = settings.get().map_err(|e| e.to_string())?;
+    current.require_provider(&provider).map_err(|e| e.to_string())?;
+
     settings
         .update_field(|s| {
             s.default_provider = provider;
@@ -119,14 +389,14 @@ pub async fn set_api_key(
     api_key: String,
     settings: State<'_, SettingsState>,
 ) -> Result<(), String> {
+    let current = settings.get().map_err(|e| e.to_string())?;
+    current.require_provider(&provider).map_err(|e| e.to_string())?;
+
     settings
-        .update_field(|s| match provider.as_str() {
-            "anthropic" => s.anthropic.api_key = api_key.clone(),
-            "openai" => s.openai.api_key = api_key.clone(),
-            "openrouter" => s.openrouter.api_key = api_key.clone(),
-            "gemini" => s.gemini.api_key = api_key.clone(),
-            "ollama" => s.ollama.api_key = api_key.clone(),
-            _ => {}
+        .update_field(|s| {
+            if let Some(p) = s.provider_mut(&provider) {
+                p.api_key = api_key;
+            }
         })
         .map_err(|e| e.to_string())
 }
@@ -139,74 +409,58 @@ pub struct ValidationResult {
     pub default_model: String,
 }
 
+/// Validate a provider key. `api_key` is only needed for a key the user has
+/// just typed; when omitted (or still the mask from `get_settings`) the stored
+/// key is used.
 #[tauri::command]
 pub async fn validate_api_key(
     provider: String,
-    api_key: String,
+    api_key: Option<String>,
+    settings: State<'_, SettingsState>,
 ) -> Result<ValidationResult, String> {
-    match provider.as_str() {
-        "anthropic" => {
-            let anthropic = AnthropicProvider::new(api_key.clone());
-            let is_valid = anthropic
-                .validate_api_key(&api_key)
-                .await
-                .map_err(|e| format!("Validation failed: {}", e))?;
-
-            if is_valid {
-                let models = anthropic.available_models();
-                let default_model = models.first().unwrap_or(&"claude-sonnet-4-5-20250929".to_string()).clone();
-
-                Ok(ValidationResult {
-                    valid: true,
-                    available_models: models,
-                    default_model,
-                })
-            } else {
-                Err("Invalid API key".to_string())
-            }
-        }
-        "openai" => {
-            let openai = OpenAIProvider::new(api_key.clone());
-            let is_valid = openai
-                .validate_api_key(&api_key)
-                .await
-                .map_err(|e| format!("Validation failed: {}", e))?;
-
-            if is_valid {
-                let models = openai.available_models();
-                let default_model = models.first().unwrap_or(&"gpt-5-mini".to_string()).clone();
-
-                Ok(ValidationResult {
-                    valid: true,
-                    available_models: models,
-                    default_model,
-                })
-            } else {
-                Err("Invalid API key".to_string())
-            }
-        }
-        "openrouter" => {
-            let openrouter = OpenRouterProvider::new(api_key.clone());
-            let is_valid = openrouter
-                .validate_api_key(&api_key)
-                .await
-                .map_err(|e| format!("Validation failed: {}", e))?;
-
-            if is_valid {
-                let models = openrouter.available_models();
-                let default_model = models.first().unwrap_or(&"anthropic/claude-3.5-sonnet".to_string()).clone();
-
-                Ok(ValidationResult {
-                    valid: true,
-                    available_models: models,
-                    default_model,
-                })
-            } else {
-                Err("Invalid API key".to_string())
-            }
-        }
-        _ => Err(format!("Provider {} validation not implemented", provider)),
+    let current = settings.get().map_err(|e| e.to_string())?;
+    let config = current.require_provider(&provider).map_err(|e| e.to_string())?;
+
+    let stored_key = settings
+        .get_api_key(&provider)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    let api_key = match api_key {
+        Some(key) if !key.is_empty() && key != mask_api_key(&stored_key) => key,
+        _ if !stored_key.is_empty() => stored_key,
+        _ => return Err(format!("No API key configured for {}", provider)),
+    };
+
+    let client = build_client(&provider, config, api_key.clone())
+        .map_err(|_| format!("Provider {} validation not implemented", provider))?;
+    let is_valid = client
+        .validate_api_key(&api_key)
+        .await
+        .map_err(|e| format!("Validation failed: {}", e))?;
+    if !is_valid {
+        return Err("Invalid API key".to_string());
     }
+
+    // User-added providers only offer the models configured for them
+    let mut models = config.models.clone();
+    if AppSettings::PROVIDER_IDS.contains(&provider.as_str()) {
+        models.extend(
+            client
+                .available_models()
+                .into_iter()
+                .filter(|m| !config.models.contains(m)),
+        );
+    }
+    let default_model = models
+        .first()
+        .cloned()
+        .unwrap_or_else(|| config.default_model.clone());
+
+    Ok(ValidationResult {
+        valid: true,
+        available_models: models,
+        default_model,
+    })
 }
 
 #[tauri::command]
@@ -215,14 +469,14 @@ pub async fn set_validation_state(
     is_validated: bool,
     settings: State<'_, SettingsState>,
 ) -> Result<(), String> {
+    let current = settings.get().map_err(|e| e.to_string())?;
+    current.require_provider(&provider).map_err(|e| e.to_string())?;
+
     settings
-        .update_field(|s| match provider.as_str() {
-            "anthropic" => s.anthropic.is_validated = is_validated,
-            "openai" => s.openai.is_validated = is_validated,
-            "openrouter" => s.openrouter.is_validated = is_validated,
-            "gemini" => s.gemini.is_validated = is_validated,
-            "ollama" => s.ollama.is_validated = is_validated,
-            _ => {}
+        .update_field(|s| {
+            if let Some(p) = s.provider_mut(&provider) {
+                p.is_validated = is_validated;
+            }
         })
         .map_err(|e| e.to_string())
 }
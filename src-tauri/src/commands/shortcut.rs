@@ -3,6 +3,7 @@ use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 use std::str::FromStr;
 use crate::services::context_detector::ContextDetector;
 use crate::commands::settings::SettingsState;
+use crate::commands::threads::wipe_ephemeral_threads;
 
 #[cfg(target_os = "macos")]
 use tauri_nspanel::ManagerExt;
@@ -39,6 +40,7 @@ pub async fn register_global_shortcut(
                     if is_visible {
                         println!("👁️ Panel is visible, hiding it");
                         panel.hide();
+                        wipe_ephemeral_threads(app);
                     } else {
                         println!("👁️ Panel is hidden, showing it");
 
@@ -103,6 +105,7 @@ pub async fn register_global_shortcut(
                                 if let Err(e) = window.hide() {
                                     eprintln!("Failed to hide window: {}", e);
                                 }
+                                wipe_ephemeral_threads(app);
                             } else {
                                 // CONTEXT DETECTION: Detect context before showing window
                                 let app_handle = app.clone();
@@ -165,6 +168,7 @@ pub async fn register_global_shortcut(
                                 if let Err(e) = window.hide() {
                                     eprintln!("Failed to hide window: {}", e);
                                 }
+                                wipe_ephemeral_threads(app);
                             } else {
                                 // CONTEXT DETECTION: Detect context before showing window
                                 let app_handle = app.clone();
@@ -1,7 +1,10 @@
 use crate::managers::ThreadManager;
-use crate::models::Thread;
+use crate::models::{Folder, Tag, Thread, ThreadFilters};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Emitted after private threads were wiped, so the frontend drops them
+pub const EPHEMERAL_WIPED_EVENT: &str = "ephemeral-threads-wiped";
 
 #[tauri::command]
 pub async fn create_thread(
@@ -22,13 +25,53 @@ pub async fn create_thread(
     }
 }
 
+/// Start a private conversation that is never saved to disk
+#[tauri::command]
+pub async fn create_ephemeral_thread(
+    name: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Thread, String> {
+    println!("🕶️  Creating private thread: {}", name);
+
+    thread_manager.create_ephemeral_thread(name).map_err(|e| {
+        eprintln!("   ❌ Failed to create private thread: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub async fn list_ephemeral_threads(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Vec<Thread>, String> {
+    thread_manager.list_ephemeral_threads().map_err(|e| e.to_string())
+}
+
+/// Drop all private threads, e.g. when the window hides or the app quits
+pub fn wipe_ephemeral_threads(app_handle: &AppHandle) {
+    // Not managed until an encrypted database has been unlocked
+    let Some(thread_manager) = app_handle.try_state::<Arc<ThreadManager>>() else {
+        return;
+    };
+    match thread_manager.wipe_ephemeral() {
+        Ok(0) => {}
+        Ok(count) => {
+            println!("🕶️  Wiped {} private thread(s)", count);
+            if let Err(e) = app_handle.emit(EPHEMERAL_WIPED_EVENT, count) {
+                eprintln!("   ⚠️  Failed to emit wipe event: {}", e);
+            }
+        }
+        Err(e) => eprintln!("⚠️  Failed to wipe private threads: {}", e),
+    }
+}
+
 #[tauri::command]
 pub async fn list_threads(
+    filters: Option<ThreadFilters>,
     thread_manager: State<'_, Arc<ThreadManager>>,
 ) -> Result<Vec<Thread>, String> {
     println!("🧵 Listing all threads...");
 
-    match thread_manager.list_threads() {
+    match thread_manager.list_threads(&filters.unwrap_or_default()) {
         Ok(threads) => {
             println!("   ✅ Found {} thread(s)", threads.len());
             for (i, thread) in threads.iter().enumerate() {
@@ -51,6 +94,28 @@ pub async fn get_thread(
     thread_manager.get_thread(&id).map_err(|e| e.to_string())
 }
 
+/// Continue a thread in a new one from `message_id`, leaving the original
+/// as it is. The fork becomes the current thread.
+#[tauri::command]
+pub async fn fork_thread(
+    thread_id: String,
+    message_id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Thread, String> {
+    println!("🧵 Forking thread {} at message {}", thread_id, message_id);
+
+    match thread_manager.fork_thread(&thread_id, &message_id) {
+        Ok(thread) => {
+            println!("   ✅ Forked into: {} (ID: {})", thread.name, thread.id);
+            Ok(thread)
+        }
+        Err(e) => {
+            eprintln!("   ❌ Failed to fork thread: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn switch_thread(
     thread_id: String,
@@ -108,3 +173,164 @@ pub async fn get_current_thread_id(
     println!("🧵 Getting current thread ID: {:?}", current_id);
     Ok(current_id)
 }
+
+#[tauri::command]
+pub async fn set_thread_pinned(
+    id: String,
+    pinned: bool,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .set_thread_pinned(&id, pinned)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_thread_archived(
+    id: String,
+    archived: bool,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .set_thread_archived(&id, archived)
+        .map_err(|e| e.to_string())
+}
+
+/// Move a thread into a folder; `None` moves it to the top level
+#[tauri::command]
+pub async fn move_thread_to_folder(
+    id: String,
+    folder_id: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .move_thread(&id, folder_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_thread_tag(
+    thread_id: String,
+    tag_id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .add_thread_tag(&thread_id, &tag_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_thread_tag(
+    thread_id: String,
+    tag_id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .remove_thread_tag(&thread_id, &tag_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_folder(
+    name: String,
+    parent_id: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Folder, String> {
+    println!("📁 Creating folder: {}", name);
+
+    thread_manager
+        .create_folder(name, parent_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_folders(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Vec<Folder>, String> {
+    thread_manager.list_folders().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_folder(
+    id: String,
+    name: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .rename_folder(&id, &name)
+        .map_err(|e| e.to_string())
+}
+
+/// Re-parent a folder; `None` moves it to the top level
+#[tauri::command]
+pub async fn move_folder(
+    id: String,
+    parent_id: Option<String>,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager
+        .move_folder(&id, parent_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a folder and its subfolders; threads inside move to the top level
+#[tauri::command]
+pub async fn delete_folder(
+    id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    println!("📁 Deleting folder: {}", id);
+
+    thread_manager.delete_folder(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_tag(
+    name: String,
+    color: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Tag, String> {
+    validate_color(&color)?;
+
+    thread_manager
+        .create_tag(name, color)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_tags(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Vec<Tag>, String> {
+    thread_manager.list_tags().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_tag(
+    tag: Tag,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    validate_color(&tag.color)?;
+
+    thread_manager.update_tag(&tag).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_tag(
+    id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    thread_manager.delete_tag(&id).map_err(|e| e.to_string())
+}
+
+/// Tag colors are `#rrggbb` hex strings
+fn validate_color(color: &str) -> Result<(), String> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid tag color '{}', expected #rrggbb", color))
+    }
+}
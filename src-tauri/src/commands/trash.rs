@@ -0,0 +1,59 @@
+use crate::managers::ThreadManager;
+use crate::models::TrashItem;
+use std::sync::Arc;
+use tauri::State;
+
+/// Deleted threads and messages that can still be restored
+#[tauri::command]
+pub async fn list_trash(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<Vec<TrashItem>, String> {
+    thread_manager.list_trash().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_thread(
+    id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    println!("♻️  Restoring thread: {}", id);
+
+    thread_manager.restore_thread(&id).map_err(|e| {
+        eprintln!("   ❌ Failed to restore thread: {}", e);
+        e.to_string()
+    })
+}
+
+/// Restore a message, and its thread if that was deleted as well
+#[tauri::command]
+pub async fn restore_message(
+    id: String,
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<(), String> {
+    println!("♻️  Restoring message: {}", id);
+
+    thread_manager.restore_message(&id).map_err(|e| {
+        eprintln!("   ❌ Failed to restore message: {}", e);
+        e.to_string()
+    })
+}
+
+/// Permanently delete everything in the trash. Returns how many threads
+/// and messages were removed.
+#[tauri::command]
+pub async fn empty_trash(
+    thread_manager: State<'_, Arc<ThreadManager>>,
+) -> Result<usize, String> {
+    println!("🗑️  Emptying trash");
+
+    match thread_manager.empty_trash() {
+        Ok(purged) => {
+            println!("   ✅ Permanently deleted {} item(s)", purged);
+            Ok(purged)
+        }
+        Err(e) => {
+            eprintln!("   ❌ Failed to empty trash: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
@@ -5,9 +5,9 @@ mod commands;
 
 use commands::settings::SettingsState;
 use managers::ThreadManager;
-use services::{Database, ScreenshotService, SettingsManager};
+use services::{Database, ProfileManager, ScreenshotService, SettingsManager};
 use std::sync::Arc;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 // Define a custom Panel for macOS
 #[cfg(target_os = "macos")]
@@ -100,26 +100,64 @@ pub fn run() {
             // Create directory if it doesn't exist
             std::fs::create_dir_all(&app_dir).expect("Failed to create app directory");
 
-            // Initialize database
-            let db_path = app_dir.join("seeva.db");
-            let database = Arc::new(Database::new(db_path).expect("Failed to initialize database"));
+            // Open the profile that was active last; the frontend may switch
+            // to another one from its profile chooser
+            let profiles = Arc::new(ProfileManager::new(app_dir).expect("Failed to load profiles"));
+            let profile = profiles.active();
+            let profile_dir = profiles.profile_dir(&profile.id);
+            std::fs::create_dir_all(&profile_dir).expect("Failed to create profile directory");
+            println!("👤 Using profile '{}'", profile.name);
 
-            // Initialize ThreadManager
-            let thread_manager = Arc::new(ThreadManager::new(database));
+            // Initialize database. An encrypted one stays closed until the
+            // frontend calls `unlock_database` with the passphrase.
+            let db_path = profiles.database_path(&profile.id);
+            if Database::file_is_encrypted(&db_path) {
+                println!("🔒 Database is encrypted, waiting for passphrase");
+            } else {
+                let database = Arc::new(Database::new(db_path, None).expect("Failed to initialize database"));
+
+                // Initialize ThreadManager
+                app.manage(Arc::new(ThreadManager::new(database)));
+            }
 
             // Initialize ScreenshotService
             let screenshot_service = Arc::new(ScreenshotService::new());
 
+            // Initialize secret storage for API keys
+            let secrets = services::secret_store::default_store(&profile_dir, &profiles.keyring_service(&profile.id))
+                .expect("Failed to initialize secret store");
+
             // Initialize Settings with file persistence
-            let settings_path = app_dir.join("settings.json");
+            let settings_path = profiles.settings_path(&profile.id);
             let settings: SettingsState = Arc::new(
-                SettingsManager::new(settings_path).expect("Failed to initialize settings")
+                SettingsManager::new(settings_path, secrets).expect("Failed to initialize settings")
             );
 
+            if let Some(warning) = settings.load_warning() {
+                let _ = app.handle().emit(commands::settings::SETTINGS_WARNING_EVENT, warning);
+            }
+
+            // React to settings changes, including edits made to settings.json directly
+            let app_handle = app.handle().clone();
+            settings.subscribe(move |changes| commands::settings::on_settings_changed(&app_handle, changes));
+            let app_handle = app.handle().clone();
+            settings.subscribe_warnings(move |warning| {
+                let _ = app_handle.emit(commands::settings::SETTINGS_WARNING_EVENT, warning);
+            });
+            if let Err(e) = settings.watch() {
+                eprintln!("⚠️  Settings won't reload on external edits: {}", e);
+            }
+
             // Manage state
-            app.manage(thread_manager);
             app.manage(screenshot_service);
             app.manage(settings.clone());
+            app.manage(profiles);
+
+            // Take scheduled backups if enabled in settings
+            commands::start_backup_scheduler(app.handle().clone());
+
+            // Apply retention rules now and once a day
+            commands::start_retention_scheduler(app.handle().clone());
 
             // Register global shortcut from settings
             let app_handle = app.handle().clone();
@@ -138,18 +176,70 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Thread commands
             commands::create_thread,
+            commands::create_ephemeral_thread,
+            commands::list_ephemeral_threads,
+            commands::fork_thread,
             commands::list_threads,
             commands::get_thread,
             commands::switch_thread,
             commands::delete_thread,
             commands::update_thread_name,
             commands::get_current_thread_id,
+            commands::set_thread_pinned,
+            commands::set_thread_archived,
+            commands::move_thread_to_folder,
+            commands::add_thread_tag,
+            commands::remove_thread_tag,
+            // Folder and tag commands
+            commands::create_folder,
+            commands::list_folders,
+            commands::rename_folder,
+            commands::move_folder,
+            commands::delete_folder,
+            commands::create_tag,
+            commands::list_tags,
+            commands::update_tag,
+            commands::delete_tag,
             // Chat commands
             commands::send_message,
+            commands::continue_message,
+            commands::send_message_multi,
             commands::get_messages,
+            commands::get_image,
             commands::delete_message,
+            // Search commands
+            commands::search_messages,
+            // Export commands
+            commands::export_thread,
+            commands::export_all,
+            commands::import_conversations,
+            // Backup commands
+            commands::create_backup,
+            commands::restore_backup,
+            // Retention commands
+            commands::preview_retention,
+            commands::run_retention,
+            // Trash commands
+            commands::list_trash,
+            commands::restore_thread,
+            commands::restore_message,
+            commands::empty_trash,
+            // Database encryption commands
+            commands::get_database_status,
+            commands::unlock_database,
+            commands::encrypt_database,
+            commands::change_database_passphrase,
+            // Profile commands
+            commands::list_profiles,
+            commands::create_profile,
+            commands::rename_profile,
+            commands::delete_profile,
+            commands::switch_profile,
             // Settings commands
             commands::get_settings,
+            commands::take_settings_warning,
+            commands::validate_settings,
+            commands::get_settings_policy,
             commands::update_settings,
             commands::set_default_provider,
             commands::set_api_key,
@@ -166,6 +256,12 @@ pub fn run() {
             // Context commands
             commands::detect_screen_context,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Private threads must not outlive the session
+            if let tauri::RunEvent::Exit = event {
+                commands::wipe_ephemeral_threads(app_handle);
+            }
+        });
 }
@@ -1,25 +1,76 @@
-use std::sync::{Arc, Mutex};
-use crate::models::{Message, Thread, MessageRole};
+use std::sync::{Arc, Mutex, RwLock};
+use crate::models::{
+    Folder, ImageData, Message, MessagePage, MessageRole, SearchFilters, SearchHit, Tag, Thread,
+    ThreadFilters, TrashItem,
+};
+use crate::services::import::{
+    ImportReport, ImportResult, ImportedConversation, ParsedImport, IMPORT_ID_KEY, IMPORT_SOURCE_KEY,
+};
+use crate::services::backup::{self, BackupError, BackupManifest, ExtractedBackup};
+use crate::services::retention::{RetentionReport, RetentionRules};
+use crate::services::database::misuse;
 use crate::services::Database;
+use std::path::Path;
+use std::collections::{HashMap, HashSet};
 use rusqlite::Result;
 
+/// Largest page `get_messages_page` returns
+pub const MAX_MESSAGE_PAGE: u32 = 500;
+
+/// Metadata keys recording where a forked thread came from
+pub const FORK_THREAD_KEY: &str = "forkedFromThread";
+pub const FORK_MESSAGE_KEY: &str = "forkedFromMessage";
+
 pub struct ThreadManager {
-    db: Arc<Database>,
+    /// Replaced when the user switches profile
+    db: RwLock<Arc<Database>>,
+    /// Private threads; never written to disk and wiped when the window hides
+    ephemeral: RwLock<Arc<Database>>,
     current_thread_id: Arc<Mutex<Option<String>>>,
 }
 
 impl ThreadManager {
     pub fn new(db: Arc<Database>) -> Self {
         Self {
-            db,
+            db: RwLock::new(db),
+            ephemeral: RwLock::new(Arc::new(
+                Database::in_memory().expect("Failed to create in-memory database"),
+            )),
             current_thread_id: Arc::new(Mutex::new(None)),
         }
     }
 
+    fn db(&self) -> Arc<Database> {
+        self.db.read().unwrap().clone()
+    }
+
+    fn ephemeral(&self) -> Arc<Database> {
+        self.ephemeral.read().unwrap().clone()
+    }
+
+    /// The database holding `thread_id`, private or not
+    fn store(&self, thread_id: &str) -> Result<Arc<Database>> {
+        let ephemeral = self.ephemeral();
+        if ephemeral.get_thread(thread_id)?.is_some() {
+            Ok(ephemeral)
+        } else {
+            Ok(self.db())
+        }
+    }
+
+    /// Work on another profile's database from now on
+    pub fn switch_database(&self, db: Arc<Database>) {
+        *self.db.write().unwrap() = db;
+        *self.current_thread_id.lock().unwrap() = None;
+        if let Err(e) = self.wipe_ephemeral() {
+            eprintln!("⚠️  Failed to wipe private threads: {}", e);
+        }
+    }
+
     // Thread operations
     pub fn create_thread(&self, name: String) -> Result<Thread> {
         let thread = Thread::new(name);
-        self.db.create_thread(&thread)?;
+        self.db().create_thread(&thread)?;
 
         // Set as current thread
         let mut current = self.current_thread_id.lock().unwrap();
@@ -28,17 +79,74 @@ impl ThreadManager {
         Ok(thread)
     }
 
-    pub fn list_threads(&self) -> Result<Vec<Thread>> {
-        self.db.list_threads()
+    /// Start a private thread that is kept in memory only
+    pub fn create_ephemeral_thread(&self, name: String) -> Result<Thread> {
+        let mut thread = Thread::new(name);
+        self.ephemeral().create_thread(&thread)?;
+        thread.ephemeral = true;
+
+        *self.current_thread_id.lock().unwrap() = Some(thread.id.clone());
+        Ok(thread)
+    }
+
+    /// Saved threads only; private threads are listed separately
+    pub fn list_threads(&self, filters: &ThreadFilters) -> Result<Vec<Thread>> {
+        self.db().list_threads(filters)
+    }
+
+    pub fn list_ephemeral_threads(&self) -> Result<Vec<Thread>> {
+        let mut threads = self.ephemeral().list_threads(&ThreadFilters::default())?;
+        for thread in &mut threads {
+            thread.ephemeral = true;
+        }
+        Ok(threads)
     }
 
     pub fn get_thread(&self, id: &str) -> Result<Option<Thread>> {
-        self.db.get_thread(id)
+        if let Some(mut thread) = self.ephemeral().get_thread(id)? {
+            thread.ephemeral = true;
+            return Ok(Some(thread));
+        }
+        self.db().get_thread(id)
+    }
+
+    /// Drop every private thread, clearing the current thread if it was one.
+    /// Returns how many were dropped.
+    pub fn wipe_ephemeral(&self) -> Result<usize> {
+        let fresh = Arc::new(Database::in_memory()?);
+        let old = std::mem::replace(&mut *self.ephemeral.write().unwrap(), fresh);
+
+        let mut current = self.current_thread_id.lock().unwrap();
+        if let Some(id) = current.as_deref() {
+            if old.get_thread(id)?.is_some() {
+                *current = None;
+            }
+        }
+        Ok(old.list_threads(&ThreadFilters::default())?.len())
+    }
+
+    /// Start a new thread from a copy of `thread_id` up to and including
+    /// `message_id`, and make it current. Forks of private threads are
+    /// private too.
+    pub fn fork_thread(&self, thread_id: &str, message_id: &str) -> Result<Thread> {
+        let db = self.store(thread_id)?;
+        let source = db.get_thread(thread_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        let mut fork = Thread::new(format!("{} (fork)", source.name));
+        fork.folder_id = source.folder_id;
+        fork.metadata = Some(HashMap::from([
+            (FORK_THREAD_KEY.to_string(), serde_json::Value::from(thread_id)),
+            (FORK_MESSAGE_KEY.to_string(), serde_json::Value::from(message_id)),
+        ]));
+        db.fork_thread(thread_id, message_id, &fork)?;
+
+        *self.current_thread_id.lock().unwrap() = Some(fork.id.clone());
+        self.get_thread(&fork.id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
     }
 
     pub fn switch_thread(&self, thread_id: String) -> Result<()> {
         // Verify thread exists
-        match self.db.get_thread(&thread_id)? {
+        match self.get_thread(&thread_id)? {
             Some(_) => {
                 let mut current = self.current_thread_id.lock().unwrap();
                 *current = Some(thread_id);
@@ -52,8 +160,14 @@ impl ThreadManager {
         self.current_thread_id.lock().unwrap().clone()
     }
 
+    /// Move a thread to the trash. Private threads are dropped right away.
     pub fn delete_thread(&self, id: &str) -> Result<()> {
-        self.db.delete_thread(id)?;
+        let ephemeral = self.ephemeral();
+        if ephemeral.get_thread(id)?.is_some() {
+            ephemeral.delete_thread(id)?;
+        } else {
+            self.db().trash_thread(id, chrono::Utc::now().timestamp_millis())?;
+        }
 
         // Clear current thread if it was deleted
         let mut current = self.current_thread_id.lock().unwrap();
@@ -65,16 +179,232 @@ impl ThreadManager {
     }
 
     pub fn update_thread_name(&self, id: &str, name: String) -> Result<()> {
-        if let Some(mut thread) = self.db.get_thread(id)? {
+        let db = self.store(id)?;
+        if let Some(mut thread) = db.get_thread(id)? {
             thread.name = name;
             thread.updated_at = chrono::Utc::now().timestamp_millis();
-            self.db.update_thread(&thread)?;
+            db.update_thread(&thread)?;
             Ok(())
         } else {
             Err(rusqlite::Error::QueryReturnedNoRows)
         }
     }
 
+    pub fn set_thread_pinned(&self, id: &str, pinned: bool) -> Result<()> {
+        self.require_thread(id)?.set_thread_pinned(id, pinned)
+    }
+
+    pub fn set_thread_archived(&self, id: &str, archived: bool) -> Result<()> {
+        self.require_thread(id)?.set_thread_archived(id, archived)
+    }
+
+    /// Move a thread into a folder, or to the top level with `None`
+    pub fn move_thread(&self, id: &str, folder_id: Option<&str>) -> Result<()> {
+        self.require_saved_thread(id)?;
+        self.db().set_thread_folder(id, folder_id)
+    }
+
+    pub fn add_thread_tag(&self, thread_id: &str, tag_id: &str) -> Result<()> {
+        self.require_saved_thread(thread_id)?;
+        self.db().add_thread_tag(thread_id, tag_id)
+    }
+
+    pub fn remove_thread_tag(&self, thread_id: &str, tag_id: &str) -> Result<()> {
+        self.require_saved_thread(thread_id)?;
+        self.db().remove_thread_tag(thread_id, tag_id)
+    }
+
+    /// The database holding thread `id`, private or not; fails if there is
+    /// no such thread
+    fn require_thread(&self, id: &str) -> Result<Arc<Database>> {
+        let db = self.store(id)?;
+        db.get_thread(id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        Ok(db)
+    }
+
+    /// Like `require_thread`, for folder and tag changes. Folders and tags
+    /// belong to the profile's database, so private threads can't use them.
+    fn require_saved_thread(&self, id: &str) -> Result<()> {
+        if self.ephemeral().get_thread(id)?.is_some() {
+            return Err(misuse("Private threads can't be moved into folders or tagged"));
+        }
+        self.db().get_thread(id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        Ok(())
+    }
+
+    // Folder operations
+    pub fn create_folder(&self, name: String, parent_id: Option<String>) -> Result<Folder> {
+        let folder = Folder::new(name, parent_id);
+        self.db().create_folder(&folder)?;
+        Ok(folder)
+    }
+
+    pub fn list_folders(&self) -> Result<Vec<Folder>> {
+        self.db().list_folders()
+    }
+
+    pub fn rename_folder(&self, id: &str, name: &str) -> Result<()> {
+        self.db().rename_folder(id, name)
+    }
+
+    /// Re-parent a folder; fails if `parent_id` is the folder or inside it
+    pub fn move_folder(&self, id: &str, parent_id: Option<&str>) -> Result<()> {
+        self.db().set_folder_parent(id, parent_id)
+    }
+
+    pub fn folder_contains(&self, ancestor_id: &str, folder_id: &str) -> Result<bool> {
+        self.db().folder_contains(ancestor_id, folder_id)
+    }
+
+    pub fn delete_folder(&self, id: &str) -> Result<()> {
+        self.db().delete_folder(id)
+    }
+
+    // Tag operations
+    pub fn create_tag(&self, name: String, color: String) -> Result<Tag> {
+        let tag = Tag::new(name, color);
+        self.db().create_tag(&tag)?;
+        Ok(tag)
+    }
+
+    pub fn list_tags(&self) -> Result<Vec<Tag>> {
+        self.db().list_tags()
+    }
+
+    pub fn update_tag(&self, tag: &Tag) -> Result<()> {
+        self.db().update_tag(tag)
+    }
+
+    pub fn delete_tag(&self, id: &str) -> Result<()> {
+        self.db().delete_tag(id)
+    }
+
+    // Encryption
+    pub fn is_encrypted(&self) -> bool {
+        self.db().is_encrypted()
+    }
+
+    pub fn encrypt_database(&self, passphrase: &str) -> Result<()> {
+        self.db().encrypt(passphrase)
+    }
+
+    pub fn change_database_passphrase(&self, current: &str, new: &str) -> Result<()> {
+        self.db().change_passphrase(current, new)
+    }
+
+    // Backup
+    pub fn create_backup(&self, settings_json: &str, dest: &Path) -> std::result::Result<BackupManifest, BackupError> {
+        backup::create_backup(&self.db(), settings_json, dest)
+    }
+
+    /// Replace all threads and messages with those from a backup
+    pub fn restore_backup(&self, backup: &ExtractedBackup) -> Result<()> {
+        self.db().restore_from(backup.database_path())?;
+        *self.current_thread_id.lock().unwrap() = None;
+        Ok(())
+    }
+
+    // Trash
+    pub fn list_trash(&self) -> Result<Vec<TrashItem>> {
+        self.db().list_trash()
+    }
+
+    pub fn restore_thread(&self, id: &str) -> Result<()> {
+        if self.db().restore_thread(id)? {
+            Ok(())
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }
+
+    pub fn restore_message(&self, id: &str) -> Result<()> {
+        if self.db().restore_message(id)? {
+            Ok(())
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }
+
+    /// Permanently delete the trash and return the space to the filesystem
+    pub fn empty_trash(&self) -> Result<usize> {
+        let db = self.db();
+        let purged = db.empty_trash()?;
+        if purged > 0 {
+            db.vacuum()?;
+        }
+        Ok(purged)
+    }
+
+    // Retention
+    /// Apply retention rules, keeping the current thread. Unless this is a
+    /// dry run, the file is vacuumed afterwards if anything was removed.
+    pub fn apply_retention(&self, rules: &RetentionRules, dry_run: bool) -> Result<RetentionReport> {
+        let db = self.db();
+        let current = self.get_current_thread_id();
+        let report = db.apply_retention(rules, current.as_deref(), dry_run)?;
+        if !dry_run && report.removed_anything() {
+            db.vacuum()?;
+        }
+        Ok(report)
+    }
+
+    // Import
+    /// Store parsed conversations, skipping any imported before. Each
+    /// conversation is written in its own transaction.
+    pub fn import_conversations(&self, parsed: ParsedImport) -> Result<ImportReport> {
+        for folder in &parsed.folders {
+            self.db().insert_folder_if_missing(folder)?;
+        }
+        let folder_ids: HashSet<String> = self.db().list_folders()?.into_iter().map(|f| f.id).collect();
+
+        let mut results = Vec::new();
+        for conversation in parsed.conversations {
+            if let Some(existing) = self.find_duplicate(&conversation)? {
+                results.push(ImportResult::skipped(&conversation, existing));
+                continue;
+            }
+
+            let mut thread = conversation.thread.clone();
+            if thread.folder_id.as_ref().is_some_and(|id| !folder_ids.contains(id)) {
+                thread.folder_id = None;
+            }
+            let tag_ids = conversation
+                .tags
+                .iter()
+                .map(|tag| match self.db().find_tag_by_name(&tag.name)? {
+                    Some(existing) => Ok(existing.id),
+                    None => self.db().create_tag(tag).map(|_| tag.id.clone()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            match self.db().import_thread(&thread, &conversation.messages, &tag_ids) {
+                Ok(_) => results.push(ImportResult::imported(&conversation)),
+                Err(e) => results.push(ImportResult::failed(thread.name, e)),
+            }
+        }
+        results.extend(parsed.failures);
+
+        Ok(ImportReport::new(parsed.source, results))
+    }
+
+    /// Id of an existing thread the conversation was already imported as
+    fn find_duplicate(&self, conversation: &ImportedConversation) -> Result<Option<String>> {
+        let thread = &conversation.thread;
+        if self.db().get_thread(&thread.id)?.is_some() {
+            return Ok(Some(thread.id.clone()));
+        }
+
+        let import_key = thread.metadata.as_ref().and_then(|m| {
+            let source = m.get(IMPORT_SOURCE_KEY)?.as_str()?;
+            let id = m.get(IMPORT_ID_KEY)?.as_str()?;
+            Some((source, id))
+        });
+        match import_key {
+            Some((source, id)) => self.db().find_imported_thread(source, id),
+            None => Ok(None),
+        }
+    }
+
     // Message operations
     pub fn add_message(
         &self,
@@ -84,56 +414,110 @@ impl ThreadManager {
         images: Option<Vec<String>>,
     ) -> Result<Message> {
         // Verify thread exists
-        let thread = self.db.get_thread(&thread_id)?
+        let db = self.store(&thread_id)?;
+        db.get_thread(&thread_id)?
             .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
 
         // Create message
         let mut message = Message::new(thread_id.clone(), role, content);
         message.images = images;
 
-        self.db.create_message(&message)?;
+        db.create_message(&message)?;
 
         // Update thread timestamp
-        let mut updated_thread = thread;
-        updated_thread.updated_at = chrono::Utc::now().timestamp_millis();
-        self.db.update_thread(&updated_thread)?;
+        db.touch_thread(&thread_id, chrono::Utc::now().timestamp_millis())?;
 
         Ok(message)
     }
 
     pub fn get_messages(&self, thread_id: &str) -> Result<Vec<Message>> {
-        self.db.get_messages(thread_id)
+        self.store(thread_id)?.get_messages(thread_id)
+    }
+
+    /// `limit` is clamped to `1..=MAX_MESSAGE_PAGE`, so paging always ends
+    pub fn get_messages_page(&self, thread_id: &str, before: Option<&str>, limit: u32) -> Result<MessagePage> {
+        let limit = limit.clamp(1, MAX_MESSAGE_PAGE);
+        self.store(thread_id)?.get_messages_page(thread_id, before, limit)
     }
 
+    pub fn get_message(&self, id: &str) -> Result<Option<Message>> {
+        if let Some(message) = self.ephemeral().get_message(id)? {
+            return Ok(Some(message));
+        }
+        self.db().get_message(id)
+    }
+
+    pub fn get_image(&self, id: &str) -> Result<Option<ImageData>> {
+        if let Some(image) = self.ephemeral().get_image(id)? {
+            return Ok(Some(image));
+        }
+        self.db().get_image(id)
+    }
+
+    /// Move a message to the trash, or drop it if its thread is private
     pub fn delete_message(&self, id: &str) -> Result<()> {
-        self.db.delete_message(id)
+        let ephemeral = self.ephemeral();
+        if ephemeral.get_message(id)?.is_some() {
+            ephemeral.delete_message(id)
+        } else {
+            self.db().trash_message(id, chrono::Utc::now().timestamp_millis())
+        }
+    }
+
+    /// Remove a message for good, skipping the trash. For taking back a
+    /// message that was saved before its request failed.
+    pub fn discard_message(&self, message: &Message) -> Result<()> {
+        self.store(&message.thread_id)?.delete_message(&message.id)
+    }
+
+    pub fn search_messages(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>> {
+        self.db().search_messages(query, filters)
+    }
+
+    pub fn update_message(&self, message: &Message) -> Result<()> {
+        let db = self.store(&message.thread_id)?;
+        self.update_message_in(&db, message)
     }
 
     pub fn create_message(&self, message: &Message) -> Result<()> {
-        self.db.create_message(message)?;
+        let db = self.store(&message.thread_id)?;
+        self.create_message_in(&db, message)
+    }
 
-        // Update thread timestamp
-        if let Some(thread) = self.db.get_thread(&message.thread_id)? {
-            let mut updated_thread = thread;
-            updated_thread.updated_at = chrono::Utc::now().timestamp_millis();
-            self.db.update_thread(&updated_thread)?;
-        }
+    /// The database `thread_id` lives in right now. Work that outlives a
+    /// command, like a streamed reply, holds on to it so a profile switch
+    /// midway can't send its writes to another profile.
+    pub fn thread_store(&self, thread_id: &str) -> Result<Arc<Database>> {
+        self.store(thread_id)
+    }
 
+    /// `update_message` against a database from `thread_store`
+    pub fn update_message_in(&self, db: &Database, message: &Message) -> Result<()> {
+        // A trashed message is left alone, and so is its thread
+        if db.update_message(message)? {
+            db.touch_thread(&message.thread_id, chrono::Utc::now().timestamp_millis())?;
+        }
         Ok(())
     }
 
+    /// `create_message` against a database from `thread_store`
+    pub fn create_message_in(&self, db: &Database, message: &Message) -> Result<()> {
+        db.create_message(message)?;
+        db.touch_thread(&message.thread_id, chrono::Utc::now().timestamp_millis())
+    }
+
     // Utility methods
     #[allow(dead_code)]
     pub fn ensure_thread_exists(&self) -> Result<String> {
         // Check if current thread exists
         if let Some(thread_id) = self.get_current_thread_id() {
-            if self.db.get_thread(&thread_id)?.is_some() {
+            if self.get_thread(&thread_id)?.is_some() {
                 return Ok(thread_id);
             }
         }
 
         // Get or create default thread
-        let threads = self.db.list_threads()?;
+        let threads = self.db().list_threads(&ThreadFilters::default())?;
         if let Some(first_thread) = threads.first() {
             let thread_id = first_thread.id.clone();
             let mut current = self.current_thread_id.lock().unwrap();
@@ -150,12 +534,13 @@ impl ThreadManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::TrashKind;
     use tempfile::TempDir;
 
     fn setup_test_db() -> (Arc<Database>, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
-        let db = Database::new(db_path).unwrap();
+        let db = Database::new(db_path, None).unwrap();
         (Arc::new(db), temp_dir)
     }
 
@@ -211,6 +596,79 @@ mod tests {
         assert_eq!(thread.name, "New Conversation");
     }
 
+    #[test]
+    fn test_update_message_replaces_content_and_metadata() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let thread = manager.create_thread("Test".to_string()).unwrap();
+        let mut message = manager.add_message(
+            thread.id.clone(),
+            MessageRole::Assistant,
+            "Partial".to_string(),
+            None,
+        ).unwrap();
+
+        message.content = "Partial answer".to_string();
+        message.metadata = Some(std::collections::HashMap::from([(
+            "status".to_string(),
+            serde_json::json!("incomplete"),
+        )]));
+        manager.update_message(&message).unwrap();
+
+        let loaded = manager.get_message(&message.id).unwrap().unwrap();
+        assert_eq!(loaded.content, "Partial answer");
+        assert_eq!(
+            loaded.metadata.unwrap().get("status"),
+            Some(&serde_json::json!("incomplete"))
+        );
+        assert!(manager.get_message("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_delete_message_moves_to_trash_and_restores() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let thread = manager.create_thread("Test".to_string()).unwrap();
+        let mut message = manager
+            .add_message(thread.id.clone(), MessageRole::Assistant, "Partial".to_string(), None)
+            .unwrap();
+
+        // Updates from a stream still running don't touch a trashed message
+        let before = manager.get_thread(&thread.id).unwrap().unwrap().updated_at;
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        manager.delete_message(&message.id).unwrap();
+        assert!(manager.get_message(&message.id).unwrap().is_none());
+        message.content = "Partial, continued".to_string();
+        manager.update_message(&message).unwrap();
+        manager.restore_message(&message.id).unwrap();
+        assert_eq!(manager.get_message(&message.id).unwrap().unwrap().content, "Partial");
+        assert_eq!(manager.get_thread(&thread.id).unwrap().unwrap().updated_at, before);
+
+        // Restoring a message whose thread was trashed too brings the thread back
+        manager.delete_message(&message.id).unwrap();
+        manager.delete_thread(&thread.id).unwrap();
+        assert!(manager.get_thread(&thread.id).unwrap().is_none());
+        manager.restore_message(&message.id).unwrap();
+        assert!(manager.get_thread(&thread.id).unwrap().is_some());
+        assert_eq!(manager.get_messages(&thread.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_discard_message_skips_trash() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let thread = manager.create_thread("Test".to_string()).unwrap();
+        let message = manager
+            .add_message(thread.id.clone(), MessageRole::User, "Unanswered".to_string(), None)
+            .unwrap();
+        manager.discard_message(&message).unwrap();
+        assert!(manager.get_messages(&thread.id).unwrap().is_empty());
+        assert!(manager.list_trash().unwrap().is_empty());
+    }
+
     #[test]
     fn test_delete_thread_clears_current() {
         let (db, _temp) = setup_test_db();
@@ -222,4 +680,507 @@ mod tests {
         manager.delete_thread(&thread.id).unwrap();
         assert_eq!(manager.get_current_thread_id(), None);
     }
+
+    #[test]
+    fn test_search_messages() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let thread = manager.create_thread("Containers".to_string()).unwrap();
+        manager.add_message(
+            thread.id.clone(),
+            MessageRole::User,
+            "How do Docker volumes work?".to_string(),
+            None,
+        ).unwrap();
+        let answer = manager.add_message(
+            thread.id.clone(),
+            MessageRole::Assistant,
+            "Volumes persist data outside the container.".to_string(),
+            None,
+        ).unwrap();
+
+        let hits = manager.search_messages("volume", &SearchFilters::default()).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].thread_name, "Containers");
+        assert!(hits.iter().any(|h| h.snippet.contains("<mark>")));
+
+        let filters = SearchFilters {
+            role: Some("assistant".to_string()),
+            ..Default::default()
+        };
+        let hits = manager.search_messages("volumes", &filters).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message_id, answer.id);
+
+        // Thread names are searchable and follow renames
+        manager.update_thread_name(&thread.id, "Docker notes".to_string()).unwrap();
+        assert_eq!(manager.search_messages("notes", &SearchFilters::default()).unwrap().len(), 2);
+
+        // FTS syntax in user input is treated as plain text
+        assert!(manager.search_messages("\"volumes OR", &SearchFilters::default()).is_ok());
+
+        manager.delete_message(&answer.id).unwrap();
+        assert_eq!(manager.search_messages("container", &SearchFilters::default()).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_identical_images_share_one_blob() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let manager = ThreadManager::new(Arc::new(Database::new(db_path.clone(), None).unwrap()));
+        let blob_count = || -> i64 {
+            rusqlite::Connection::open(&db_path)
+                .unwrap()
+                .query_row("SELECT COUNT(*) FROM image_blobs", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        let img = image::RgbImage::from_pixel(32, 16, image::Rgb([0, 120, 255]));
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(img)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let screenshot = crate::services::images::to_base64(&png);
+
+        let thread = manager.create_thread("Screens".to_string()).unwrap();
+        let first = manager.add_message(
+            thread.id.clone(),
+            MessageRole::User,
+            "Look".to_string(),
+            Some(vec![screenshot.clone()]),
+        ).unwrap();
+        let second = manager.add_message(
+            thread.id.clone(),
+            MessageRole::User,
+            "Again".to_string(),
+            Some(vec![screenshot.clone()]),
+        ).unwrap();
+        assert_eq!(blob_count(), 1);
+
+        let loaded = manager.get_message(&second.id).unwrap().unwrap();
+        assert_eq!(loaded.images, Some(vec![screenshot.clone()]));
+
+        // Pages carry references; data is loaded separately
+        let page = manager.get_messages_page(&thread.id, None, 10).unwrap();
+        assert!(page.messages.iter().all(|m| m.images.is_none()));
+        let image_ref = &page.messages[0].image_refs.as_ref().unwrap()[0];
+        assert_eq!(image_ref.mime_type, "image/png");
+        assert_eq!((image_ref.width, image_ref.height), (Some(32), Some(16)));
+        let image = manager.get_image(&image_ref.id).unwrap().unwrap();
+        assert_eq!(image.data, screenshot);
+        assert!(manager.get_thread(&thread.id).unwrap().unwrap().thumbnail.is_some());
+
+        // The blob goes away with its last reference, once out of the trash
+        manager.delete_message(&first.id).unwrap();
+        manager.empty_trash().unwrap();
+        assert_eq!(blob_count(), 1);
+        manager.delete_message(&second.id).unwrap();
+        assert_eq!(blob_count(), 1);
+        manager.empty_trash().unwrap();
+        assert_eq!(blob_count(), 0);
+    }
+
+    #[test]
+    fn test_get_messages_page() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let thread = manager.create_thread("Paged".to_string()).unwrap();
+        let ids: Vec<String> = (0..5)
+            .map(|i| {
+                manager.add_message(thread.id.clone(), MessageRole::User, format!("m{}", i), None)
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
+        let page = manager.get_messages_page(&thread.id, None, 2).unwrap();
+        assert!(page.has_more);
+        assert_eq!(page.messages.iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), ["m3", "m4"]);
+
+        let page = manager.get_messages_page(&thread.id, Some(&ids[3]), 2).unwrap();
+        assert!(page.has_more);
+        assert_eq!(page.messages[0].id, ids[1]);
+
+        let page = manager.get_messages_page(&thread.id, Some(&ids[1]), 2).unwrap();
+        assert!(!page.has_more);
+        assert_eq!(page.messages.len(), 1);
+
+        // Out-of-range limits are clamped rather than looping or overflowing
+        let page = manager.get_messages_page(&thread.id, None, 0).unwrap();
+        assert_eq!(page.messages.len(), 1);
+        assert!(page.has_more);
+        let page = manager.get_messages_page(&thread.id, None, u32::MAX).unwrap();
+        assert_eq!(page.messages.len(), 5);
+        assert!(!page.has_more);
+    }
+
+    #[test]
+    fn test_list_threads_filters_and_pins() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let work = manager.create_folder("Work".to_string(), None).unwrap();
+        let infra = manager.create_folder("Infra".to_string(), Some(work.id.clone())).unwrap();
+        let tag = manager.create_tag("Urgent".to_string(), "#ef4444".to_string()).unwrap();
+
+        let old = manager.create_thread("Old".to_string()).unwrap();
+        let nested = manager.create_thread("Nested".to_string()).unwrap();
+        let archived = manager.create_thread("Archived".to_string()).unwrap();
+        manager.create_thread("Newest".to_string()).unwrap();
+
+        manager.set_thread_pinned(&old.id, true).unwrap();
+        manager.move_thread(&nested.id, Some(&infra.id)).unwrap();
+        manager.add_thread_tag(&nested.id, &tag.id).unwrap();
+        manager.set_thread_archived(&archived.id, true).unwrap();
+
+        // Pinned first, archived hidden
+        let names = |threads: Vec<Thread>| threads.into_iter().map(|t| t.name).collect::<Vec<_>>();
+        let all = manager.list_threads(&ThreadFilters::default()).unwrap();
+        assert_eq!(all[0].id, old.id);
+        assert!(all[0].pinned);
+        assert_eq!(names(all), ["Old", "Newest", "Nested"]);
+
+        // Folder filter includes nested folders
+        let filters = ThreadFilters { folder_id: Some(work.id.clone()), ..Default::default() };
+        let in_work = manager.list_threads(&filters).unwrap();
+        assert_eq!(names(in_work.clone()), ["Nested"]);
+        assert_eq!(in_work[0].tags, vec![tag.id.clone()]);
+
+        let filters = ThreadFilters { tag_id: Some(tag.id.clone()), ..Default::default() };
+        assert_eq!(names(manager.list_threads(&filters).unwrap()), ["Nested"]);
+
+        let filters = ThreadFilters { archived: true, ..Default::default() };
+        assert_eq!(names(manager.list_threads(&filters).unwrap()), ["Archived"]);
+
+        assert!(manager.folder_contains(&work.id, &infra.id).unwrap());
+        assert!(!manager.folder_contains(&infra.id, &work.id).unwrap());
+
+        // A folder can't move into itself or its own subtree
+        assert!(manager.move_folder(&work.id, Some(&work.id)).is_err());
+        assert!(manager.move_folder(&work.id, Some(&infra.id)).is_err());
+        let folders = manager.list_folders().unwrap();
+        assert_eq!(folders.iter().find(|f| f.id == work.id).unwrap().parent_id, None);
+
+        // Tag changes on a missing thread are rejected, like other thread updates
+        assert!(manager.remove_thread_tag("missing", &tag.id).is_err());
+
+        // Deleting a folder moves its threads to the top level
+        manager.delete_folder(&work.id).unwrap();
+        assert!(manager.list_folders().unwrap().is_empty());
+        assert_eq!(manager.get_thread(&nested.id).unwrap().unwrap().folder_id, None);
+    }
+
+    #[test]
+    fn test_import_skips_duplicates_and_keeps_timestamps() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let export = r#"[{
+            "uuid": "c-1",
+            "name": "Imported",
+            "created_at": "2024-05-01T10:00:00Z",
+            "updated_at": "2024-05-01T10:05:00Z",
+            "chat_messages": [
+                {"sender": "human", "text": "Hello", "created_at": "2024-05-01T10:00:01Z"},
+                {"sender": "assistant", "text": "Hi there", "created_at": "2024-05-01T10:00:02Z"}
+            ]
+        }]"#;
+
+        let report = manager
+            .import_conversations(crate::services::import::parse(export).unwrap())
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.failed), (1, 0, 0));
+
+        let thread_id = report.results[0].thread_id.clone().unwrap();
+        let thread = manager.get_thread(&thread_id).unwrap().unwrap();
+        assert_eq!(thread.updated_at, 1_714_557_900_000);
+        let messages = manager.get_messages(&thread_id).unwrap();
+        assert_eq!(messages[1].created_at, 1_714_557_602_000);
+
+        let report = manager
+            .import_conversations(crate::services::import::parse(export).unwrap())
+            .unwrap();
+        assert_eq!((report.imported, report.skipped), (0, 1));
+        assert_eq!(report.results[0].thread_id, Some(thread_id.clone()));
+
+        // A trashed copy is replaced rather than counted as a duplicate
+        manager.delete_thread(&thread_id).unwrap();
+        let report = manager
+            .import_conversations(crate::services::import::parse(export).unwrap())
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.failed), (1, 0, 0));
+        assert!(manager.list_trash().unwrap().is_empty());
+        assert_eq!(manager.list_threads(&ThreadFilters::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_switch_database_separates_threads() {
+        let (work_db, _work) = setup_test_db();
+        let (personal_db, _personal) = setup_test_db();
+        let manager = ThreadManager::new(work_db);
+        let work_thread = manager.create_thread("Work".to_string()).unwrap();
+
+        manager.switch_database(personal_db);
+        assert_eq!(manager.get_current_thread_id(), None);
+        assert!(manager.get_thread(&work_thread.id).unwrap().is_none());
+        assert!(manager.list_threads(&Default::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_held_store_survives_profile_switch() {
+        let (work_db, _work) = setup_test_db();
+        let (personal_db, _personal) = setup_test_db();
+        let manager = ThreadManager::new(work_db.clone());
+        let thread = manager.create_thread("Work".to_string()).unwrap();
+
+        // A reply started streaming, then the user switched profiles
+        let store = manager.thread_store(&thread.id).unwrap();
+        manager.switch_database(personal_db.clone());
+
+        let reply = Message::new(thread.id.clone(), MessageRole::Assistant, "Done".to_string());
+        manager.create_message_in(&store, &reply).unwrap();
+        assert_eq!(work_db.get_messages(&thread.id).unwrap()[0].content, "Done");
+        assert!(personal_db.get_messages(&thread.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_retention_dry_run_then_apply() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db.clone());
+        let day = 24 * 60 * 60 * 1000;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let mut stale = manager.create_thread("Stale".to_string()).unwrap();
+        stale.updated_at = now - 90 * day;
+        db.update_thread(&stale).unwrap();
+        let mut pinned = manager.create_thread("Pinned".to_string()).unwrap();
+        pinned.updated_at = now - 90 * day;
+        db.update_thread(&pinned).unwrap();
+        manager.set_thread_pinned(&pinned.id, true).unwrap();
+
+        let current = manager.create_thread("Current".to_string()).unwrap();
+        let png = crate::services::images::to_base64(&[0x89, b'P', b'N', b'G']);
+        let message = manager
+            .add_message(current.id.clone(), MessageRole::User, "Screenshot".to_string(), Some(vec![png]))
+            .unwrap();
+
+        let rules = RetentionRules {
+            threads_before: Some(now - 30 * day),
+            images_before: Some(now + day),
+            max_bytes: None,
+            trash_before: None,
+        };
+        let preview = manager.apply_retention(&rules, true).unwrap();
+        assert_eq!(preview.deleted_threads.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["Stale"]);
+        assert_eq!(preview.stripped_images, 1);
+        assert!(manager.get_thread(&stale.id).unwrap().is_some());
+
+        let report = manager.apply_retention(&rules, false).unwrap();
+        assert_eq!(report.deleted_threads.len(), 1);
+        assert!(manager.get_thread(&stale.id).unwrap().is_none());
+        assert!(manager.get_thread(&pinned.id).unwrap().is_some());
+        let kept = manager.get_message(&message.id).unwrap().unwrap();
+        assert_eq!(kept.content, "Screenshot");
+        assert!(kept.images.is_none());
+
+        // The size cap deletes old threads but never the current one
+        let capped = RetentionRules { max_bytes: Some(1), ..Default::default() };
+        let report = manager.apply_retention(&capped, false).unwrap();
+        assert!(report.deleted_threads.is_empty());
+        assert!(manager.get_thread(&current.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_retention_keeps_images_of_pinned_threads() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+        let now = chrono::Utc::now().timestamp_millis();
+        let png = crate::services::images::to_base64(&[0x89, b'P', b'N', b'G']);
+
+        let pinned = manager.create_thread("Pinned".to_string()).unwrap();
+        manager.set_thread_pinned(&pinned.id, true).unwrap();
+        let kept = manager
+            .add_message(pinned.id.clone(), MessageRole::User, "Keep".to_string(), Some(vec![png.clone()]))
+            .unwrap();
+        let other = manager.create_thread("Other".to_string()).unwrap();
+        let stripped = manager
+            .add_message(other.id.clone(), MessageRole::User, "Strip".to_string(), Some(vec![png]))
+            .unwrap();
+
+        let rules = RetentionRules { images_before: Some(now + 1000), ..Default::default() };
+        let report = manager.apply_retention(&rules, false).unwrap();
+        assert_eq!(report.stripped_images, 1);
+        assert_eq!(manager.get_message(&kept.id).unwrap().unwrap().images.map(|i| i.len()), Some(1));
+        assert!(manager.get_message(&stripped.id).unwrap().unwrap().images.is_none());
+    }
+
+    #[test]
+    fn test_ephemeral_threads_stay_in_memory() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db.clone());
+        let saved = manager.create_thread("Saved".to_string()).unwrap();
+
+        let private = manager.create_ephemeral_thread("Private".to_string()).unwrap();
+        assert!(private.ephemeral);
+        assert_eq!(manager.get_current_thread_id(), Some(private.id.clone()));
+
+        let message = manager
+            .add_message(private.id.clone(), MessageRole::User, "Secret".to_string(), None)
+            .unwrap();
+        assert_eq!(manager.get_messages(&private.id).unwrap().len(), 1);
+        assert!(manager.get_thread(&private.id).unwrap().unwrap().ephemeral);
+        assert!(manager.get_message(&message.id).unwrap().is_some());
+
+        // Nothing reaches the database file or the thread list
+        assert!(db.get_thread(&private.id).unwrap().is_none());
+        assert!(db.get_message(&message.id).unwrap().is_none());
+        let listed = manager.list_threads(&ThreadFilters::default()).unwrap();
+        assert_eq!(listed.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec![saved.id.as_str()]);
+        assert_eq!(manager.list_ephemeral_threads().unwrap().len(), 1);
+
+        assert_eq!(manager.wipe_ephemeral().unwrap(), 1);
+        assert!(manager.get_thread(&private.id).unwrap().is_none());
+        assert!(manager.list_ephemeral_threads().unwrap().is_empty());
+        assert_eq!(manager.get_current_thread_id(), None);
+    }
+
+    #[test]
+    fn test_private_threads_can_be_pinned_and_archived() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db.clone());
+        let private = manager.create_ephemeral_thread("Private".to_string()).unwrap();
+
+        manager.set_thread_pinned(&private.id, true).unwrap();
+        manager.set_thread_archived(&private.id, true).unwrap();
+        let thread = manager.get_thread(&private.id).unwrap().unwrap();
+        assert!(thread.pinned);
+        assert!(thread.archived);
+        assert!(db.get_thread(&private.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_private_threads_reject_folders_and_tags() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+        let private = manager.create_ephemeral_thread("Private".to_string()).unwrap();
+        let folder = manager.create_folder("Work".to_string(), None).unwrap();
+        let tag = manager.create_tag("urgent".to_string(), "#ff0000".to_string()).unwrap();
+
+        let err = manager.move_thread(&private.id, Some(&folder.id)).unwrap_err();
+        assert!(err.to_string().contains("Private threads"));
+        assert!(manager.add_thread_tag(&private.id, &tag.id).is_err());
+        assert!(manager.remove_thread_tag(&private.id, &tag.id).is_err());
+        assert_eq!(manager.get_thread(&private.id).unwrap().unwrap().folder_id, None);
+    }
+
+    #[test]
+    fn test_trash_restore_and_purge() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db.clone());
+
+        let thread = manager.create_thread("Trashed".to_string()).unwrap();
+        let message = manager
+            .add_message(thread.id.clone(), MessageRole::User, "Hello".to_string(), None)
+            .unwrap();
+        let kept = manager.create_thread("Kept".to_string()).unwrap();
+        let removed = manager
+            .add_message(kept.id.clone(), MessageRole::User, "Oops".to_string(), None)
+            .unwrap();
+
+        // Deleted items disappear from the app but stay in the trash
+        manager.delete_thread(&thread.id).unwrap();
+        manager.delete_message(&removed.id).unwrap();
+        assert!(manager.get_thread(&thread.id).unwrap().is_none());
+        assert!(manager.get_messages(&kept.id).unwrap().is_empty());
+        assert_eq!(manager.get_thread(&kept.id).unwrap().unwrap().message_count, Some(0));
+        let names: Vec<String> = manager
+            .list_threads(&ThreadFilters::default())
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, ["Kept"]);
+
+        let trash = manager.list_trash().unwrap();
+        assert_eq!(trash.len(), 2);
+        let trashed_message = trash.iter().find(|item| item.kind == TrashKind::Message).unwrap();
+        assert_eq!(trashed_message.preview.as_deref(), Some("Oops"));
+        assert_eq!(trashed_message.thread_name, "Kept");
+
+        manager.restore_thread(&thread.id).unwrap();
+        assert_eq!(manager.get_messages(&thread.id).unwrap()[0].id, message.id);
+        assert!(manager.restore_thread(&thread.id).is_err());
+        manager.delete_message(&message.id).unwrap();
+        manager.restore_message(&message.id).unwrap();
+        assert_eq!(manager.get_messages(&thread.id).unwrap().len(), 1);
+
+        // A message that isn't in the trash doesn't bring back its thread
+        manager.delete_thread(&thread.id).unwrap();
+        assert!(manager.restore_message(&message.id).is_err());
+        assert!(manager.get_thread(&thread.id).unwrap().is_none());
+        manager.restore_thread(&thread.id).unwrap();
+
+        // Emptying the trash deletes for good
+        assert_eq!(manager.empty_trash().unwrap(), 1);
+        assert!(manager.list_trash().unwrap().is_empty());
+        assert!(manager.restore_message(&removed.id).is_err());
+
+        // Purging by age keeps recent trash
+        manager.delete_thread(&thread.id).unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        let rules = RetentionRules { trash_before: Some(now - 1000), ..Default::default() };
+        assert_eq!(manager.apply_retention(&rules, false).unwrap().purged_trash, 0);
+        let rules = RetentionRules { trash_before: Some(now + 1000), ..Default::default() };
+        assert_eq!(manager.apply_retention(&rules, false).unwrap().purged_trash, 1);
+        assert!(db.list_trash().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fork_thread_copies_history_up_to_message() {
+        let (db, _temp) = setup_test_db();
+        let manager = ThreadManager::new(db);
+
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(4, 4))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let screenshot = crate::services::images::to_base64(&png);
+
+        let source = manager.create_thread("Research".to_string()).unwrap();
+        let question = manager
+            .add_message(source.id.clone(), MessageRole::User, "Look".to_string(), Some(vec![screenshot.clone()]))
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let answer = manager
+            .add_message(source.id.clone(), MessageRole::Assistant, "A cat".to_string(), None)
+            .unwrap();
+        // Distinct timestamps keep the order, and so the cut, deterministic
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        manager
+            .add_message(source.id.clone(), MessageRole::User, "Later".to_string(), None)
+            .unwrap();
+
+        let fork = manager.fork_thread(&source.id, &answer.id).unwrap();
+        assert_eq!(fork.name, "Research (fork)");
+        assert_eq!(manager.get_current_thread_id(), Some(fork.id.clone()));
+        let metadata = fork.metadata.unwrap();
+        assert_eq!(metadata[FORK_THREAD_KEY], source.id.as_str());
+        assert_eq!(metadata[FORK_MESSAGE_KEY], answer.id.as_str());
+
+        let copied = manager.get_messages(&fork.id).unwrap();
+        assert_eq!(copied.iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), ["Look", "A cat"]);
+        assert_ne!(copied[0].id, question.id);
+        assert_eq!(copied[0].created_at, question.created_at);
+        assert_eq!(copied[0].images, Some(vec![screenshot]));
+
+        // The source is untouched, and deleting it keeps the fork's images
+        assert_eq!(manager.get_messages(&source.id).unwrap().len(), 3);
+        manager.delete_thread(&source.id).unwrap();
+        manager.empty_trash().unwrap();
+        assert!(manager.get_message(&copied[0].id).unwrap().unwrap().images.is_some());
+
+        assert!(manager.fork_thread(&fork.id, &question.id).is_err());
+    }
 }
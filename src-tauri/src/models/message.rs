@@ -37,12 +37,42 @@ pub struct Message {
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub images: Option<Vec<String>>, // Base64 encoded
+    /// Stored images, without data; fetch each with `get_image`
+    #[serde(skip_serializing_if = "Option::is_none", rename = "imageRefs", default)]
+    pub image_refs: Option<Vec<ImageRef>>,
     #[serde(rename = "createdAt")]
     pub created_at: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageRef {
+    pub id: String,
+    pub mime_type: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub byte_size: i64,
+}
+
+/// A stored image with its base64 data
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageData {
+    #[serde(flatten)]
+    pub info: ImageRef,
+    pub data: String,
+}
+
+/// One page of a thread's messages, oldest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagePage {
+    pub messages: Vec<Message>,
+    /// Whether older messages exist before the first one in this page
+    pub has_more: bool,
+}
+
 impl Message {
     pub fn new(thread_id: String, role: MessageRole, content: String) -> Self {
         Self {
@@ -51,6 +81,7 @@ impl Message {
             role,
             content,
             images: None,
+            image_refs: None,
             created_at: chrono::Utc::now().timestamp_millis(),
             metadata: None,
         }
@@ -1,5 +1,9 @@
 pub mod message;
 pub mod thread;
+pub mod search;
+pub mod trash;
 
-pub use message::{Message, MessageRole};
-pub use thread::Thread;
+pub use message::{ImageData, ImageRef, Message, MessagePage, MessageRole};
+pub use thread::{Folder, Tag, Thread, ThreadFilters};
+pub use search::{SearchFilters, SearchHit};
+pub use trash::{TrashItem, TrashKind};
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Optional filters for full-text search. Dates are Unix millis.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFilters {
+    pub role: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub message_id: String,
+    pub thread_id: String,
+    pub thread_name: String,
+    pub role: String,
+    /// Matching excerpt with hits wrapped in `<mark>` tags
+    pub snippet: String,
+    pub created_at: i64,
+    /// BM25 score; lower is a better match
+    pub rank: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Thread {
@@ -12,6 +13,23 @@ pub struct Thread {
     pub message_count: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "lastMessage")]
     pub last_message: Option<String>,
+    /// Base64 JPEG thumbnail of the thread's most recent image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "folderId")]
+    pub folder_id: Option<String>,
+    /// Ids of the tags attached to this thread
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// Private thread kept in memory only; see `ThreadManager::create_ephemeral_thread`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ephemeral: bool,
 }
 
 impl Thread {
@@ -24,6 +42,66 @@ impl Thread {
             updated_at: now,
             message_count: None,
             last_message: None,
+            thumbnail: None,
+            pinned: false,
+            archived: false,
+            folder_id: None,
+            tags: Vec::new(),
+            metadata: None,
+            ephemeral: false,
         }
     }
 }
+
+/// A folder for organizing threads; folders nest via `parent_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "parentId")]
+    pub parent_id: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+impl Folder {
+    pub fn new(name: String, parent_id: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            parent_id,
+            created_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// A named color label that can be attached to any number of threads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    /// Hex color, e.g. `#3b82f6`
+    pub color: String,
+}
+
+impl Tag {
+    pub fn new(name: String, color: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            color,
+        }
+    }
+}
+
+/// Filters for `list_threads`. Archived threads are only listed when
+/// `archived` is set, and then exclusively.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadFilters {
+    /// Only threads in this folder or any folder nested below it
+    pub folder_id: Option<String>,
+    pub tag_id: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+}
@@ -0,0 +1,22 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Thread,
+    Message,
+}
+
+/// A deleted thread, or a deleted message of a thread that isn't
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    pub id: String,
+    pub thread_id: String,
+    pub thread_name: String,
+    /// Start of the message content; `None` for threads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    pub deleted_at: i64,
+}
@@ -1,19 +1,35 @@
 use async_trait::async_trait;
 use futures::StreamExt;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::provider::{
     AIError, AIProvider, ChatMessage, ChatRequest, StreamEvent, StreamResult, TokenUsage,
 };
 
-const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
+/// Default base URL; `with_endpoint` can point the client elsewhere
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_PATH: &str = "/messages";
 const ANTHROPIC_API_VERSION: &str = "2023-06-01";
 
+/// Output token limit of a known model
+pub fn max_output_tokens(model: &str) -> Option<u32> {
+    match model {
+        "claude-sonnet-4-5-20250929" | "claude-haiku-4-5-20251001" => Some(64000),
+        "claude-sonnet-4-20250514" | "claude-3-7-sonnet-20250219" => Some(64000),
+        "claude-opus-4-1-20250805" => Some(32000),
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct AnthropicProvider {
     api_key: String,
     client: Client,
+    api_url: String,
+    extra_headers: HeaderMap,
 }
 
 #[derive(Debug, Serialize)]
@@ -67,6 +83,8 @@ struct AnthropicResponse {
 
 #[derive(Debug, Deserialize)]
 struct AnthropicUsage {
+    // `message_delta` events only report output tokens
+    #[serde(default)]
     input_tokens: u32,
     output_tokens: u32,
 }
@@ -115,7 +133,6 @@ enum ContentDelta {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct MessageDeltaData {
     stop_reason: Option<String>,
 }
@@ -133,9 +150,20 @@ impl AnthropicProvider {
         Self {
             api_key,
             client: Client::new(),
+            api_url: format!("{}{}", ANTHROPIC_BASE_URL, ANTHROPIC_PATH),
+            extra_headers: HeaderMap::new(),
         }
     }
 
+    /// Use a different base URL and send extra headers with every request
+    pub fn with_endpoint(mut self, base_url: Option<&str>, headers: &BTreeMap<String, String>) -> Self {
+        if let Some(base_url) = base_url {
+            self.api_url = format!("{}{}", base_url.trim_end_matches('/'), ANTHROPIC_PATH);
+        }
+        self.extra_headers = super::provider::header_map(headers);
+        self
+    }
+
     fn convert_messages(&self, messages: Vec<ChatMessage>) -> Vec<AnthropicMessage> {
         messages
             .into_iter()
@@ -174,10 +202,11 @@ impl AnthropicProvider {
     async fn send_request(&self, request: AnthropicRequest) -> Result<reqwest::Response, AIError> {
         let response = self
             .client
-            .post(ANTHROPIC_API_URL)
+            .post(&self.api_url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", ANTHROPIC_API_VERSION)
             .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
             .json(&request)
             .send()
             .await?;
@@ -198,6 +227,31 @@ impl AnthropicProvider {
     }
 }
 
+/// Map an Anthropic SSE event onto the provider-neutral stream event
+fn convert_stream_event(event: AnthropicStreamEvent) -> Option<StreamEvent> {
+    match event {
+        AnthropicStreamEvent::MessageStart { .. } => Some(StreamEvent::MessageStart),
+        AnthropicStreamEvent::ContentBlockDelta { delta, .. } => match delta {
+            ContentDelta::TextDelta { text } => Some(StreamEvent::ContentDelta { delta: text }),
+        },
+        AnthropicStreamEvent::MessageDelta { delta, usage } => Some(StreamEvent::MessageStop {
+            usage: Some(TokenUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+            }),
+            stop_reason: delta.stop_reason,
+        }),
+        AnthropicStreamEvent::MessageStop => Some(StreamEvent::MessageStop {
+            usage: None,
+            stop_reason: None,
+        }),
+        AnthropicStreamEvent::Error { error } => Some(StreamEvent::Error {
+            error: error.message,
+        }),
+        _ => None, // Skip other events like Ping, ContentBlockStart, ContentBlockStop
+    }
+}
+
 #[async_trait]
 impl AIProvider for AnthropicProvider {
     async fn chat_stream(&self, request: ChatRequest) -> Result<StreamResult, AIError> {
@@ -224,36 +278,8 @@ impl AIProvider for AnthropicProvider {
                                 if let Ok(event) =
                                     serde_json::from_str::<AnthropicStreamEvent>(json_str)
                                 {
-                                    let stream_event = match event {
-                                        AnthropicStreamEvent::MessageStart { .. } => {
-                                            Some(StreamEvent::MessageStart)
-                                        }
-                                        AnthropicStreamEvent::ContentBlockDelta { delta, .. } => {
-                                            match delta {
-                                                ContentDelta::TextDelta { text } => {
-                                                    Some(StreamEvent::ContentDelta { delta: text })
-                                                }
-                                            }
-                                        }
-                                        AnthropicStreamEvent::MessageDelta { usage, .. } => {
-                                            Some(StreamEvent::MessageStop {
-                                                usage: Some(TokenUsage {
-                                                    input_tokens: usage.input_tokens,
-                                                    output_tokens: usage.output_tokens,
-                                                }),
-                                            })
-                                        }
-                                        AnthropicStreamEvent::MessageStop => {
-                                            Some(StreamEvent::MessageStop { usage: None })
-                                        }
-                                        AnthropicStreamEvent::Error { error } => Some(StreamEvent::Error {
-                                            error: error.message,
-                                        }),
-                                        _ => None, // Skip other events like Ping, ContentBlockStart, ContentBlockStop
-                                    };
-
                                     // If we got a valid event, return it
-                                    if let Some(evt) = stream_event {
+                                    if let Some(evt) = convert_stream_event(event) {
                                         return Some(Ok(evt));
                                     }
                                 }
@@ -286,10 +312,11 @@ impl AIProvider for AnthropicProvider {
 
         let response = self
             .client
-            .post(ANTHROPIC_API_URL)
+            .post(&self.api_url)
             .header("x-api-key", api_key)
             .header("anthropic-version", ANTHROPIC_API_VERSION)
             .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
             .json(&test_request)
             .send()
             .await?;
@@ -354,6 +381,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_message_delta_carries_stop_reason() {
+        let json = r#"{"type":"message_delta","delta":{"stop_reason":"max_tokens","stop_sequence":null},"usage":{"output_tokens":4096}}"#;
+        let event = serde_json::from_str::<AnthropicStreamEvent>(json).unwrap();
+
+        match convert_stream_event(event) {
+            Some(StreamEvent::MessageStop { usage, stop_reason }) => {
+                assert_eq!(stop_reason.as_deref(), Some("max_tokens"));
+                assert_eq!(usage.unwrap().output_tokens, 4096);
+            }
+            other => panic!("Expected message stop, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_available_models() {
         let provider = AnthropicProvider::new("test-key".to_string());
@@ -1,18 +1,32 @@
 use async_trait::async_trait;
 use futures::StreamExt;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::provider::{
     AIError, AIProvider, ChatMessage, ChatRequest, StreamEvent, StreamResult,
 };
 
-const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+/// Default base URL; `with_endpoint` can point the client elsewhere
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+const OPENAI_PATH: &str = "/chat/completions";
+
+/// Output token limit of a known model
+pub fn max_output_tokens(model: &str) -> Option<u32> {
+    match model {
+        "gpt-5-mini" | "gpt-5-nano" => Some(128000),
+        _ => None,
+    }
+}
 
 #[derive(Debug)]
 pub struct OpenAIProvider {
     api_key: String,
     client: Client,
+    api_url: String,
+    extra_headers: HeaderMap,
 }
 
 #[derive(Debug, Serialize)]
@@ -114,7 +128,18 @@ impl OpenAIProvider {
         Self {
             api_key,
             client: Client::new(),
+            api_url: format!("{}{}", OPENAI_BASE_URL, OPENAI_PATH),
+            extra_headers: HeaderMap::new(),
+        }
+    }
+
+    /// Use a different base URL and send extra headers with every request
+    pub fn with_endpoint(mut self, base_url: Option<&str>, headers: &BTreeMap<String, String>) -> Self {
+        if let Some(base_url) = base_url {
+            self.api_url = format!("{}{}", base_url.trim_end_matches('/'), OPENAI_PATH);
         }
+        self.extra_headers = super::provider::header_map(headers);
+        self
     }
 
     fn convert_messages(&self, messages: Vec<ChatMessage>) -> Vec<OpenAIMessage> {
@@ -157,9 +182,10 @@ impl OpenAIProvider {
     async fn send_request(&self, request: OpenAIRequest) -> Result<reqwest::Response, AIError> {
         let response = self
             .client
-            .post(OPENAI_API_URL)
+            .post(&self.api_url)
             .header("Authorization", format!("Bearer {}", &self.api_key))
             .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
             .json(&request)
             .send()
             .await?;
@@ -238,7 +264,10 @@ impl AIProvider for OpenAIProvider {
 
                             // Check for stream end
                             if line.contains("data: [DONE]") {
-                                events.push(Ok(StreamEvent::MessageStop { usage: None }));
+                                events.push(Ok(StreamEvent::MessageStop {
+                                    usage: None,
+                                    stop_reason: None,
+                                }));
                                 continue;
                             }
 
@@ -248,9 +277,10 @@ impl AIProvider for OpenAIProvider {
                                 {
                                     if let Some(choice) = stream_response.choices.first() {
                                         // Check for finish
-                                        if choice.finish_reason.is_some() {
+                                        if let Some(reason) = &choice.finish_reason {
                                             events.push(Ok(StreamEvent::MessageStop {
                                                 usage: None,
+                                                stop_reason: Some(reason.clone()),
                                             }));
                                             continue;
                                         }
@@ -298,9 +328,10 @@ impl AIProvider for OpenAIProvider {
 
         let response = self
             .client
-            .post(OPENAI_API_URL)
+            .post(&self.api_url)
             .header("Authorization", format!("Bearer {}", api_key))
             .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
             .json(&test_request)
             .send()
             .await?;
@@ -1,18 +1,24 @@
 use async_trait::async_trait;
 use futures::StreamExt;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::provider::{
     AIError, AIProvider, ChatMessage, ChatRequest, StreamEvent, StreamResult,
 };
 
-const OPENROUTER_API_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
+/// Default base URL; `with_endpoint` can point the client elsewhere
+pub const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+const OPENROUTER_PATH: &str = "/chat/completions";
 
 #[derive(Debug)]
 pub struct OpenRouterProvider {
     api_key: String,
     client: Client,
+    api_url: String,
+    extra_headers: HeaderMap,
 }
 
 #[derive(Debug, Serialize)]
@@ -111,9 +117,20 @@ impl OpenRouterProvider {
         Self {
             api_key,
             client: Client::new(),
+            api_url: format!("{}{}", OPENROUTER_BASE_URL, OPENROUTER_PATH),
+            extra_headers: HeaderMap::new(),
         }
     }
 
+    /// Use a different base URL and send extra headers with every request
+    pub fn with_endpoint(mut self, base_url: Option<&str>, headers: &BTreeMap<String, String>) -> Self {
+        if let Some(base_url) = base_url {
+            self.api_url = format!("{}{}", base_url.trim_end_matches('/'), OPENROUTER_PATH);
+        }
+        self.extra_headers = super::provider::header_map(headers);
+        self
+    }
+
     fn convert_messages(&self, messages: Vec<ChatMessage>) -> Vec<OpenRouterMessage> {
         messages
             .into_iter()
@@ -157,11 +174,12 @@ impl OpenRouterProvider {
     ) -> Result<reqwest::Response, AIError> {
         let response = self
             .client
-            .post(OPENROUTER_API_URL)
+            .post(&self.api_url)
             .header("Authorization", format!("Bearer {}", &self.api_key))
             .header("HTTP-Referer", "https://seeva.ai") // Optional but recommended
             .header("X-Title", "Seeva AI Assistant") // Optional but recommended
             .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
             .json(&request)
             .send()
             .await?;
@@ -235,7 +253,10 @@ impl AIProvider for OpenRouterProvider {
 
                             // Check for stream end
                             if line.contains("data: [DONE]") {
-                                return Some(Ok(StreamEvent::MessageStop { usage: None }));
+                                return Some(Ok(StreamEvent::MessageStop {
+                                    usage: None,
+                                    stop_reason: None,
+                                }));
                             }
 
                             if let Some(json_str) = line.strip_prefix("data: ") {
@@ -244,9 +265,10 @@ impl AIProvider for OpenRouterProvider {
                                 {
                                     if let Some(choice) = stream_response.choices.first() {
                                         // Check for finish
-                                        if choice.finish_reason.is_some() {
+                                        if let Some(reason) = &choice.finish_reason {
                                             return Some(Ok(StreamEvent::MessageStop {
                                                 usage: None,
+                                                stop_reason: Some(reason.clone()),
                                             }));
                                         }
 
@@ -293,11 +315,12 @@ impl AIProvider for OpenRouterProvider {
 
         let response = self
             .client
-            .post(OPENROUTER_API_URL)
+            .post(&self.api_url)
             .header("Authorization", format!("Bearer {}", api_key))
             .header("HTTP-Referer", "https://seeva.ai")
             .header("X-Title", "Seeva AI Assistant")
             .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
             .json(&test_request)
             .send()
             .await?;
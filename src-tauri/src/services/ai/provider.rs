@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use futures::stream::Stream;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::pin::Pin;
 use thiserror::Error;
 
@@ -44,10 +46,21 @@ pub struct TokenUsage {
 pub enum StreamEvent {
     ContentDelta { delta: String },
     MessageStart,
-    MessageStop { usage: Option<TokenUsage> },
+    MessageStop {
+        usage: Option<TokenUsage>,
+        /// Provider-specific reason the response ended (e.g. `end_turn`, `max_tokens`, `length`)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stop_reason: Option<String>,
+    },
     Error { error: String },
 }
 
+/// Whether a provider stop reason means the response was cut off by the
+/// output token limit (`max_tokens` for Anthropic, `length` for OpenAI-style APIs)
+pub fn is_length_limit(stop_reason: &str) -> bool {
+    matches!(stop_reason, "max_tokens" | "length")
+}
+
 #[derive(Error, Debug)]
 pub enum AIError {
     #[error("HTTP request failed: {0}")]
@@ -82,3 +95,18 @@ pub trait AIProvider: Send + Sync {
     /// Get available models
     fn available_models(&self) -> Vec<String>;
 }
+
+/// Convert configured extra headers for reqwest, skipping any that aren't
+/// valid HTTP headers (settings validation rejects those up front)
+pub fn header_map(headers: &BTreeMap<String, String>) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                map.insert(name, value);
+            }
+            _ => eprintln!("⚠️  Ignoring invalid header: {}", name),
+        }
+    }
+    map
+}
@@ -0,0 +1,330 @@
+use crate::services::{migrations, Database};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const BACKUP_FORMAT: &str = "seeva-backup";
+pub const BACKUP_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "seeva.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+
+/// Backups saved by hand are suggested as `seeva-backup-<timestamp>.zip`
+const FILE_PREFIX: &str = "seeva-backup-";
+/// Scheduled backups are named `seeva-auto-backup-<timestamp>.zip`, so
+/// sorting by name sorts by age and rotation never touches manual backups
+const SCHEDULED_FILE_PREFIX: &str = "seeva-auto-backup-";
+const FILE_TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Failed to access backup file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to read or write backup archive: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[error("Database backup failed: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("Invalid backup manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+
+    #[error("Not a Seeva backup: {0}")]
+    InvalidBackup(String),
+}
+
+/// Describes an archive; stored as `manifest.json` next to the database and
+/// settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub created_at: i64,
+    pub app_version: String,
+    pub schema_version: u32,
+    /// Encrypted with the passphrase in use when the backup was taken
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+/// Snapshot the database with SQLite's online backup API and write it to a
+/// zip archive at `dest` together with `settings_json`. The archive is
+/// written to a temporary file and renamed into place when complete.
+pub fn create_backup(
+    db: &Database,
+    settings_json: &str,
+    dest: &Path,
+) -> Result<BackupManifest, BackupError> {
+    let snapshot = TempFile::new(dest, "db");
+    db.backup_to(&snapshot.0)?;
+    let schema_version = db.schema_version()?;
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now().timestamp_millis(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version,
+        encrypted: db.is_encrypted(),
+    };
+
+    let partial = TempFile::new(dest, "partial");
+    let mut zip = ZipWriter::new(File::create(&partial.0)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    zip.start_file(DATABASE_ENTRY, options)?;
+    io::copy(&mut File::open(&snapshot.0)?, &mut zip)?;
+    zip.start_file(SETTINGS_ENTRY, options)?;
+    zip.write_all(settings_json.as_bytes())?;
+    zip.finish()?.sync_all()?;
+
+    fs::rename(&partial.0, dest)?;
+    Ok(manifest)
+}
+
+/// Contents of a backup archive. The database is extracted to a temporary
+/// file which is removed when this is dropped.
+pub struct ExtractedBackup {
+    pub manifest: BackupManifest,
+    pub settings_json: Option<String>,
+    database: TempFile,
+}
+
+impl ExtractedBackup {
+    pub fn database_path(&self) -> &Path {
+        &self.database.0
+    }
+}
+
+/// Open and validate a backup archive
+pub fn read_backup(path: &Path) -> Result<ExtractedBackup, BackupError> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    let manifest: BackupManifest = match archive.by_name(MANIFEST_ENTRY) {
+        Ok(entry) => serde_json::from_reader(entry)?,
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(BackupError::InvalidBackup(format!("missing {}", MANIFEST_ENTRY)))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if manifest.format != BACKUP_FORMAT {
+        return Err(BackupError::InvalidBackup(format!(
+            "unexpected format '{}'",
+            manifest.format
+        )));
+    }
+    if manifest.version > BACKUP_VERSION {
+        return Err(BackupError::InvalidBackup(format!(
+            "backup version {} is newer than this app supports",
+            manifest.version
+        )));
+    }
+    if manifest.schema_version > migrations::latest_version() {
+        return Err(BackupError::InvalidBackup(format!(
+            "database schema v{} is newer than this app supports",
+            manifest.schema_version
+        )));
+    }
+
+    let database = TempFile::new(&std::env::temp_dir().join(DATABASE_ENTRY), "restore");
+    match archive.by_name(DATABASE_ENTRY) {
+        Ok(mut entry) => {
+            io::copy(&mut entry, &mut File::create(&database.0)?)?;
+        }
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(BackupError::InvalidBackup(format!("missing {}", DATABASE_ENTRY)))
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let settings_json = match archive.by_name(SETTINGS_ENTRY) {
+        Ok(mut entry) => {
+            let mut json = String::new();
+            entry.read_to_string(&mut json)?;
+            Some(json)
+        }
+        Err(zip::result::ZipError::FileNotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(ExtractedBackup {
+        manifest,
+        settings_json,
+        database,
+    })
+}
+
+/// Suggested file name for a backup taken by hand at `time`
+pub fn backup_file_name(time: DateTime<Utc>) -> String {
+    format!("{}{}.zip", FILE_PREFIX, time.format(FILE_TIMESTAMP))
+}
+
+/// File name for a scheduled backup taken at `time`
+pub fn scheduled_backup_file_name(time: DateTime<Utc>) -> String {
+    format!("{}{}.zip", SCHEDULED_FILE_PREFIX, time.format(FILE_TIMESTAMP))
+}
+
+fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    let stem = path.file_stem()?.to_str()?.strip_prefix(SCHEDULED_FILE_PREFIX)?;
+    NaiveDateTime::parse_from_str(stem, FILE_TIMESTAMP)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Scheduled backups in `folder`, oldest first
+pub fn list_backups(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    if !folder.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<PathBuf> = fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip") && backup_time(path).is_some())
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Whether the newest backup in `folder` is at least `interval_hours` old
+pub fn is_backup_due(folder: &Path, interval_hours: u32, now: DateTime<Utc>) -> io::Result<bool> {
+    let latest = list_backups(folder)?.last().and_then(|path| backup_time(path));
+    Ok(latest.is_none_or(|time| now - time >= chrono::Duration::hours(interval_hours.into())))
+}
+
+/// Delete the oldest backups so at most `keep` remain. Returns the removed files.
+pub fn rotate_backups(folder: &Path, keep: usize) -> io::Result<Vec<PathBuf>> {
+    let backups = list_backups(folder)?;
+    let excess = backups.len().saturating_sub(keep);
+    let removed: Vec<PathBuf> = backups.into_iter().take(excess).collect();
+    for path in &removed {
+        fs::remove_file(path)?;
+    }
+    Ok(removed)
+}
+
+/// Uniquely named scratch file next to `base`, removed on drop
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(base: &Path, suffix: &str) -> Self {
+        let file_name = base
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "seeva-backup".to_string());
+        let path = base.with_file_name(format!(
+            ".{}.{}.{}",
+            file_name,
+            uuid::Uuid::new_v4().simple(),
+            suffix
+        ));
+        Self(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Message, MessageRole, Thread};
+    use tempfile::TempDir;
+
+    fn seeded_db(dir: &Path, name: &str) -> (Database, Thread) {
+        let db = Database::new(dir.join(name), None).unwrap();
+        let thread = Thread::new("Backed up".to_string());
+        db.create_thread(&thread).unwrap();
+        db.create_message(&Message::new(
+            thread.id.clone(),
+            MessageRole::User,
+            "Remember this".to_string(),
+        ))
+        .unwrap();
+        (db, thread)
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let (db, thread) = seeded_db(dir.path(), "seeva.db");
+        let archive = dir.path().join("backup.zip");
+
+        let manifest = create_backup(&db, r#"{"theme":"light"}"#, &archive).unwrap();
+        assert_eq!(manifest.schema_version, migrations::latest_version());
+
+        let extracted = read_backup(&archive).unwrap();
+        assert_eq!(extracted.settings_json.as_deref(), Some(r#"{"theme":"light"}"#));
+
+        // Restore into a database that has diverged since the backup
+        let target = Database::new(dir.path().join("other.db"), None).unwrap();
+        target.create_thread(&Thread::new("Newer".to_string())).unwrap();
+        target.restore_from(extracted.database_path()).unwrap();
+
+        let threads = target.list_threads(&Default::default()).unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].id, thread.id);
+        assert_eq!(target.get_messages(&thread.id).unwrap()[0].content, "Remember this");
+
+        let extracted_path = extracted.database_path().to_path_buf();
+        drop(extracted);
+        assert!(!extracted_path.exists());
+    }
+
+    #[test]
+    fn test_rejects_foreign_archive() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("other.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("notes.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        assert!(matches!(read_backup(&path), Err(BackupError::InvalidBackup(_))));
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest() {
+        let dir = TempDir::new().unwrap();
+        let start = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().to_utc();
+        for day in 0..5 {
+            let name = scheduled_backup_file_name(start + chrono::Duration::days(day));
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        fs::write(dir.path().join("unrelated.zip"), b"").unwrap();
+        // Saved by hand into the same folder
+        let manual = dir.path().join(backup_file_name(start));
+        fs::write(&manual, b"").unwrap();
+
+        let removed = rotate_backups(dir.path(), 3).unwrap();
+        assert_eq!(removed.len(), 2);
+
+        let remaining = list_backups(dir.path()).unwrap();
+        assert_eq!(remaining.len(), 3);
+        assert!(remaining[0].ends_with("seeva-auto-backup-20260103-000000.zip"));
+        assert!(dir.path().join("unrelated.zip").exists());
+        assert!(manual.exists());
+    }
+
+    #[test]
+    fn test_backup_due_after_interval() {
+        let dir = TempDir::new().unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-01-02T12:00:00Z").unwrap().to_utc();
+        assert!(is_backup_due(dir.path(), 24, now).unwrap());
+
+        let last = now - chrono::Duration::hours(6);
+        fs::write(dir.path().join(scheduled_backup_file_name(last)), b"").unwrap();
+        assert!(!is_backup_due(dir.path(), 24, now).unwrap());
+        assert!(is_backup_due(dir.path(), 6, now).unwrap());
+    }
+}
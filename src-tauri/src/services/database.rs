@@ -1,117 +1,277 @@
-use rusqlite::{Connection, params, Result};
-use std::path::PathBuf;
+use rusqlite::backup::Backup;
+use rusqlite::{ffi, Connection, DatabaseName, ErrorCode, params, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use crate::models::{Message, Thread, MessageRole};
+use std::time::Duration;
+use crate::models::{
+    Folder, ImageData, ImageRef, Message, MessagePage, MessageRole, SearchFilters, SearchHit, Tag,
+    Thread, ThreadFilters, TrashItem, TrashKind,
+};
+use crate::services::images::{self, ImageBlob};
+use crate::services::import::{IMPORT_ID_KEY, IMPORT_SOURCE_KEY};
+use crate::services::migrations;
+use crate::services::retention::{RemovedThread, RetentionReport, RetentionRules};
+
+/// Message columns followed by one (optional) image per row; see `query_messages`
+const MESSAGE_COLUMNS: &str =
+    "m.id, m.thread_id, m.role, m.content, m.created_at, m.metadata,
+     i.id, b.mime_type, b.width, b.height, b.byte_size";
+const IMAGE_JOIN: &str =
+    "LEFT JOIN images i ON i.message_id = m.id LEFT JOIN image_blobs b ON b.hash = i.hash";
+
+/// Columns read by `thread_from_row`, selected from `threads t`
+const THREAD_COLUMNS: &str =
+    "t.id, t.name, t.created_at, t.updated_at,
+     (SELECT COUNT(*) FROM messages WHERE thread_id = t.id AND deleted_at IS NULL) as message_count,
+     (SELECT content FROM messages WHERE thread_id = t.id AND deleted_at IS NULL
+      ORDER BY created_at DESC LIMIT 1) as last_message,
+     (SELECT b.thumbnail FROM images i
+        JOIN messages im ON im.id = i.message_id
+        JOIN image_blobs b ON b.hash = i.hash
+      WHERE im.thread_id = t.id AND im.deleted_at IS NULL AND b.thumbnail IS NOT NULL
+      ORDER BY im.created_at DESC, i.position LIMIT 1) as thumbnail,
+     t.pinned, t.archived, t.folder_id,
+     (SELECT group_concat(tag_id) FROM thread_tags WHERE thread_id = t.id) as tags,
+     t.metadata";
+
+/// Every plaintext SQLite file starts with this; SQLCipher files don't
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
 
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    path: PathBuf,
+    /// SQLCipher key, `None` while the database is plaintext
+    passphrase: Mutex<Option<String>>,
 }
 
 impl Database {
-    pub fn new(db_path: PathBuf) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
+    /// Open (or create) the database. An encrypted database needs its
+    /// `passphrase`; see `is_wrong_passphrase` for telling a bad one apart.
+    pub fn new(db_path: PathBuf, passphrase: Option<&str>) -> Result<Self> {
+        let mut conn = open_connection(&db_path, passphrase)?;
 
-        // Enable foreign key constraints
+        // Create or upgrade the schema
+        migrations::run(&mut conn, &db_path)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            path: db_path,
+            passphrase: Mutex::new(passphrase.map(str::to_string)),
+        })
+    }
+
+    /// A database that lives only in memory and is gone once dropped
+    pub fn in_memory() -> Result<Self> {
+        let path = PathBuf::from(":memory:");
+        let mut conn = Connection::open_in_memory()?;
         conn.execute("PRAGMA foreign_keys = ON", [])?;
+        migrations::run(&mut conn, &path)?;
 
-        let db = Self {
+        Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
-        };
-        db.init_schema()?;
-        Ok(db)
+            path,
+            passphrase: Mutex::new(None),
+        })
+    }
+
+    /// Whether the file at `path` is an encrypted database. Missing or empty
+    /// files count as plaintext, since that's what `new` creates without a key.
+    pub fn file_is_encrypted(path: &Path) -> bool {
+        let mut header = [0u8; 16];
+        match File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+            Ok(_) => &header != SQLITE_HEADER,
+            Err(_) => false,
+        }
     }
 
-    fn init_schema(&self) -> Result<()> {
+    pub fn schema_version(&self) -> Result<u32> {
+        migrations::current_version(&self.conn.lock().unwrap())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.passphrase.lock().unwrap().is_some()
+    }
+
+    /// Encrypt a plaintext database in place. The contents are exported to
+    /// an encrypted copy which then replaces the original file.
+    pub fn encrypt(&self, passphrase: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let mut key = self.passphrase.lock().unwrap();
+        if key.is_some() {
+            return Err(misuse("Database is already encrypted"));
+        }
+
+        let encrypted_path = self.path.with_extension("db.encrypting");
+        let _ = std::fs::remove_file(&encrypted_path);
+        export_encrypted(&conn, &encrypted_path, passphrase)?;
+
+        // Close the plaintext file before swapping the encrypted copy in
+        *conn = Connection::open_in_memory()?;
+        if let Err(e) = std::fs::rename(&encrypted_path, &self.path) {
+            *conn = open_connection(&self.path, None)?;
+            return Err(misuse(&format!("Failed to replace database file: {}", e)));
+        }
+
+        *conn = open_connection(&self.path, Some(passphrase))?;
+        *key = Some(passphrase.to_string());
+        Ok(())
+    }
+
+    /// Re-encrypt the database under a new passphrase
+    pub fn change_passphrase(&self, current: &str, new: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        let mut key = self.passphrase.lock().unwrap();
+        match key.as_deref() {
+            None => return Err(misuse("Database is not encrypted")),
+            Some(existing) if existing != current => {
+                return Err(rusqlite::Error::SqliteFailure(
+                    ffi::Error::new(ffi::SQLITE_NOTADB),
+                    Some("Incorrect passphrase".to_string()),
+                ))
+            }
+            Some(_) => {}
+        }
 
-        // Create threads table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS threads (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                metadata TEXT
-            )",
-            [],
-        )?;
+        conn.pragma_update(None, "rekey", new)?;
+        *key = Some(new.to_string());
+        Ok(())
+    }
 
-        // Create messages table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS messages (
-                id TEXT PRIMARY KEY,
-                thread_id TEXT NOT NULL,
-                role TEXT NOT NULL,
-                content TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                metadata TEXT,
-                FOREIGN KEY (thread_id) REFERENCES threads(id) ON DELETE CASCADE
-            )",
-            [],
-        )?;
-
-        // Create images table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS images (
-                id TEXT PRIMARY KEY,
-                message_id TEXT NOT NULL,
-                data TEXT NOT NULL,
-                mime_type TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
-            )",
-            [],
-        )?;
-
-        // Create indexes
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_messages_thread ON messages(thread_id)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_messages_created ON messages(created_at)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_images_message ON images(message_id)",
-            [],
-        )?;
+    /// Snapshot the live database to `dest` with SQLite's online backup API.
+    /// The copy is encrypted with the same passphrase as the database.
+    pub fn backup_to(&self, dest: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let key = self.passphrase.lock().unwrap();
+
+        let mut dest = open_connection(dest, key.as_deref())?;
+        let backup = Backup::new(&conn, &mut dest)?;
+        backup.run_to_completion(256, Duration::ZERO, None)
+    }
+
+    /// Replace the contents of the live database with the database at `src`,
+    /// upgrading `src` to the current schema first. An encrypted `src` must
+    /// use the current passphrase; a plaintext one is encrypted on the way in.
+    pub fn restore_from(&self, src: &Path) -> Result<()> {
+        let key = self.passphrase.lock().unwrap().clone();
+        let src_encrypted = Self::file_is_encrypted(src);
+        if src_encrypted && key.is_none() {
+            return Err(misuse(
+                "Backup is encrypted; encrypt this database with the backup's passphrase first",
+            ));
+        }
+
+        let mut source = open_connection(src, key.as_deref().filter(|_| src_encrypted))?;
+        migrations::run(&mut source, src)?;
+
+        // The backup API can't copy between plaintext and encrypted databases
+        let encrypted_copy = src.with_extension("db.encrypting");
+        if let (false, Some(key)) = (src_encrypted, key.as_deref()) {
+            let _ = std::fs::remove_file(&encrypted_copy);
+            export_encrypted(&source, &encrypted_copy, key)?;
+            source = open_connection(&encrypted_copy, Some(key))?;
+        }
+
+        let mut conn = self.conn.lock().unwrap();
+        let result = Backup::new(&source, &mut conn)
+            .and_then(|backup| backup.run_to_completion(256, Duration::ZERO, None));
+        drop(source);
+        let _ = std::fs::remove_file(&encrypted_copy);
+        result
+    }
+
+    /// Purge old trash, delete stale threads and old images, then the least
+    /// recently updated threads until the database fits `rules.max_bytes`.
+    /// Pinned threads and `protect` are never deleted, and pinned threads keep
+    /// their images. A dry run reports the same without changing anything.
+    pub fn apply_retention(
+        &self,
+        rules: &RetentionRules,
+        protect: Option<&str>,
+        dry_run: bool,
+    ) -> Result<RetentionReport> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut report = RetentionReport {
+            dry_run,
+            bytes_before: used_bytes(&tx)?,
+            ..Default::default()
+        };
+
+        if let Some(cutoff) = rules.trash_before {
+            report.purged_trash = purge_trash(&tx, cutoff)?;
+        }
+
+        let deletable = "SELECT id, name FROM threads
+             WHERE deleted_at IS NULL AND pinned = 0 AND (?1 IS NULL OR id != ?1) AND updated_at < ?2
+             ORDER BY updated_at";
+        if let Some(cutoff) = rules.threads_before {
+            let stale = tx
+                .prepare(deletable)?
+                .query_map(params![protect, cutoff], |row| {
+                    Ok(RemovedThread { id: row.get(0)?, name: row.get(1)? })
+                })?
+                .collect::<Result<Vec<_>>>()?;
+            for thread in stale {
+                tx.execute("DELETE FROM threads WHERE id = ?1", params![thread.id])?;
+                report.deleted_threads.push(thread);
+            }
+        }
+
+        if let Some(cutoff) = rules.images_before {
+            // Blobs go with their last reference; see `images_release_blob`
+            report.stripped_images = tx.execute(
+                "DELETE FROM images WHERE created_at < ?1 AND message_id IN (
+                    SELECT m.id FROM messages m JOIN threads t ON t.id = m.thread_id WHERE t.pinned = 0
+                 )",
+                params![cutoff],
+            )?;
+        }
+
+        if let Some(max_bytes) = rules.max_bytes {
+            while used_bytes(&tx)? > max_bytes {
+                let oldest = tx
+                    .query_row(&format!("{} LIMIT 1", deletable), params![protect, i64::MAX], |row| {
+                        Ok(RemovedThread { id: row.get(0)?, name: row.get(1)? })
+                    });
+                let thread = match oldest {
+                    Ok(thread) => thread,
+                    Err(rusqlite::Error::QueryReturnedNoRows) => break,
+                    Err(e) => return Err(e),
+                };
+                tx.execute("DELETE FROM threads WHERE id = ?1", params![thread.id])?;
+                report.deleted_threads.push(thread);
+            }
+        }
+
+        report.bytes_after = used_bytes(&tx)?;
+        if dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+        }
+        Ok(report)
+    }
 
+    /// Rebuild the database file so space freed by deletes is returned to
+    /// the filesystem
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM", [])?;
         Ok(())
     }
 
     // Thread operations
     pub fn create_thread(&self, thread: &Thread) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO threads (id, name, created_at, updated_at, metadata) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![thread.id, thread.name, thread.created_at, thread.updated_at, Option::<String>::None],
-        )?;
-        Ok(())
+        insert_thread(&conn, thread)
     }
 
     pub fn get_thread(&self, id: &str) -> Result<Option<Thread>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT t.id, t.name, t.created_at, t.updated_at,
-                    COUNT(m.id) as message_count,
-                    (SELECT content FROM messages WHERE thread_id = t.id ORDER BY created_at DESC LIMIT 1) as last_message
-             FROM threads t
-             LEFT JOIN messages m ON m.thread_id = t.id
-             WHERE t.id = ?1
-             GROUP BY t.id"
-        )?;
-
-        let thread = stmt.query_row(params![id], |row| {
-            Ok(Thread {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                created_at: row.get(2)?,
-                updated_at: row.get(3)?,
-                message_count: row.get(4).ok(),
-                last_message: row.get(5).ok(),
-            })
-        });
+        let sql = format!("SELECT {} FROM threads t WHERE t.id = ?1 AND t.deleted_at IS NULL", THREAD_COLUMNS);
+        let thread = conn.query_row(&sql, params![id], thread_from_row);
 
         match thread {
             Ok(t) => Ok(Some(t)),
@@ -120,29 +280,30 @@ impl Database {
         }
     }
 
-    pub fn list_threads(&self) -> Result<Vec<Thread>> {
+    /// Threads matching `filters`, pinned first, then most recently updated
+    pub fn list_threads(&self, filters: &ThreadFilters) -> Result<Vec<Thread>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT t.id, t.name, t.created_at, t.updated_at,
-                    COUNT(m.id) as message_count,
-                    (SELECT content FROM messages WHERE thread_id = t.id ORDER BY created_at DESC LIMIT 1) as last_message
-             FROM threads t
-             LEFT JOIN messages m ON m.thread_id = t.id
-             GROUP BY t.id
-             ORDER BY t.updated_at DESC"
+        let sql = format!(
+            "SELECT {} FROM threads t
+             WHERE t.deleted_at IS NULL AND t.archived = ?1
+               AND (?2 IS NULL OR t.folder_id IN (
+                    WITH RECURSIVE subtree(id) AS (
+                        SELECT ?2
+                        UNION ALL
+                        SELECT f.id FROM folders f JOIN subtree s ON f.parent_id = s.id
+                    )
+                    SELECT id FROM subtree))
+               AND (?3 IS NULL OR EXISTS (
+                    SELECT 1 FROM thread_tags tt WHERE tt.thread_id = t.id AND tt.tag_id = ?3))
+             ORDER BY t.pinned DESC, t.updated_at DESC",
+            THREAD_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let threads = stmt.query_map(
+            params![filters.archived, filters.folder_id, filters.tag_id],
+            thread_from_row,
         )?;
 
-        let threads = stmt.query_map([], |row| {
-            Ok(Thread {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                created_at: row.get(2)?,
-                updated_at: row.get(3)?,
-                message_count: row.get(4).ok(),
-                last_message: row.get(5).ok(),
-            })
-        })?;
-
         threads.collect()
     }
 
@@ -155,92 +316,846 @@ impl Database {
         Ok(())
     }
 
+    /// Set only `updated_at`, so the thread's search index isn't rewritten
+    pub fn touch_thread(&self, id: &str, updated_at: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE threads SET updated_at = ?1 WHERE id = ?2", params![updated_at, id])?;
+        Ok(())
+    }
+
+    pub fn set_thread_pinned(&self, id: &str, pinned: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE threads SET pinned = ?1 WHERE id = ?2", params![pinned, id])?;
+        Ok(())
+    }
+
+    pub fn set_thread_archived(&self, id: &str, archived: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE threads SET archived = ?1 WHERE id = ?2", params![archived, id])?;
+        Ok(())
+    }
+
+    pub fn set_thread_folder(&self, id: &str, folder_id: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE threads SET folder_id = ?1 WHERE id = ?2", params![folder_id, id])?;
+        Ok(())
+    }
+
+    /// Delete a thread with its messages for good; see `trash_thread`
     pub fn delete_thread(&self, id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute("DELETE FROM threads WHERE id = ?1", params![id])?;
         Ok(())
     }
 
+    /// Hide a thread until it is restored or the trash is purged
+    pub fn trash_thread(&self, id: &str, deleted_at: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE threads SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![deleted_at, id],
+        )?;
+        Ok(())
+    }
+
+    /// Take a thread out of the trash. Returns false if it wasn't there.
+    pub fn restore_thread(&self, id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let restored = conn.execute(
+            "UPDATE threads SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )?;
+        Ok(restored > 0)
+    }
+
     // Message operations
     pub fn create_message(&self, message: &Message) -> Result<()> {
+        // Decode images first so bad data doesn't leave a half-written message
+        let blobs = decode_images(message)?;
+
         let conn = self.conn.lock().unwrap();
+        insert_message(&conn, message, &blobs)
+    }
 
-        // Insert message
-        conn.execute(
-            "INSERT INTO messages (id, thread_id, role, content, created_at, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    /// Store a complete thread with its messages in one transaction. A copy
+    /// of the same thread or import in the trash is replaced.
+    pub fn import_thread(&self, thread: &Thread, messages: &[Message], tag_ids: &[String]) -> Result<()> {
+        let blobs = messages
+            .iter()
+            .map(decode_images)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM threads
+             WHERE deleted_at IS NOT NULL
+               AND (id = ?1 OR (json_extract(metadata, '$.importSource') = ?2
+                                AND json_extract(metadata, '$.importId') = ?3))",
+            params![thread.id, import_key(thread, IMPORT_SOURCE_KEY), import_key(thread, IMPORT_ID_KEY)],
+        )?;
+        insert_thread(&tx, thread)?;
+        for (message, blobs) in messages.iter().zip(&blobs) {
+            insert_message(&tx, message, blobs)?;
+        }
+        for tag_id in tag_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO thread_tags (thread_id, tag_id) VALUES (?1, ?2)",
+                params![thread.id, tag_id],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Create `fork` with copies of the messages of `source_id` up to and
+    /// including `message_id`. Copied images reference the same blobs.
+    /// Returns the number of messages copied.
+    pub fn fork_thread(&self, source_id: &str, message_id: &str, fork: &Thread) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let until: i64 = tx.query_row(
+            "SELECT created_at FROM messages WHERE id = ?1 AND thread_id = ?2 AND deleted_at IS NULL",
+            params![message_id, source_id],
+            |row| row.get(0),
+        )?;
+        insert_thread(&tx, fork)?;
+
+        let ids = tx
+            .prepare(
+                "SELECT id FROM messages
+                 WHERE thread_id = ?1 AND deleted_at IS NULL AND (created_at, id) <= (?2, ?3)
+                 ORDER BY created_at, id",
+            )?
+            .query_map(params![source_id, until, message_id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?;
+        for id in &ids {
+            let copy_id = uuid::Uuid::new_v4().to_string();
+            tx.execute(
+                "INSERT INTO messages (id, thread_id, role, content, created_at, metadata)
+                 SELECT ?1, ?2, role, content, created_at, metadata FROM messages WHERE id = ?3",
+                params![copy_id, fork.id, id],
+            )?;
+            tx.execute(
+                "INSERT INTO images (id, message_id, hash, position, created_at)
+                 SELECT ?1 || '-' || position, ?1, hash, position, created_at FROM images WHERE message_id = ?2",
+                params![copy_id, id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(ids.len())
+    }
+
+    /// The thread previously imported from `source` with the given id, if
+    /// any. Threads in the trash don't count; importing replaces them.
+    pub fn find_imported_thread(&self, source: &str, import_id: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let id = conn.query_row(
+            "SELECT id FROM threads
+             WHERE deleted_at IS NULL
+               AND json_extract(metadata, '$.importSource') = ?1
+               AND json_extract(metadata, '$.importId') = ?2",
+            params![source, import_id],
+            |row| row.get(0),
+        );
+
+        match id {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// All messages of a thread with image data, for building requests
+    pub fn get_messages(&self, thread_id: &str) -> Result<Vec<Message>> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!(
+            "SELECT {}, b.data FROM messages m {}
+             WHERE m.thread_id = ?1 AND m.deleted_at IS NULL
+             ORDER BY m.created_at, m.id, i.position",
+            MESSAGE_COLUMNS, IMAGE_JOIN
+        );
+        query_messages(&conn, &sql, params![thread_id], true)
+    }
+
+    /// Up to `limit` messages older than `before` (or the newest ones),
+    /// with image references instead of data
+    pub fn get_messages_page(&self, thread_id: &str, before: Option<&str>, limit: u32) -> Result<MessagePage> {
+        let conn = self.conn.lock().unwrap();
+        // Fetch one extra row to learn whether there is more
+        let sql = format!(
+            "WITH page AS (
+                SELECT * FROM messages
+                WHERE thread_id = ?1 AND deleted_at IS NULL
+                  AND (?2 IS NULL OR (created_at, id) < (SELECT created_at, id FROM messages WHERE id = ?2))
+                ORDER BY created_at DESC, id DESC
+                LIMIT ?3
+             )
+             SELECT {}, NULL FROM page m {}
+             ORDER BY m.created_at, m.id, i.position",
+            MESSAGE_COLUMNS, IMAGE_JOIN
+        );
+        let mut messages = query_messages(&conn, &sql, params![thread_id, before, limit.saturating_add(1)], false)?;
+
+        let has_more = messages.len() > limit as usize;
+        if has_more {
+            messages.remove(0);
+        }
+
+        Ok(MessagePage { messages, has_more })
+    }
+
+    pub fn get_message(&self, id: &str) -> Result<Option<Message>> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!(
+            "SELECT {}, b.data FROM messages m {}
+             WHERE m.id = ?1 AND m.deleted_at IS NULL
+             ORDER BY i.position",
+            MESSAGE_COLUMNS, IMAGE_JOIN
+        );
+        Ok(query_messages(&conn, &sql, params![id], true)?.pop())
+    }
+
+    pub fn get_image(&self, id: &str) -> Result<Option<ImageData>> {
+        let conn = self.conn.lock().unwrap();
+        let image = conn.query_row(
+            "SELECT i.id, b.mime_type, b.width, b.height, b.byte_size, b.data
+             FROM images i JOIN image_blobs b ON b.hash = i.hash
+             WHERE i.id = ?1",
+            params![id],
+            |row| {
+                let data: Vec<u8> = row.get(5)?;
+                Ok(ImageData {
+                    info: ImageRef {
+                        id: row.get(0)?,
+                        mime_type: row.get(1)?,
+                        width: row.get(2)?,
+                        height: row.get(3)?,
+                        byte_size: row.get(4)?,
+                    },
+                    data: images::to_base64(&data),
+                })
+            },
+        );
+
+        match image {
+            Ok(img) => Ok(Some(img)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Update a message's content and metadata (images are immutable).
+    /// Returns false if the message is gone or in the trash, e.g. when it
+    /// was deleted while still streaming.
+    pub fn update_message(&self, message: &Message) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE messages SET content = ?1, metadata = ?2 WHERE id = ?3 AND deleted_at IS NULL",
             params![
-                message.id,
-                message.thread_id,
-                message.role.as_str(),
                 message.content,
-                message.created_at,
-                message.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok())
+                message.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok()),
+                message.id
             ],
         )?;
+        Ok(updated > 0)
+    }
 
-        // Insert images if present
-        if let Some(images) = &message.images {
-            for (idx, img_data) in images.iter().enumerate() {
-                let image_id = format!("{}-{}", message.id, idx);
-                conn.execute(
-                    "INSERT INTO images (id, message_id, data, mime_type, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-                    params![image_id, message.id, img_data, "image/png", message.created_at],
-                )?;
-            }
+    /// Delete a message for good; see `trash_message`
+    pub fn delete_message(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM messages WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Hide a message until it is restored or the trash is purged
+    pub fn trash_message(&self, id: &str, deleted_at: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE messages SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![deleted_at, id],
+        )?;
+        Ok(())
+    }
+
+    /// Take a message out of the trash, along with its thread if that was
+    /// deleted too. Returns false if the message wasn't in the trash.
+    pub fn restore_message(&self, id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let restored = tx.execute(
+            "UPDATE messages SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )?;
+        if restored == 0 {
+            return Ok(false);
         }
+        tx.execute(
+            "UPDATE threads SET deleted_at = NULL
+             WHERE id = (SELECT thread_id FROM messages WHERE id = ?1)",
+            params![id],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
 
+    // Trash operations
+    /// Deleted threads, and deleted messages of threads that aren't,
+    /// most recently deleted first
+    pub fn list_trash(&self) -> Result<Vec<TrashItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT 'thread', t.id, t.id, t.name, NULL, t.deleted_at
+             FROM threads t WHERE t.deleted_at IS NOT NULL
+             UNION ALL
+             SELECT 'message', m.id, m.thread_id, t.name, substr(m.content, 1, 200), m.deleted_at
+             FROM messages m JOIN threads t ON t.id = m.thread_id
+             WHERE m.deleted_at IS NOT NULL AND t.deleted_at IS NULL
+             ORDER BY 6 DESC",
+        )?;
+        let items = stmt.query_map([], |row| {
+            let kind: String = row.get(0)?;
+            Ok(TrashItem {
+                kind: if kind == "thread" { TrashKind::Thread } else { TrashKind::Message },
+                id: row.get(1)?,
+                thread_id: row.get(2)?,
+                thread_name: row.get(3)?,
+                preview: row.get(4)?,
+                deleted_at: row.get(5)?,
+            })
+        })?;
+
+        items.collect()
+    }
+
+    /// Permanently delete everything in the trash. Returns the number of
+    /// threads and messages removed.
+    pub fn empty_trash(&self) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let purged = purge_trash(&tx, i64::MAX)?;
+        tx.commit()?;
+        Ok(purged)
+    }
+
+    // Folder operations
+    pub fn create_folder(&self, folder: &Folder) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO folders (id, name, parent_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![folder.id, folder.name, folder.parent_id, folder.created_at],
+        )?;
         Ok(())
     }
 
-    pub fn get_messages(&self, thread_id: &str) -> Result<Vec<Message>> {
+    /// Insert a folder unless one with the same id exists
+    pub fn insert_folder_if_missing(&self, folder: &Folder) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO folders (id, name, parent_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![folder.id, folder.name, folder.parent_id, folder.created_at],
+        )?;
+        Ok(())
+    }
 
-        // Get messages
+    pub fn list_folders(&self) -> Result<Vec<Folder>> {
+        let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, thread_id, role, content, created_at, metadata FROM messages WHERE thread_id = ?1 ORDER BY created_at ASC"
+            "SELECT id, name, parent_id, created_at FROM folders ORDER BY name COLLATE NOCASE"
         )?;
+        let folders = stmt.query_map([], |row| {
+            Ok(Folder {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                parent_id: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+        folders.collect()
+    }
 
-        let messages = stmt.query_map(params![thread_id], |row| {
-            let id: String = row.get(0)?;
-            let thread_id: String = row.get(1)?;
-            let role_str: String = row.get(2)?;
-            let role = MessageRole::from_str(&role_str).unwrap_or(MessageRole::User);
-            let content: String = row.get(3)?;
-            let created_at: i64 = row.get(4)?;
-            let metadata_str: Option<String> = row.get(5)?;
-            let metadata = metadata_str.and_then(|s| serde_json::from_str(&s).ok());
+    pub fn rename_folder(&self, id: &str, name: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE folders SET name = ?1 WHERE id = ?2", params![name, id])?;
+        Ok(())
+    }
+
+    /// Re-parent a folder; refuses to move it into itself or its own subtree,
+    /// which would orphan the branch
+    pub fn set_folder_parent(&self, id: &str, parent_id: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        if let Some(parent_id) = parent_id {
+            if folder_contains(&conn, id, parent_id)? {
+                return Err(misuse("Cannot move a folder into itself or one of its subfolders"));
+            }
+        }
+        conn.execute("UPDATE folders SET parent_id = ?1 WHERE id = ?2", params![parent_id, id])?;
+        Ok(())
+    }
+
+    /// Whether `folder_id` is `ancestor_id` or nested anywhere below it
+    pub fn folder_contains(&self, ancestor_id: &str, folder_id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        folder_contains(&conn, ancestor_id, folder_id)
+    }
+
+    /// Delete a folder and its subfolders; their threads move to the top level
+    pub fn delete_folder(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM folders WHERE id = ?1", params![id])?;
+        Ok(())
+    }
 
-            Ok((id, thread_id, role, content, created_at, metadata))
+    // Tag operations
+    pub fn create_tag(&self, tag: &Tag) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO tags (id, name, color) VALUES (?1, ?2, ?3)",
+            params![tag.id, tag.name, tag.color],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, color FROM tags ORDER BY name COLLATE NOCASE")?;
+        let tags = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+            })
         })?;
+        tags.collect()
+    }
 
-        let mut result = Vec::new();
-        for msg in messages {
-            let (id, thread_id, role, content, created_at, metadata) = msg?;
+    pub fn find_tag_by_name(&self, name: &str) -> Result<Option<Tag>> {
+        let conn = self.conn.lock().unwrap();
+        let tag = conn.query_row(
+            "SELECT id, name, color FROM tags WHERE name = ?1",
+            params![name],
+            |row| {
+                Ok(Tag {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                })
+            },
+        );
 
-            // Get images for this message
-            let mut img_stmt = conn.prepare("SELECT data FROM images WHERE message_id = ?1 ORDER BY id")?;
-            let images: Result<Vec<String>> = img_stmt
-                .query_map(params![&id], |row| row.get(0))?
-                .collect();
-            let images = images.ok().filter(|v| !v.is_empty());
+        match tag {
+            Ok(t) => Ok(Some(t)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-            result.push(Message {
+    pub fn update_tag(&self, tag: &Tag) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+            params![tag.name, tag.color, tag.id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_tag(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn add_thread_tag(&self, thread_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO thread_tags (thread_id, tag_id) VALUES (?1, ?2)",
+            params![thread_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_thread_tag(&self, thread_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM thread_tags WHERE thread_id = ?1 AND tag_id = ?2",
+            params![thread_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    // Search operations
+    pub fn search_messages(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>> {
+        let Some(fts_query) = to_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.thread_id, t.name, m.role,
+                    snippet(messages_fts, -1, '<mark>', '</mark>', '…', 16),
+                    m.created_at, bm25(messages_fts) AS rank,
+                    json_extract(m.metadata, '$.provider'),
+                    json_extract(m.metadata, '$.model')
+             FROM messages_fts f
+             JOIN messages m ON m.id = f.message_id
+             JOIN threads t ON t.id = m.thread_id
+             WHERE messages_fts MATCH ?1
+               AND m.deleted_at IS NULL AND t.deleted_at IS NULL
+               AND (?2 IS NULL OR m.role = ?2)
+               AND (?3 IS NULL OR json_extract(m.metadata, '$.provider') = ?3)
+               AND (?4 IS NULL OR json_extract(m.metadata, '$.model') = ?4)
+               AND (?5 IS NULL OR m.created_at >= ?5)
+               AND (?6 IS NULL OR m.created_at <= ?6)
+             ORDER BY rank
+             LIMIT ?7"
+        )?;
+
+        let hits = stmt.query_map(
+            params![
+                fts_query,
+                filters.role,
+                filters.provider,
+                filters.model,
+                filters.from,
+                filters.to,
+                filters.limit.unwrap_or(50)
+            ],
+            |row| {
+                Ok(SearchHit {
+                    message_id: row.get(0)?,
+                    thread_id: row.get(1)?,
+                    thread_name: row.get(2)?,
+                    role: row.get(3)?,
+                    snippet: row.get(4)?,
+                    created_at: row.get(5)?,
+                    rank: row.get(6)?,
+                    provider: row.get(7)?,
+                    model: row.get(8)?,
+                })
+            },
+        )?;
+
+        hits.collect()
+    }
+}
+
+/// Open a connection, keying it first if a passphrase is given. Reads the
+/// schema straight away so a wrong passphrase fails here rather than later.
+fn open_connection(path: &Path, passphrase: Option<&str>) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase)?;
+    }
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))?;
+
+    // Enable foreign key constraints
+    conn.execute("PRAGMA foreign_keys = ON", [])?;
+    Ok(conn)
+}
+
+/// Copy everything in `conn` to a new database at `dest` encrypted with
+/// `passphrase`
+fn export_encrypted(conn: &Connection, dest: &Path, passphrase: &str) -> Result<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        params![dest.to_string_lossy(), passphrase],
+    )?;
+    let result = conn
+        .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+        .and_then(|_| migrations::current_version(conn))
+        .and_then(|version| {
+            // sqlcipher_export doesn't carry the schema version over
+            conn.pragma_update(Some(DatabaseName::Attached("encrypted")), "user_version", version)
+        });
+    conn.execute("DETACH DATABASE encrypted", [])?;
+    result
+}
+
+/// Bytes in use, not counting pages freed since the last VACUUM
+fn used_bytes(conn: &Connection) -> Result<u64> {
+    conn.query_row(
+        "SELECT (page_count - freelist_count) * page_size
+         FROM pragma_page_count(), pragma_freelist_count(), pragma_page_size()",
+        [],
+        |row| row.get::<_, i64>(0).map(|bytes| bytes as u64),
+    )
+}
+
+/// Permanently delete threads and messages trashed before `before`
+fn purge_trash(conn: &Connection, before: i64) -> Result<usize> {
+    let threads = conn.execute("DELETE FROM threads WHERE deleted_at < ?1", params![before])?;
+    let messages = conn.execute("DELETE FROM messages WHERE deleted_at < ?1", params![before])?;
+    Ok(threads + messages)
+}
+
+fn folder_contains(conn: &Connection, ancestor_id: &str, folder_id: &str) -> Result<bool> {
+    conn.query_row(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT f.id FROM folders f JOIN subtree s ON f.parent_id = s.id
+         )
+         SELECT EXISTS (SELECT 1 FROM subtree WHERE id = ?2)",
+        params![ancestor_id, folder_id],
+        |row| row.get(0),
+    )
+}
+
+pub(crate) fn misuse(message: &str) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(ffi::Error::new(ffi::SQLITE_MISUSE), Some(message.to_string()))
+}
+
+/// Whether opening an encrypted database failed because of its passphrase
+pub fn is_wrong_passphrase(error: &rusqlite::Error) -> bool {
+    error.sqlite_error_code() == Some(ErrorCode::NotADatabase)
+}
+
+fn insert_thread(conn: &Connection, thread: &Thread) -> Result<()> {
+    conn.execute(
+        "INSERT INTO threads (id, name, created_at, updated_at, metadata, pinned, archived, folder_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            thread.id,
+            thread.name,
+            thread.created_at,
+            thread.updated_at,
+            thread.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok()),
+            thread.pinned,
+            thread.archived,
+            thread.folder_id
+        ],
+    )?;
+    Ok(())
+}
+
+fn import_key<'a>(thread: &'a Thread, key: &str) -> Option<&'a str> {
+    thread.metadata.as_ref()?.get(key)?.as_str()
+}
+
+fn thread_from_row(row: &rusqlite::Row) -> Result<Thread> {
+    let tags: Option<String> = row.get(10)?;
+    let metadata: Option<String> = row.get(11)?;
+
+    Ok(Thread {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+        message_count: row.get(4).ok(),
+        last_message: row.get(5).ok(),
+        thumbnail: row
+            .get::<_, Option<Vec<u8>>>(6)?
+            .map(|t| images::to_base64(&t)),
+        pinned: row.get(7)?,
+        archived: row.get(8)?,
+        folder_id: row.get(9)?,
+        tags: tags
+            .map(|t| t.split(',').map(String::from).collect())
+            .unwrap_or_default(),
+        metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        ephemeral: false,
+    })
+}
+
+fn decode_images(message: &Message) -> Result<Vec<ImageBlob>> {
+    message
+        .images
+        .iter()
+        .flatten()
+        .map(|data| ImageBlob::from_base64(data))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Insert a message and its images, sharing blobs with identical
+/// earlier attachments
+fn insert_message(conn: &Connection, message: &Message, blobs: &[ImageBlob]) -> Result<()> {
+    conn.execute(
+        "INSERT INTO messages (id, thread_id, role, content, created_at, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            message.id,
+            message.thread_id,
+            message.role.as_str(),
+            message.content,
+            message.created_at,
+            message.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok())
+        ],
+    )?;
+
+    for (idx, blob) in blobs.iter().enumerate() {
+        conn.execute(
+            "INSERT OR IGNORE INTO image_blobs (hash, data, mime_type, width, height, byte_size, thumbnail, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                blob.hash,
+                blob.data,
+                blob.mime_type,
+                blob.width,
+                blob.height,
+                blob.byte_size() as i64,
+                blob.thumbnail,
+                message.created_at
+            ],
+        )?;
+        conn.execute(
+            "INSERT INTO images (id, message_id, hash, position, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![format!("{}-{}", message.id, idx), message.id, blob.hash, idx as i64, message.created_at],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Run a message query selecting `MESSAGE_COLUMNS` plus image data (or
+/// NULL), ordered by message then image position. Rows of the same
+/// message are folded together, so one query loads messages and images.
+fn query_messages(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+    with_data: bool,
+) -> Result<Vec<Message>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(params)?;
+    let mut messages: Vec<Message> = Vec::new();
+
+    while let Some(row) = rows.next()? {
+        let id: String = row.get(0)?;
+        if messages.last().map(|m| &m.id) != Some(&id) {
+            let role_str: String = row.get(2)?;
+            let metadata_str: Option<String> = row.get(5)?;
+            messages.push(Message {
                 id,
-                thread_id,
-                role,
-                content,
-                created_at,
-                metadata,
-                images,
+                thread_id: row.get(1)?,
+                role: MessageRole::from_str(&role_str).unwrap_or(MessageRole::User),
+                content: row.get(3)?,
+                created_at: row.get(4)?,
+                metadata: metadata_str.and_then(|s| serde_json::from_str(&s).ok()),
+                images: None,
+                image_refs: None,
             });
         }
 
-        Ok(result)
+        let Some(image_id) = row.get::<_, Option<String>>(6)? else {
+            continue;
+        };
+        let message = messages.last_mut().expect("message pushed above");
+        message.image_refs.get_or_insert_with(Vec::new).push(ImageRef {
+            id: image_id,
+            mime_type: row.get(7)?,
+            width: row.get(8)?,
+            height: row.get(9)?,
+            byte_size: row.get(10)?,
+        });
+        if with_data {
+            let data: Vec<u8> = row.get(11)?;
+            message.images.get_or_insert_with(Vec::new).push(images::to_base64(&data));
+        }
     }
 
-    pub fn delete_message(&self, id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM messages WHERE id = ?1", params![id])?;
-        Ok(())
+    Ok(messages)
+}
+
+/// Turn free text into an FTS5 query: every word must match, the last one
+/// as a prefix so results update while typing. Quoting each word keeps
+/// FTS syntax characters in user input from being interpreted.
+fn to_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+
+    let last = terms.len().checked_sub(1)?;
+    let query = terms
+        .iter()
+        .enumerate()
+        .map(|(i, term)| if i == last { format!("{}*", term) } else { term.clone() })
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn seed(db: &Database) -> Thread {
+        let thread = Thread::new("Secret plans".to_string());
+        db.create_thread(&thread).unwrap();
+        db.create_message(&Message::new(
+            thread.id.clone(),
+            MessageRole::User,
+            "customer token rotation".to_string(),
+        ))
+        .unwrap();
+        thread
+    }
+
+    #[test]
+    fn test_encrypt_in_place_requires_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("seeva.db");
+        let db = Database::new(path.clone(), None).unwrap();
+        let thread = seed(&db);
+        assert!(!Database::file_is_encrypted(&path));
+
+        db.encrypt("hunter22").unwrap();
+        assert!(db.is_encrypted());
+        assert!(Database::file_is_encrypted(&path));
+        // The open handle keeps working after the swap
+        assert_eq!(db.get_messages(&thread.id).unwrap().len(), 1);
+        drop(db);
+
+        let err = Database::new(path.clone(), None).err().unwrap();
+        assert!(is_wrong_passphrase(&err));
+        let err = Database::new(path.clone(), Some("wrong")).err().unwrap();
+        assert!(is_wrong_passphrase(&err));
+
+        let db = Database::new(path, Some("hunter22")).unwrap();
+        assert_eq!(db.schema_version().unwrap(), migrations::latest_version());
+        let hits = db.search_messages("token", &SearchFilters::default()).unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_change_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("seeva.db");
+        let db = Database::new(path.clone(), None).unwrap();
+        seed(&db);
+        db.encrypt("first").unwrap();
+
+        assert!(is_wrong_passphrase(&db.change_passphrase("nope", "second").unwrap_err()));
+        db.change_passphrase("first", "second").unwrap();
+        drop(db);
+
+        assert!(Database::new(path.clone(), Some("first")).is_err());
+        assert!(Database::new(path, Some("second")).is_ok());
+    }
+
+    #[test]
+    fn test_restore_plaintext_backup_into_encrypted_database() {
+        let dir = TempDir::new().unwrap();
+        let plain = Database::new(dir.path().join("plain.db"), None).unwrap();
+        let thread = seed(&plain);
+        let snapshot = dir.path().join("snapshot.db");
+        plain.backup_to(&snapshot).unwrap();
+
+        let path = dir.path().join("seeva.db");
+        let db = Database::new(path.clone(), None).unwrap();
+        db.encrypt("hunter22").unwrap();
+        db.restore_from(&snapshot).unwrap();
+        assert_eq!(db.get_messages(&thread.id).unwrap().len(), 1);
+
+        // Backups of an encrypted database are encrypted too
+        let encrypted_snapshot = dir.path().join("encrypted.db");
+        db.backup_to(&encrypted_snapshot).unwrap();
+        assert!(Database::file_is_encrypted(&encrypted_snapshot));
+        assert!(plain.restore_from(&encrypted_snapshot).is_err());
+        drop(db);
+
+        let db = Database::new(path, Some("hunter22")).unwrap();
+        assert_eq!(db.list_threads(&ThreadFilters::default()).unwrap().len(), 1);
     }
 }
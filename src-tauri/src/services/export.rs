@@ -0,0 +1,412 @@
+use crate::models::{Folder, ImageRef, Message, MessageRole, Tag, Thread};
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Identifies Seeva JSON exports
+pub const EXPORT_FORMAT_ID: &str = "seeva-export";
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("Failed to write export: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to serialize export: {0}")]
+    SerializationError(#[from] serde_json::Error),
+
+    #[error("Not a Seeva export (format '{0}')")]
+    UnknownFormat(String),
+
+    #[error("Unsupported Seeva export version {0}")]
+    UnsupportedVersion(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Html => "HTML",
+        }
+    }
+}
+
+/// How Markdown exports carry images
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageMode {
+    /// Inline `data:` URLs, so the file stands alone
+    #[default]
+    Embedded,
+    /// Separate files in a `<name>_images` folder next to the export
+    Sidecar,
+}
+
+/// A thread with all of its messages, images included as base64
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedThread {
+    pub thread: Thread,
+    pub messages: Vec<Message>,
+}
+
+/// The lossless JSON export; re-importing it recreates threads exactly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    pub threads: Vec<ExportedThread>,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+impl ExportBundle {
+    pub fn new(threads: Vec<ExportedThread>, folders: Vec<Folder>, tags: Vec<Tag>) -> Self {
+        Self {
+            format: EXPORT_FORMAT_ID.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: chrono::Utc::now().timestamp_millis(),
+            threads,
+            folders,
+            tags,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ExportError> {
+        let bundle: Self = serde_json::from_str(json)?;
+        if bundle.format != EXPORT_FORMAT_ID {
+            return Err(ExportError::UnknownFormat(bundle.format));
+        }
+        if bundle.version > EXPORT_VERSION {
+            return Err(ExportError::UnsupportedVersion(bundle.version));
+        }
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> Result<String, ExportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Write one thread as Markdown or HTML. JSON exports go through
+/// `ExportBundle` so they also carry folders and tags.
+pub fn write_thread(
+    path: &Path,
+    exported: &ExportedThread,
+    format: ExportFormat,
+    image_mode: ImageMode,
+) -> Result<(), ExportError> {
+    match format {
+        ExportFormat::Markdown => {
+            let sidecar_dir = match image_mode {
+                ImageMode::Embedded => None,
+                ImageMode::Sidecar => {
+                    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    Some(format!("{}_images", stem))
+                }
+            };
+            let (markdown, sidecars) = render_markdown(exported, sidecar_dir.as_deref());
+
+            if let Some(dir) = &sidecar_dir {
+                if !sidecars.is_empty() {
+                    let dir = path.with_file_name(dir);
+                    fs::create_dir_all(&dir)?;
+                    for (file_name, data) in sidecars {
+                        fs::write(dir.join(file_name), data)?;
+                    }
+                }
+            }
+            fs::write(path, markdown)?;
+        }
+        ExportFormat::Html => fs::write(path, render_html(exported))?,
+        ExportFormat::Json => {
+            let bundle = ExportBundle::new(vec![exported.clone()], Vec::new(), Vec::new());
+            fs::write(path, bundle.to_json()?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Render a thread as Markdown. With `sidecar_dir`, images are returned as
+/// `(file name, bytes)` pairs to be written into that folder.
+pub fn render_markdown(
+    exported: &ExportedThread,
+    sidecar_dir: Option<&str>,
+) -> (String, Vec<(String, Vec<u8>)>) {
+    let thread = &exported.thread;
+    let mut out = format!("# {}\n\n", thread.name);
+    out.push_str(&format!("- Created: {}\n", format_timestamp(thread.created_at)));
+    out.push_str(&format!("- Updated: {}\n", format_timestamp(thread.updated_at)));
+    out.push_str(&format!("- Messages: {}\n", exported.messages.len()));
+
+    let mut sidecars = Vec::new();
+    for message in &exported.messages {
+        out.push_str(&format!(
+            "\n---\n\n## {} · {}\n\n",
+            speaker(message),
+            format_timestamp(message.created_at)
+        ));
+        out.push_str(message.content.trim_end());
+        out.push('\n');
+
+        for (idx, (data, mime_type)) in message_images(message).enumerate() {
+            let alt = format!("Image {}", idx + 1);
+            match sidecar_dir {
+                Some(dir) => {
+                    let file_name = format!("{}-{}.{}", message.id, idx, mime_extension(&mime_type));
+                    out.push_str(&format!("\n![{}]({}/{})\n", alt, dir, file_name));
+                    if let Ok(bytes) = general_purpose::STANDARD.decode(data) {
+                        sidecars.push((file_name, bytes));
+                    }
+                }
+                None => {
+                    out.push_str(&format!("\n![{}](data:{};base64,{})\n", alt, mime_type, data));
+                }
+            }
+        }
+    }
+
+    (out, sidecars)
+}
+
+/// Render a thread as a standalone HTML page with inline images
+pub fn render_html(exported: &ExportedThread) -> String {
+    let thread = &exported.thread;
+    let mut body = String::new();
+
+    for message in &exported.messages {
+        let class = match message.role {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::System => "system",
+        };
+        body.push_str(&format!(
+            "<section class=\"message {}\">\n<header>{} · {}</header>\n<div class=\"content\">{}</div>\n",
+            class,
+            escape_html(&speaker(message)),
+            format_timestamp(message.created_at),
+            escape_html(&message.content)
+        ));
+        for (idx, (data, mime_type)) in message_images(message).enumerate() {
+            body.push_str(&format!(
+                "<img src=\"data:{};base64,{}\" alt=\"Image {}\">\n",
+                escape_html(&mime_type),
+                data,
+                idx + 1
+            ));
+        }
+        body.push_str("</section>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; max-width: 760px; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }}
+h1 {{ margin-bottom: 0.25rem; }}
+.meta {{ color: #656d76; font-size: 0.85rem; margin-bottom: 2rem; }}
+.message {{ border-radius: 10px; padding: 0.75rem 1rem; margin: 1rem 0; }}
+.message.user {{ background: #ddf4ff; }}
+.message.assistant {{ background: #f6f8fa; }}
+.message.system {{ background: #fff8c5; }}
+.message header {{ font-size: 0.8rem; color: #656d76; margin-bottom: 0.4rem; }}
+.content {{ white-space: pre-wrap; line-height: 1.55; }}
+.message img {{ max-width: 100%; border-radius: 6px; margin-top: 0.5rem; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<div class="meta">Created {created} · {count} messages</div>
+{body}</body>
+</html>
+"#,
+        title = escape_html(&thread.name),
+        created = format_timestamp(thread.created_at),
+        count = exported.messages.len(),
+        body = body
+    )
+}
+
+/// A file name derived from a thread name, safe on all platforms
+pub fn safe_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.');
+
+    if cleaned.is_empty() {
+        "conversation".to_string()
+    } else {
+        cleaned.chars().take(100).collect()
+    }
+}
+
+/// Base64 data and MIME type of each image on a message
+fn message_images(message: &Message) -> impl Iterator<Item = (&str, String)> {
+    let refs: &[ImageRef] = message.image_refs.as_deref().unwrap_or_default();
+    message
+        .images
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(move |(idx, data)| {
+            let mime_type = refs
+                .get(idx)
+                .map(|r| r.mime_type.clone())
+                .unwrap_or_else(|| "image/jpeg".to_string());
+            (data.as_str(), mime_type)
+        })
+}
+
+fn speaker(message: &Message) -> String {
+    match message.role {
+        MessageRole::User => "User".to_string(),
+        MessageRole::System => "System".to_string(),
+        MessageRole::Assistant => {
+            let model = message
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("model"))
+                .and_then(|m| m.as_str());
+            match model {
+                Some(model) => format!("Assistant ({})", model),
+                None => "Assistant".to_string(),
+            }
+        }
+    }
+}
+
+fn format_timestamp(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn mime_extension(mime_type: &str) -> &str {
+    match mime_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "bin",
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn sample_thread() -> ExportedThread {
+        let thread = Thread::new("Docker <notes>".to_string());
+        let mut question = Message::new(thread.id.clone(), MessageRole::User, "What is a volume?".to_string());
+        question.images = Some(vec!["aGVsbG8=".to_string()]);
+        question.image_refs = Some(vec![ImageRef {
+            id: format!("{}-0", question.id),
+            mime_type: "image/png".to_string(),
+            width: Some(1),
+            height: Some(1),
+            byte_size: 5,
+        }]);
+
+        let mut answer = Message::new(thread.id.clone(), MessageRole::Assistant, "Persistent storage.".to_string());
+        answer.metadata = Some(HashMap::from([("model".to_string(), serde_json::json!("test-model"))]));
+
+        ExportedThread {
+            thread,
+            messages: vec![question, answer],
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let exported = sample_thread();
+        let bundle = ExportBundle::new(vec![exported.clone()], Vec::new(), Vec::new());
+
+        let restored = ExportBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        let thread = &restored.threads[0];
+        assert_eq!(thread.thread.id, exported.thread.id);
+        assert_eq!(thread.messages.len(), 2);
+        assert_eq!(thread.messages[0].images, exported.messages[0].images);
+        assert_eq!(thread.messages[1].metadata, exported.messages[1].metadata);
+        assert_eq!(thread.messages[1].created_at, exported.messages[1].created_at);
+
+        assert!(matches!(
+            ExportBundle::from_json(r#"{"format":"other","version":1,"exportedAt":0,"threads":[]}"#),
+            Err(ExportError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_markdown_embeds_or_writes_sidecar_images() {
+        let exported = sample_thread();
+
+        let (markdown, sidecars) = render_markdown(&exported, None);
+        assert!(markdown.starts_with("# Docker <notes>"));
+        assert!(markdown.contains("## Assistant (test-model)"));
+        assert!(markdown.contains("(data:image/png;base64,aGVsbG8=)"));
+        assert!(sidecars.is_empty());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.md");
+        write_thread(&path, &exported, ExportFormat::Markdown, ImageMode::Sidecar).unwrap();
+
+        let markdown = fs::read_to_string(&path).unwrap();
+        let file_name = format!("{}-0.png", exported.messages[0].id);
+        assert!(markdown.contains(&format!("(notes_images/{})", file_name)));
+        assert_eq!(fs::read(temp_dir.path().join("notes_images").join(file_name)).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_html_escapes_content_and_inlines_images() {
+        let html = render_html(&sample_thread());
+        assert!(html.contains("<title>Docker &lt;notes&gt;</title>"));
+        assert!(html.contains("src=\"data:image/png;base64,aGVsbG8=\""));
+    }
+
+    #[test]
+    fn test_safe_file_name() {
+        assert_eq!(safe_file_name("a/b: c?"), "a_b_ c_");
+        assert_eq!(safe_file_name("  ..  "), "conversation");
+    }
+}
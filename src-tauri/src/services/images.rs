@@ -0,0 +1,128 @@
+use base64::{engine::general_purpose, Engine};
+use image::ImageEncoder;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+/// Longest side of generated thumbnails, in pixels
+const THUMBNAIL_SIZE: u32 = 160;
+const THUMBNAIL_QUALITY: u8 = 75;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageError {
+    #[error("Invalid base64 image data: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+
+    #[error("Failed to encode thumbnail: {0}")]
+    ThumbnailError(#[from] image::ImageError),
+}
+
+/// An image ready to be stored, content-addressed by its SHA-256 hash
+#[derive(Debug, Clone)]
+pub struct ImageBlob {
+    pub hash: String,
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+impl ImageBlob {
+    /// Decode a base64 image (optionally a `data:` URL) as sent by the frontend
+    pub fn from_base64(encoded: &str) -> Result<Self, ImageError> {
+        let encoded = match encoded.split_once(";base64,") {
+            Some((prefix, data)) if prefix.starts_with("data:") => data,
+            _ => encoded,
+        };
+        let data = general_purpose::STANDARD.decode(encoded.trim())?;
+        Self::from_bytes(data)
+    }
+
+    /// Inspect raw image bytes. Data that can't be decoded as an image is
+    /// still stored, just without dimensions or a thumbnail; so is an image
+    /// whose thumbnail fails to encode.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ImageError> {
+        let hash = format!("{:x}", Sha256::digest(&data));
+        let format = image::guess_format(&data).ok();
+        let mime_type = format
+            .map(|f| f.to_mime_type().to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        let decoded = format.and_then(|f| image::load_from_memory_with_format(&data, f).ok());
+        let (width, height, thumbnail) = match decoded {
+            Some(img) => {
+                let thumbnail = make_thumbnail(&img)
+                    .map_err(|e| eprintln!("   ⚠️  Storing image without thumbnail: {}", e))
+                    .ok();
+                (Some(img.width()), Some(img.height()), thumbnail)
+            }
+            None => (None, None, None),
+        };
+
+        Ok(Self {
+            hash,
+            data,
+            mime_type,
+            width,
+            height,
+            thumbnail,
+        })
+    }
+
+    pub fn byte_size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+/// Downscale to fit `THUMBNAIL_SIZE` and encode as JPEG
+fn make_thumbnail(img: &image::DynamicImage) -> Result<Vec<u8>, ImageError> {
+    let thumb = img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+
+    let mut bytes = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(Cursor::new(&mut bytes), THUMBNAIL_QUALITY)
+        .write_image(
+            thumb.as_raw(),
+            thumb.width(),
+            thumb.height(),
+            image::ExtendedColorType::Rgb8,
+        )?;
+    Ok(bytes)
+}
+
+pub fn to_base64(data: &[u8]) -> String {
+    general_purpose::STANDARD.encode(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_base64(width: u32, height: u32) -> String {
+        let img = image::RgbImage::from_pixel(width, height, image::Rgb([200, 10, 10]));
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgb8(img)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        to_base64(&bytes)
+    }
+
+    #[test]
+    fn test_blob_records_format_dimensions_and_thumbnail() {
+        let blob = ImageBlob::from_base64(&png_base64(640, 320)).unwrap();
+
+        assert_eq!(blob.mime_type, "image/png");
+        assert_eq!((blob.width, blob.height), (Some(640), Some(320)));
+        assert_eq!(blob.hash.len(), 64);
+
+        let thumb = image::load_from_memory(&blob.thumbnail.unwrap()).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+    }
+
+    #[test]
+    fn test_same_image_hashes_the_same() {
+        let encoded = png_base64(8, 8);
+        let a = ImageBlob::from_base64(&encoded).unwrap();
+        let b = ImageBlob::from_base64(&format!("data:image/png;base64,{}", encoded)).unwrap();
+        assert_eq!(a.hash, b.hash);
+    }
+}
@@ -0,0 +1,545 @@
+use crate::models::{Folder, Message, MessageRole, Tag, Thread};
+use crate::services::export::ExportBundle;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Thread metadata keys recording where an imported thread came from
+pub const IMPORT_SOURCE_KEY: &str = "importSource";
+pub const IMPORT_ID_KEY: &str = "importId";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Failed to read import file: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to read archive: {0}")]
+    ArchiveError(#[from] zip::result::ZipError),
+
+    #[error("Invalid JSON: {0}")]
+    ParseError(#[from] serde_json::Error),
+
+    #[error("No conversations.json found in archive")]
+    MissingConversations,
+
+    #[error("Unrecognized export format")]
+    UnknownFormat,
+
+    #[error("{0}")]
+    InvalidExport(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    ChatGpt,
+    Claude,
+    Seeva,
+}
+
+impl ImportSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportSource::ChatGpt => "chatgpt",
+            ImportSource::Claude => "claude",
+            ImportSource::Seeva => "seeva",
+        }
+    }
+}
+
+/// A conversation mapped onto Seeva's models, ready to be stored
+#[derive(Debug, Clone)]
+pub struct ImportedConversation {
+    pub thread: Thread,
+    pub messages: Vec<Message>,
+    /// Tags to attach, matched to existing tags by name
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedImport {
+    pub source: ImportSource,
+    pub conversations: Vec<ImportedConversation>,
+    /// Seeva exports only; parents come before their children
+    pub folders: Vec<Folder>,
+    /// Conversations that could not be mapped
+    pub failures: Vec<ImportResult>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+/// Outcome for a single conversation
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub title: String,
+    pub status: ImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    pub message_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportResult {
+    pub fn imported(conversation: &ImportedConversation) -> Self {
+        Self {
+            title: conversation.thread.name.clone(),
+            status: ImportStatus::Imported,
+            thread_id: Some(conversation.thread.id.clone()),
+            message_count: conversation.messages.len(),
+            error: None,
+        }
+    }
+
+    pub fn skipped(conversation: &ImportedConversation, existing_thread_id: String) -> Self {
+        Self {
+            title: conversation.thread.name.clone(),
+            status: ImportStatus::Skipped,
+            thread_id: Some(existing_thread_id),
+            message_count: conversation.messages.len(),
+            error: None,
+        }
+    }
+
+    pub fn failed(title: String, error: impl ToString) -> Self {
+        Self {
+            title,
+            status: ImportStatus::Failed,
+            thread_id: None,
+            message_count: 0,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub source: ImportSource,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub results: Vec<ImportResult>,
+}
+
+impl ImportReport {
+    pub fn new(source: ImportSource, results: Vec<ImportResult>) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        Self {
+            source,
+            imported: count(ImportStatus::Imported),
+            skipped: count(ImportStatus::Skipped),
+            failed: count(ImportStatus::Failed),
+            results,
+        }
+    }
+}
+
+/// Read a `.json` export or a `.zip` archive containing `conversations.json`
+pub fn read_file(path: &Path) -> Result<ParsedImport, ImportError> {
+    let is_zip = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+
+    let json = if is_zip {
+        let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+        let name = archive
+            .file_names()
+            .find(|n| n.rsplit('/').next() == Some("conversations.json"))
+            .map(String::from)
+            .ok_or(ImportError::MissingConversations)?;
+        let mut json = String::new();
+        archive.by_name(&name)?.read_to_string(&mut json)?;
+        json
+    } else {
+        fs::read_to_string(path)?
+    };
+
+    parse(&json)
+}
+
+/// Detect the export format and map every conversation in it
+pub fn parse(json: &str) -> Result<ParsedImport, ImportError> {
+    let value: Value = serde_json::from_str(json)?;
+
+    if value.get("format").is_some() {
+        let bundle = ExportBundle::from_json(json)
+            .map_err(|e| ImportError::InvalidExport(e.to_string()))?;
+        return Ok(parse_seeva(bundle));
+    }
+
+    let conversations = value.as_array().ok_or(ImportError::UnknownFormat)?;
+    let source = match conversations.first() {
+        Some(first) if first.get("mapping").is_some() => ImportSource::ChatGpt,
+        Some(first) if first.get("chat_messages").is_some() => ImportSource::Claude,
+        Some(_) => return Err(ImportError::UnknownFormat),
+        None => ImportSource::ChatGpt,
+    };
+
+    let mut parsed = ParsedImport {
+        source,
+        conversations: Vec::new(),
+        folders: Vec::new(),
+        failures: Vec::new(),
+    };
+    for conversation in conversations {
+        let result = match source {
+            ImportSource::ChatGpt => parse_chatgpt(conversation),
+            _ => parse_claude(conversation),
+        };
+        match result {
+            Ok(c) => parsed.conversations.push(c),
+            Err(e) => {
+                let title = conversation
+                    .get("title")
+                    .or_else(|| conversation.get("name"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("Untitled")
+                    .to_string();
+                parsed.failures.push(ImportResult::failed(title, e));
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_seeva(bundle: ExportBundle) -> ParsedImport {
+    let tags_by_id: HashMap<&str, &Tag> = bundle.tags.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    let conversations = bundle
+        .threads
+        .iter()
+        .map(|exported| {
+            let mut thread = exported.thread.clone();
+            let tags = thread
+                .tags
+                .iter()
+                .filter_map(|id| tags_by_id.get(id.as_str()).map(|t| (*t).clone()))
+                .collect();
+            thread.tags.clear();
+            // Derived fields are recomputed once stored
+            thread.message_count = None;
+            thread.last_message = None;
+            thread.thumbnail = None;
+
+            let messages = exported
+                .messages
+                .iter()
+                .cloned()
+                .map(|mut m| {
+                    m.image_refs = None;
+                    m
+                })
+                .collect();
+
+            ImportedConversation { thread, messages, tags }
+        })
+        .collect();
+
+    ParsedImport {
+        source: ImportSource::Seeva,
+        conversations,
+        folders: order_folders(bundle.folders),
+        failures: Vec::new(),
+    }
+}
+
+/// Sort folders so that each parent precedes its children
+fn order_folders(mut remaining: Vec<Folder>) -> Vec<Folder> {
+    let mut ordered: Vec<Folder> = Vec::with_capacity(remaining.len());
+
+    while !remaining.is_empty() {
+        let before = remaining.len();
+        let (ready, rest): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|f| {
+            f.parent_id.as_ref().is_none_or(|p| ordered.iter().any(|o| &o.id == p))
+        });
+        ordered.extend(ready);
+        remaining = rest;
+
+        if remaining.len() == before {
+            // Parent missing from the export; attach to the top level
+            for mut folder in remaining.drain(..) {
+                folder.parent_id = None;
+                ordered.push(folder);
+            }
+        }
+    }
+
+    ordered
+}
+
+/// ChatGPT stores each conversation as a tree of nodes. The visible
+/// branch is the path from `current_node` back up to the root.
+fn parse_chatgpt(conversation: &Value) -> Result<ImportedConversation, String> {
+    let source_id = conversation
+        .get("conversation_id")
+        .or_else(|| conversation.get("id"))
+        .and_then(Value::as_str)
+        .ok_or("Conversation has no id")?;
+    let title = conversation.get("title").and_then(Value::as_str).unwrap_or("Untitled");
+    let mapping = conversation
+        .get("mapping")
+        .and_then(Value::as_object)
+        .ok_or("Conversation has no messages")?;
+    let created_at = seconds_to_millis(conversation.get("create_time")).unwrap_or(0);
+    let updated_at = seconds_to_millis(conversation.get("update_time")).unwrap_or(created_at);
+
+    let mut branch = Vec::new();
+    let mut node_id = conversation.get("current_node").and_then(Value::as_str);
+    while let Some(id) = node_id {
+        let node = mapping.get(id).ok_or_else(|| format!("Missing node {}", id))?;
+        branch.push(node);
+        node_id = node.get("parent").and_then(Value::as_str);
+        if branch.len() > mapping.len() {
+            return Err("Conversation tree contains a cycle".to_string());
+        }
+    }
+    branch.reverse();
+
+    let thread = imported_thread(ImportSource::ChatGpt, source_id, title, created_at, updated_at);
+    let mut messages = Vec::new();
+    for node in branch {
+        let Some(message) = node.get("message").filter(|m| !m.is_null()) else {
+            continue;
+        };
+        let role = match message.pointer("/author/role").and_then(Value::as_str) {
+            Some("user") => MessageRole::User,
+            Some("assistant") => MessageRole::Assistant,
+            // System prompts and tool calls aren't shown in ChatGPT either
+            _ => continue,
+        };
+
+        let content = message
+            .pointer("/content/parts")
+            .and_then(Value::as_array)
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            })
+            .or_else(|| message.pointer("/content/text").and_then(Value::as_str).map(String::from))
+            .unwrap_or_default();
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let mut msg = Message::new(thread.id.clone(), role, content);
+        msg.created_at = seconds_to_millis(message.get("create_time"))
+            .unwrap_or(created_at + messages.len() as i64);
+        if let Some(model) = message.pointer("/metadata/model_slug").and_then(Value::as_str) {
+            msg.metadata = Some(HashMap::from([
+                ("provider".to_string(), Value::from("openai")),
+                ("model".to_string(), Value::from(model)),
+            ]));
+        }
+        messages.push(msg);
+    }
+
+    Ok(ImportedConversation { thread, messages, tags: Vec::new() })
+}
+
+fn parse_claude(conversation: &Value) -> Result<ImportedConversation, String> {
+    let source_id = conversation
+        .get("uuid")
+        .and_then(Value::as_str)
+        .ok_or("Conversation has no uuid")?;
+    let title = conversation
+        .get("name")
+        .and_then(Value::as_str)
+        .filter(|n| !n.is_empty())
+        .unwrap_or("Untitled");
+    let created_at = iso_to_millis(conversation.get("created_at")).unwrap_or(0);
+    let updated_at = iso_to_millis(conversation.get("updated_at")).unwrap_or(created_at);
+    let chat_messages = conversation
+        .get("chat_messages")
+        .and_then(Value::as_array)
+        .ok_or("Conversation has no messages")?;
+
+    let thread = imported_thread(ImportSource::Claude, source_id, title, created_at, updated_at);
+    let mut messages = Vec::new();
+    for message in chat_messages {
+        let role = match message.get("sender").and_then(Value::as_str) {
+            Some("human") => MessageRole::User,
+            Some("assistant") => MessageRole::Assistant,
+            _ => continue,
+        };
+
+        // Newer exports split text into content blocks
+        let blocks: Vec<&str> = message
+            .get("content")
+            .and_then(Value::as_array)
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|b| b.get("type").and_then(Value::as_str) == Some("text"))
+                    .filter_map(|b| b.get("text").and_then(Value::as_str))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let content = if blocks.is_empty() {
+            message.get("text").and_then(Value::as_str).unwrap_or_default().to_string()
+        } else {
+            blocks.join("\n\n")
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let mut msg = Message::new(thread.id.clone(), role, content);
+        msg.created_at = iso_to_millis(message.get("created_at"))
+            .unwrap_or(created_at + messages.len() as i64);
+        if msg.role == MessageRole::Assistant {
+            msg.metadata = Some(HashMap::from([("provider".to_string(), Value::from("anthropic"))]));
+        }
+        messages.push(msg);
+    }
+
+    Ok(ImportedConversation { thread, messages, tags: Vec::new() })
+}
+
+fn imported_thread(
+    source: ImportSource,
+    source_id: &str,
+    title: &str,
+    created_at: i64,
+    updated_at: i64,
+) -> Thread {
+    let mut thread = Thread::new(title.to_string());
+    thread.created_at = created_at;
+    thread.updated_at = updated_at;
+    thread.metadata = Some(HashMap::from([
+        (IMPORT_SOURCE_KEY.to_string(), Value::from(source.as_str())),
+        (IMPORT_ID_KEY.to_string(), Value::from(source_id)),
+    ]));
+    thread
+}
+
+fn seconds_to_millis(value: Option<&Value>) -> Option<i64> {
+    value.and_then(Value::as_f64).map(|s| (s * 1000.0) as i64)
+}
+
+fn iso_to_millis(value: Option<&Value>) -> Option<i64> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|d| d.timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::export::ExportedThread;
+
+    const CHATGPT_EXPORT: &str = r#"[{
+        "id": "conv-1",
+        "title": "Docker volumes",
+        "create_time": 1700000000.5,
+        "update_time": 1700000100.0,
+        "current_node": "n3",
+        "mapping": {
+            "root": {"id": "root", "message": null, "parent": null, "children": ["n1"]},
+            "n1": {"id": "n1", "parent": "root", "children": ["n2", "n2b"], "message": {
+                "author": {"role": "user"}, "create_time": 1700000001.0,
+                "content": {"content_type": "text", "parts": ["What is a volume?"]}}},
+            "n2b": {"id": "n2b", "parent": "n1", "children": [], "message": {
+                "author": {"role": "assistant"}, "create_time": 1700000002.0,
+                "content": {"content_type": "text", "parts": ["Abandoned branch"]}}},
+            "n2": {"id": "n2", "parent": "n1", "children": ["n3"], "message": {
+                "author": {"role": "tool"}, "create_time": 1700000002.0,
+                "content": {"content_type": "text", "parts": ["tool output"]}}},
+            "n3": {"id": "n3", "parent": "n2", "children": [], "message": {
+                "author": {"role": "assistant"}, "create_time": 1700000003.0,
+                "metadata": {"model_slug": "gpt-4o"},
+                "content": {"content_type": "text", "parts": ["Persistent storage."]}}}
+        }
+    }]"#;
+
+    const CLAUDE_EXPORT: &str = r#"[{
+        "uuid": "c-1",
+        "name": "Rust lifetimes",
+        "created_at": "2024-05-01T10:00:00.000000Z",
+        "updated_at": "2024-05-01T10:05:00.000000Z",
+        "chat_messages": [
+            {"uuid": "m1", "sender": "human", "text": "Explain 'a",
+             "created_at": "2024-05-01T10:00:01Z", "content": []},
+            {"uuid": "m2", "sender": "assistant", "text": "",
+             "created_at": "2024-05-01T10:00:05Z",
+             "content": [{"type": "text", "text": "A lifetime names a scope."}]}
+        ]
+    }, {"name": "Broken"}]"#;
+
+    #[test]
+    fn test_parse_chatgpt_follows_current_branch() {
+        let parsed = parse(CHATGPT_EXPORT).unwrap();
+        assert_eq!(parsed.source, ImportSource::ChatGpt);
+
+        let conversation = &parsed.conversations[0];
+        assert_eq!(conversation.thread.name, "Docker volumes");
+        assert_eq!(conversation.thread.created_at, 1_700_000_000_500);
+        let contents: Vec<_> = conversation.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["What is a volume?", "Persistent storage."]);
+        assert_eq!(conversation.messages[1].created_at, 1_700_000_003_000);
+        assert_eq!(
+            conversation.thread.metadata.as_ref().unwrap().get(IMPORT_ID_KEY),
+            Some(&Value::from("conv-1"))
+        );
+    }
+
+    #[test]
+    fn test_parse_claude_reports_broken_conversations() {
+        let parsed = parse(CLAUDE_EXPORT).unwrap();
+        assert_eq!(parsed.source, ImportSource::Claude);
+        assert_eq!(parsed.conversations.len(), 1);
+        assert_eq!(parsed.failures.len(), 1);
+        assert_eq!(parsed.failures[0].title, "Broken");
+
+        let messages = &parsed.conversations[0].messages;
+        assert_eq!(messages[0].role, MessageRole::User);
+        assert_eq!(messages[1].content, "A lifetime names a scope.");
+    }
+
+    #[test]
+    fn test_parse_seeva_keeps_ids_and_resolves_tags() {
+        let tag = Tag::new("Work".to_string(), "#3b82f6".to_string());
+        let mut thread = Thread::new("Exported".to_string());
+        thread.tags = vec![tag.id.clone()];
+        let message = Message::new(thread.id.clone(), MessageRole::User, "hi".to_string());
+        let bundle = ExportBundle::new(
+            vec![ExportedThread { thread: thread.clone(), messages: vec![message.clone()] }],
+            Vec::new(),
+            vec![tag],
+        );
+
+        let parsed = parse(&bundle.to_json().unwrap()).unwrap();
+        let conversation = &parsed.conversations[0];
+        assert_eq!(parsed.source, ImportSource::Seeva);
+        assert_eq!(conversation.thread.id, thread.id);
+        assert_eq!(conversation.messages[0].id, message.id);
+        assert_eq!(conversation.tags[0].name, "Work");
+    }
+
+    #[test]
+    fn test_order_folders_puts_parents_first() {
+        let parent = Folder::new("Parent".to_string(), None);
+        let child = Folder::new("Child".to_string(), Some(parent.id.clone()));
+        let orphan = Folder::new("Orphan".to_string(), Some("missing".to_string()));
+
+        let ordered = order_folders(vec![child.clone(), orphan, parent.clone()]);
+        assert_eq!(ordered[0].id, parent.id);
+        assert_eq!(ordered[1].id, child.id);
+        assert_eq!(ordered[2].parent_id, None);
+    }
+}
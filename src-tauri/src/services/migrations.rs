@@ -0,0 +1,504 @@
+use crate::services::images::ImageBlob;
+use rusqlite::{params, Connection, Transaction, Result};
+use std::path::{Path, PathBuf};
+
+/// A single schema change. Migrations run in order, each in its own
+/// transaction, and bump `PRAGMA user_version` to `version` on success.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> Result<()>,
+}
+
+/// All schema migrations, ordered by version. Never edit a released
+/// migration; append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "baseline schema",
+        up: baseline_schema,
+    },
+    Migration {
+        version: 2,
+        description: "full-text search over messages",
+        up: message_search,
+    },
+    Migration {
+        version: 3,
+        description: "content-addressed image blobs",
+        up: image_blobs,
+    },
+    Migration {
+        version: 4,
+        description: "thread organization",
+        up: thread_organization,
+    },
+    Migration {
+        version: 5,
+        description: "soft delete",
+        up: soft_delete,
+    },
+    Migration {
+        version: 6,
+        description: "reindex thread names only on rename",
+        up: rename_trigger_when_changed,
+    },
+];
+
+/// Schema version this build expects
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Path of the copy taken before upgrading from `version`
+pub fn backup_path(db_path: &Path, version: u32) -> PathBuf {
+    let file_name = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "seeva.db".to_string());
+    db_path.with_file_name(format!("{}.v{}.bak", file_name, version))
+}
+
+/// Bring the database up to `latest_version()`.
+///
+/// If the database already holds data, a copy is written next to it
+/// before anything changes. Returns the resulting schema version.
+pub fn run(conn: &mut Connection, db_path: &Path) -> Result<u32> {
+    let version = current_version(conn)?;
+    let latest = latest_version();
+
+    if version > latest {
+        // Written by a newer build; leave it alone rather than guess
+        eprintln!(
+            "⚠️  Database schema v{} is newer than this build (v{})",
+            version, latest
+        );
+        return Ok(version);
+    }
+    if version == latest {
+        return Ok(version);
+    }
+
+    if has_tables(conn)? {
+        let backup = backup_path(db_path, version);
+        println!("   💾 Backing up database to {}", backup.display());
+        let _ = std::fs::remove_file(&backup);
+        conn.execute("VACUUM INTO ?1", [backup.to_string_lossy()])?;
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        println!(
+            "   🔧 Migrating database to v{}: {}",
+            migration.version, migration.description
+        );
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(latest)
+}
+
+fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// v1: the schema as it existed before versioning. Uses `IF NOT EXISTS`
+/// so unversioned databases from older builds upgrade in place.
+fn baseline_schema(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS threads (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            metadata TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS messages (
+            id TEXT PRIMARY KEY,
+            thread_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            metadata TEXT,
+            FOREIGN KEY (thread_id) REFERENCES threads(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS images (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL,
+            data TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_messages_thread ON messages(thread_id);
+        CREATE INDEX IF NOT EXISTS idx_messages_created ON messages(created_at);
+        CREATE INDEX IF NOT EXISTS idx_images_message ON images(message_id);",
+    )
+}
+
+/// v2: FTS5 index over message content and thread names, kept in sync by
+/// triggers. Keyed on message id because VACUUM may renumber rowids.
+fn message_search(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE messages_fts USING fts5(
+            content,
+            thread_name,
+            message_id UNINDEXED,
+            tokenize = 'porter unicode61'
+        );
+
+        INSERT INTO messages_fts (content, thread_name, message_id)
+            SELECT m.content, t.name, m.id
+            FROM messages m JOIN threads t ON t.id = m.thread_id;
+
+        CREATE TRIGGER messages_fts_insert AFTER INSERT ON messages BEGIN
+            INSERT INTO messages_fts (content, thread_name, message_id)
+            VALUES (new.content, (SELECT name FROM threads WHERE id = new.thread_id), new.id);
+        END;
+
+        CREATE TRIGGER messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+            UPDATE messages_fts SET content = new.content WHERE message_id = old.id;
+        END;
+
+        CREATE TRIGGER messages_fts_delete AFTER DELETE ON messages BEGIN
+            DELETE FROM messages_fts WHERE message_id = old.id;
+        END;
+
+        CREATE TRIGGER threads_fts_rename AFTER UPDATE OF name ON threads BEGIN
+            UPDATE messages_fts SET thread_name = new.name
+            WHERE message_id IN (SELECT id FROM messages WHERE thread_id = new.id);
+        END;",
+    )
+}
+
+/// v3: move images out of base64 TEXT into BLOBs keyed by SHA-256, with
+/// sniffed MIME type, dimensions and a thumbnail. `images` becomes a list
+/// of per-message references; blobs are dropped with their last reference.
+fn image_blobs(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE images RENAME TO images_v2;
+
+        CREATE TABLE image_blobs (
+            hash TEXT PRIMARY KEY,
+            data BLOB NOT NULL,
+            mime_type TEXT NOT NULL,
+            width INTEGER,
+            height INTEGER,
+            byte_size INTEGER NOT NULL,
+            thumbnail BLOB,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE images (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE,
+            FOREIGN KEY (hash) REFERENCES image_blobs(hash)
+        );",
+    )?;
+
+    {
+        // One image at a time; a database full of screenshots needn't fit in memory
+        let mut select = tx.prepare(
+            "SELECT id, message_id, data, created_at FROM images_v2 ORDER BY message_id, id",
+        )?;
+        let mut rows = select.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: String = row.get(0)?;
+            let message_id: String = row.get(1)?;
+            let data: String = row.get(2)?;
+            let created_at: i64 = row.get(3)?;
+
+            let blob = match ImageBlob::from_base64(&data) {
+                Ok(blob) => blob,
+                Err(e) => {
+                    // Keep what was stored rather than lose it; it loads as
+                    // an attachment of unknown type
+                    eprintln!("   ⚠️  Keeping unreadable image {} as raw data: {}", id, e);
+                    ImageBlob::from_bytes(data.into_bytes())
+                        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?
+                }
+            };
+            // Old ids are "<message id>-<index>"
+            let position: i64 = id
+                .rsplit_once('-')
+                .and_then(|(_, idx)| idx.parse().ok())
+                .unwrap_or(0);
+
+            tx.execute(
+                "INSERT OR IGNORE INTO image_blobs
+                    (hash, data, mime_type, width, height, byte_size, thumbnail, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    blob.hash,
+                    blob.data,
+                    blob.mime_type,
+                    blob.width,
+                    blob.height,
+                    blob.byte_size() as i64,
+                    blob.thumbnail,
+                    created_at
+                ],
+            )?;
+            tx.execute(
+                "INSERT INTO images (id, message_id, hash, position, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, message_id, blob.hash, position, created_at],
+            )?;
+        }
+    }
+
+    tx.execute_batch(
+        "DROP TABLE images_v2;
+
+        CREATE INDEX idx_images_message ON images(message_id);
+        CREATE INDEX idx_images_hash ON images(hash);
+
+        CREATE TRIGGER images_release_blob AFTER DELETE ON images BEGIN
+            DELETE FROM image_blobs
+            WHERE hash = old.hash
+              AND NOT EXISTS (SELECT 1 FROM images WHERE hash = old.hash);
+        END;",
+    )
+}
+
+/// v4: pinned/archived flags, nested folders and color tags for threads
+fn thread_organization(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE folders (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            parent_id TEXT,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (parent_id) REFERENCES folders(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT NOT NULL
+        );
+
+        CREATE TABLE thread_tags (
+            thread_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY (thread_id, tag_id),
+            FOREIGN KEY (thread_id) REFERENCES threads(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+
+        ALTER TABLE threads ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE threads ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE threads ADD COLUMN folder_id TEXT REFERENCES folders(id) ON DELETE SET NULL;
+
+        CREATE INDEX idx_threads_folder ON threads(folder_id);
+        CREATE INDEX idx_folders_parent ON folders(parent_id);
+        CREATE INDEX idx_thread_tags_tag ON thread_tags(tag_id);",
+    )
+}
+
+/// v5: deleted threads and messages stay in the trash until purged
+fn soft_delete(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE threads ADD COLUMN deleted_at INTEGER;
+        ALTER TABLE messages ADD COLUMN deleted_at INTEGER;
+
+        CREATE INDEX idx_threads_deleted ON threads(deleted_at);
+        CREATE INDEX idx_messages_deleted ON messages(deleted_at);",
+    )
+}
+
+/// v6: `threads_fts_rename` fired on every `updated_at` bump because
+/// updates always set `name`, rewriting the thread's whole search index
+fn rename_trigger_when_changed(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "DROP TRIGGER threads_fts_rename;
+
+        CREATE TRIGGER threads_fts_rename AFTER UPDATE OF name ON threads
+        WHEN old.name IS NOT new.name BEGIN
+            UPDATE messages_fts SET thread_name = new.name
+            WHERE message_id IN (SELECT id FROM messages WHERE thread_id = new.id);
+        END;",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Schema written by builds before migrations existed (user_version 0)
+    const V0_FIXTURE: &str = "
+        CREATE TABLE threads (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            metadata TEXT
+        );
+        CREATE TABLE messages (
+            id TEXT PRIMARY KEY,
+            thread_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            metadata TEXT,
+            FOREIGN KEY (thread_id) REFERENCES threads(id) ON DELETE CASCADE
+        );
+        CREATE TABLE images (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL,
+            data TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
+        );
+        CREATE INDEX idx_messages_thread ON messages(thread_id);
+
+        INSERT INTO threads VALUES ('t1', 'Old thread', 1000, 2000, NULL);
+        INSERT INTO messages VALUES ('m1', 't1', 'user', 'hello', 1500, NULL);
+        INSERT INTO images VALUES ('m1-0', 'm1', 'aGVsbG8=', 'image/png', 1500);
+    ";
+
+    fn open_v0_fixture(dir: &TempDir) -> (Connection, PathBuf) {
+        let db_path = dir.path().join("seeva.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(V0_FIXTURE).unwrap();
+        (conn, db_path)
+    }
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+        let expected: Vec<u32> = (1..=MIGRATIONS.len() as u32).collect();
+        assert_eq!(versions, expected);
+    }
+
+    #[test]
+    fn test_upgrade_v0_fixture_keeps_data_and_backs_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut conn, db_path) = open_v0_fixture(&temp_dir);
+        assert_eq!(current_version(&conn).unwrap(), 0);
+
+        let version = run(&mut conn, &db_path).unwrap();
+        assert_eq!(version, latest_version());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        let content: String = conn
+            .query_row("SELECT content FROM messages WHERE id = 'm1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content, "hello");
+
+        // Existing messages are indexed for search
+        let indexed: String = conn
+            .query_row(
+                "SELECT message_id FROM messages_fts WHERE messages_fts MATCH 'hello'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, "m1");
+
+        // Base64 images are moved into blobs
+        let (mime_type, byte_size): (String, i64) = conn
+            .query_row(
+                "SELECT b.mime_type, b.byte_size FROM images i
+                 JOIN image_blobs b ON b.hash = i.hash WHERE i.message_id = 'm1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(mime_type, "application/octet-stream");
+        assert_eq!(byte_size, 5);
+
+        // Backup holds the untouched v0 database
+        let backup = Connection::open(backup_path(&db_path, 0)).unwrap();
+        assert_eq!(current_version(&backup).unwrap(), 0);
+        let threads: i64 = backup
+            .query_row("SELECT COUNT(*) FROM threads", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(threads, 1);
+    }
+
+    #[test]
+    fn test_unreadable_images_are_kept_as_raw_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut conn, db_path) = open_v0_fixture(&temp_dir);
+        conn.execute(
+            "INSERT INTO images VALUES ('m1-1', 'm1', '%% not base64 %%', 'image/png', 1500)",
+            [],
+        )
+        .unwrap();
+
+        run(&mut conn, &db_path).unwrap();
+
+        let (mime_type, data, thumbnail): (String, Vec<u8>, Option<Vec<u8>>) = conn
+            .query_row(
+                "SELECT b.mime_type, b.data, b.thumbnail FROM images i
+                 JOIN image_blobs b ON b.hash = i.hash WHERE i.id = 'm1-1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(mime_type, "application/octet-stream");
+        assert_eq!(data, b"%% not base64 %%");
+        assert_eq!(thumbnail, None);
+    }
+
+    #[test]
+    fn test_search_index_untouched_unless_renamed() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut conn, db_path) = open_v0_fixture(&temp_dir);
+        run(&mut conn, &db_path).unwrap();
+        conn.execute("INSERT INTO messages (id, thread_id, role, content, created_at) VALUES ('m2', 't1', 'user', 'again', 1600)", [])
+            .unwrap();
+
+        // Rows written, counting those written by triggers
+        let changes = |conn: &Connection, sql: &str| {
+            let before = conn.total_changes();
+            conn.execute(sql, []).unwrap();
+            conn.total_changes() - before
+        };
+        assert_eq!(changes(&conn, "UPDATE threads SET updated_at = 3000 WHERE id = 't1'"), 1);
+        assert_eq!(changes(&conn, "UPDATE threads SET name = 'Old thread', updated_at = 4000 WHERE id = 't1'"), 1);
+        // A rename rewrites the index, including FTS5's own tables
+        assert!(changes(&conn, "UPDATE threads SET name = 'Renamed' WHERE id = 't1'") > 1);
+
+        let renamed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM messages_fts WHERE thread_name = 'Renamed'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(renamed, 2);
+    }
+
+    #[test]
+    fn test_fresh_database_skips_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("seeva.db");
+        let mut conn = Connection::open(&db_path).unwrap();
+
+        run(&mut conn, &db_path).unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(!backup_path(&db_path, 0).exists());
+
+        // Running again is a no-op
+        assert_eq!(run(&mut conn, &db_path).unwrap(), latest_version());
+    }
+}
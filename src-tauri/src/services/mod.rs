@@ -1,9 +1,20 @@
 pub mod database;
+pub mod migrations;
+pub mod images;
+pub mod export;
+pub mod import;
+pub mod backup;
+pub mod retention;
 pub mod ai;
 pub mod screenshot;
 pub mod settings_manager;
+pub mod settings_validator;
+pub mod settings_policy;
+pub mod profile_manager;
+pub mod secret_store;
 pub mod context_detector;
 
 pub use database::Database;
 pub use screenshot::ScreenshotService;
 pub use settings_manager::SettingsManager;
+pub use profile_manager::ProfileManager;
@@ -0,0 +1,313 @@
+use crate::services::settings_manager::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// The profile that existed before profiles were introduced. Its files stay
+/// at the top of the app data directory.
+pub const DEFAULT_PROFILE: &str = "default";
+
+const PROFILES_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+const KEYRING_SERVICE: &str = "ai.seeva.assistant";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    #[error("Failed to access profile files: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to read or write profiles: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Unknown profile: {0}")]
+    NotFound(String),
+
+    #[error("Invalid profile name: {0}")]
+    InvalidName(String),
+
+    #[error("Cannot delete {0}")]
+    CannotDelete(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: i64,
+}
+
+/// Contents of `profiles.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileList {
+    profiles: Vec<Profile>,
+    /// Opened at startup
+    active: String,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        Self {
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Default".to_string(),
+                created_at: chrono::Utc::now().timestamp_millis(),
+            }],
+            active: DEFAULT_PROFILE.to_string(),
+        }
+    }
+}
+
+/// Named profiles, each with its own `settings.json`, `seeva.db` and API
+/// keys. The list and the last active profile are kept in `profiles.json`.
+pub struct ProfileManager {
+    app_dir: PathBuf,
+    list: Mutex<ProfileList>,
+}
+
+impl ProfileManager {
+    pub fn new(app_dir: PathBuf) -> Result<Self, ProfileError> {
+        let path = app_dir.join(PROFILES_FILE);
+        let mut list: ProfileList = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            ProfileList::default()
+        };
+        if !list.profiles.iter().any(|p| p.id == list.active) {
+            eprintln!("⚠️  Last active profile '{}' is gone, using default", list.active);
+            list.active = DEFAULT_PROFILE.to_string();
+        }
+
+        let manager = Self {
+            app_dir,
+            list: Mutex::new(list),
+        };
+        if !path.exists() {
+            manager.save()?;
+        }
+        Ok(manager)
+    }
+
+    pub fn list(&self) -> Vec<Profile> {
+        self.list.lock().unwrap().profiles.clone()
+    }
+
+    pub fn active(&self) -> Profile {
+        let list = self.list.lock().unwrap();
+        list.profiles
+            .iter()
+            .find(|p| p.id == list.active)
+            .cloned()
+            .expect("active profile is always listed")
+    }
+
+    pub fn get(&self, id: &str) -> Result<Profile, ProfileError> {
+        self.list
+            .lock()
+            .unwrap()
+            .profiles
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| ProfileError::NotFound(id.to_string()))
+    }
+
+    /// Directory holding a profile's files
+    pub fn profile_dir(&self, id: &str) -> PathBuf {
+        if id == DEFAULT_PROFILE {
+            self.app_dir.clone()
+        } else {
+            self.app_dir.join(PROFILES_DIR).join(id)
+        }
+    }
+
+    pub fn database_path(&self, id: &str) -> PathBuf {
+        self.profile_dir(id).join("seeva.db")
+    }
+
+    pub fn settings_path(&self, id: &str) -> PathBuf {
+        self.profile_dir(id).join("settings.json")
+    }
+
+    /// Keyring service under which a profile's API keys are stored
+    pub fn keyring_service(&self, id: &str) -> String {
+        if id == DEFAULT_PROFILE {
+            KEYRING_SERVICE.to_string()
+        } else {
+            format!("{}.{}", KEYRING_SERVICE, id)
+        }
+    }
+
+    /// Add a profile. Its id is derived from `name`.
+    pub fn create(&self, name: &str) -> Result<Profile, ProfileError> {
+        let name = validate_name(name)?;
+        let profile = {
+            let mut list = self.list.lock().unwrap();
+            let base = slug(name);
+            let mut id = base.clone();
+            let mut n = 2;
+            while id == DEFAULT_PROFILE || list.profiles.iter().any(|p| p.id == id) {
+                id = format!("{}-{}", base, n);
+                n += 1;
+            }
+
+            let profile = Profile {
+                id,
+                name: name.to_string(),
+                created_at: chrono::Utc::now().timestamp_millis(),
+            };
+            list.profiles.push(profile.clone());
+            profile
+        };
+
+        fs::create_dir_all(self.profile_dir(&profile.id))?;
+        self.save()?;
+        Ok(profile)
+    }
+
+    pub fn rename(&self, id: &str, name: &str) -> Result<Profile, ProfileError> {
+        let name = validate_name(name)?;
+        let profile = {
+            let mut list = self.list.lock().unwrap();
+            let profile = list
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == id)
+                .ok_or_else(|| ProfileError::NotFound(id.to_string()))?;
+            profile.name = name.to_string();
+            profile.clone()
+        };
+        self.save()?;
+        Ok(profile)
+    }
+
+    /// Remove a profile and all of its files. The default and the active
+    /// profile can't be deleted.
+    pub fn delete(&self, id: &str) -> Result<(), ProfileError> {
+        if id == DEFAULT_PROFILE {
+            return Err(ProfileError::CannotDelete("the default profile".to_string()));
+        }
+        {
+            let mut list = self.list.lock().unwrap();
+            if list.active == id {
+                return Err(ProfileError::CannotDelete("the active profile".to_string()));
+            }
+            let before = list.profiles.len();
+            list.profiles.retain(|p| p.id != id);
+            if list.profiles.len() == before {
+                return Err(ProfileError::NotFound(id.to_string()));
+            }
+        }
+        self.save()?;
+
+        let dir = self.profile_dir(id);
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Record `id` as the active profile, opened at the next startup
+    pub fn set_active(&self, id: &str) -> Result<(), ProfileError> {
+        {
+            let mut list = self.list.lock().unwrap();
+            if !list.profiles.iter().any(|p| p.id == id) {
+                return Err(ProfileError::NotFound(id.to_string()));
+            }
+            list.active = id.to_string();
+        }
+        fs::create_dir_all(self.profile_dir(id))?;
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), ProfileError> {
+        let json = serde_json::to_string_pretty(&*self.list.lock().unwrap())?;
+        write_atomic(&self.app_dir.join(PROFILES_FILE), json.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<&str, ProfileError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ProfileError::InvalidName("name cannot be empty".to_string()));
+    }
+    if name.chars().count() > 64 {
+        return Err(ProfileError::InvalidName("name is longer than 64 characters".to_string()));
+    }
+    Ok(name)
+}
+
+/// Directory-safe id for a profile name
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "profile".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_profile_uses_app_dir() {
+        let dir = TempDir::new().unwrap();
+        let manager = ProfileManager::new(dir.path().to_path_buf()).unwrap();
+
+        assert_eq!(manager.active().id, DEFAULT_PROFILE);
+        assert_eq!(manager.database_path(DEFAULT_PROFILE), dir.path().join("seeva.db"));
+        assert_eq!(manager.keyring_service(DEFAULT_PROFILE), KEYRING_SERVICE);
+        assert!(dir.path().join(PROFILES_FILE).exists());
+    }
+
+    #[test]
+    fn test_create_switch_and_delete() {
+        let dir = TempDir::new().unwrap();
+        let manager = ProfileManager::new(dir.path().to_path_buf()).unwrap();
+
+        let work = manager.create("Work Stuff").unwrap();
+        assert_eq!(work.id, "work-stuff");
+        assert_eq!(manager.create("work stuff!").unwrap().id, "work-stuff-2");
+        assert_eq!(
+            manager.settings_path(&work.id),
+            dir.path().join("profiles/work-stuff/settings.json")
+        );
+        assert_ne!(manager.keyring_service(&work.id), KEYRING_SERVICE);
+
+        // The active profile is remembered across restarts
+        manager.set_active(&work.id).unwrap();
+        let reopened = ProfileManager::new(dir.path().to_path_buf()).unwrap();
+        assert_eq!(reopened.active(), work);
+        assert_eq!(reopened.list().len(), 3);
+
+        assert!(matches!(reopened.delete(&work.id), Err(ProfileError::CannotDelete(_))));
+        assert!(matches!(reopened.delete(DEFAULT_PROFILE), Err(ProfileError::CannotDelete(_))));
+        reopened.set_active(DEFAULT_PROFILE).unwrap();
+        reopened.delete(&work.id).unwrap();
+        assert!(!dir.path().join("profiles/work-stuff").exists());
+        assert!(matches!(reopened.get(&work.id), Err(ProfileError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_rejects_blank_names() {
+        let dir = TempDir::new().unwrap();
+        let manager = ProfileManager::new(dir.path().to_path_buf()).unwrap();
+        assert!(matches!(manager.create("   "), Err(ProfileError::InvalidName(_))));
+        assert_eq!(manager.create("!!!").unwrap().id, "profile");
+    }
+}
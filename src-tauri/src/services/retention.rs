@@ -0,0 +1,90 @@
+use crate::commands::settings::RetentionSettings;
+use serde::Serialize;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const MB: u64 = 1024 * 1024;
+
+/// Retention settings turned into cutoffs at a point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionRules {
+    /// Threads last updated before this (ms since epoch) are deleted
+    pub threads_before: Option<i64>,
+    /// Images attached before this are removed
+    pub images_before: Option<i64>,
+    /// Oldest threads are deleted while the database uses more bytes
+    pub max_bytes: Option<u64>,
+    /// Trash deleted before this is purged
+    pub trash_before: Option<i64>,
+}
+
+impl RetentionRules {
+    pub fn from_settings(settings: &RetentionSettings, now: i64) -> Self {
+        let cutoff = |days: u32| now - days as i64 * DAY_MS;
+        Self {
+            threads_before: settings.delete_threads_after_days.map(cutoff),
+            images_before: settings.strip_images_after_days.map(cutoff),
+            max_bytes: settings.max_database_mb.map(|mb| mb as u64 * MB),
+            trash_before: settings.purge_trash_after_days.map(cutoff),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemovedThread {
+    pub id: String,
+    pub name: String,
+}
+
+/// What a retention pass removed, or would remove in a dry run
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub deleted_threads: Vec<RemovedThread>,
+    pub stripped_images: usize,
+    /// Threads and messages permanently deleted from the trash
+    pub purged_trash: usize,
+    /// Space used by the database before and after, excluding free pages
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl RetentionReport {
+    pub fn removed_anything(&self) -> bool {
+        !self.deleted_threads.is_empty() || self.stripped_images > 0 || self.purged_trash > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_from_settings() {
+        let now = 100 * DAY_MS;
+        let rules = RetentionRules::from_settings(
+            &RetentionSettings {
+                delete_threads_after_days: Some(30),
+                strip_images_after_days: None,
+                max_database_mb: Some(2),
+                purge_trash_after_days: None,
+            },
+            now,
+        );
+        assert_eq!(rules.threads_before, Some(70 * DAY_MS));
+        assert_eq!(rules.images_before, None);
+        assert_eq!(rules.max_bytes, Some(2 * MB));
+        assert_eq!(rules.trash_before, None);
+
+        // Only the trash is purged by default
+        let defaults = RetentionRules::from_settings(&RetentionSettings::default(), now);
+        assert_eq!(defaults, RetentionRules { trash_before: Some(70 * DAY_MS), ..Default::default() });
+        let off = RetentionSettings { purge_trash_after_days: None, ..Default::default() };
+        assert!(RetentionRules::from_settings(&off, now).is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::enum_variant_names)]
+pub enum SecretStoreError {
+    #[error("Failed to access secret store: {0}")]
+    IoError(String),
+
+    #[error("Failed to encrypt or decrypt secrets: {0}")]
+    CryptoError(String),
+
+    #[error("Failed to serialize/deserialize secrets: {0}")]
+    SerializationError(String),
+
+    #[error("Keyring error: {0}")]
+    KeyringError(String),
+}
+
+/// Storage for sensitive values such as provider API keys, kept out of
+/// `settings.json`
+pub trait SecretStore: Send + Sync {
+    fn get(&self, name: &str) -> Result<Option<String>, SecretStoreError>;
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretStoreError>;
+
+    /// Remove a secret; deleting a missing secret is not an error
+    fn delete(&self, name: &str) -> Result<(), SecretStoreError>;
+}
+
+/// Open the secret store used by the app: the OS keyring when built with the
+/// `keyring` feature, otherwise an encrypted file in `dir` protected by a
+/// machine-local key file. Each profile has its own keyring service.
+pub fn default_store(dir: &Path, keyring_service: &str) -> Result<Arc<dyn SecretStore>, SecretStoreError> {
+    #[cfg(feature = "keyring")]
+    {
+        let _ = dir;
+        Ok(Arc::new(KeyringStore::new(keyring_service)))
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    {
+        let _ = keyring_service;
+        Ok(Arc::new(EncryptedFileStore::with_key_file(
+            dir.join("secrets.enc"),
+            dir.join("secrets.key"),
+        )?))
+    }
+}
+
+const KEY_LEN: usize = 32;
+
+/// On-disk layout of `secrets.enc`. The whole secret map is encrypted as one
+/// JSON blob with a fresh nonce on every write.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Secrets encrypted with AES-256-GCM in a single file
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl EncryptedFileStore {
+    /// Use a random 256-bit key kept in `key_path`, creating it on first use
+    pub fn with_key_file(path: PathBuf, key_path: PathBuf) -> Result<Self, SecretStoreError> {
+        let key_bytes = if key_path.exists() {
+            let bytes = fs::read(&key_path).map_err(|e| SecretStoreError::IoError(e.to_string()))?;
+            if bytes.len() != KEY_LEN {
+                return Err(SecretStoreError::CryptoError(format!(
+                    "Key file {:?} is corrupt",
+                    key_path
+                )));
+            }
+            bytes
+        } else {
+            let key = Aes256Gcm::generate_key(OsRng);
+            write_private(&key_path, key.as_slice())?;
+            key.to_vec()
+        };
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key_bytes));
+        Self::open(path, cipher)
+    }
+
+    fn open(path: PathBuf, cipher: Aes256Gcm) -> Result<Self, SecretStoreError> {
+        let secrets = match Self::read_file(&path)? {
+            Some(file) => {
+                let nonce = decode(&file.nonce)?;
+                let ciphertext = decode(&file.ciphertext)?;
+                let plaintext = cipher
+                    .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+                    .map_err(|_| {
+                        SecretStoreError::CryptoError("Failed to decrypt secrets (wrong key?)".to_string())
+                    })?;
+                serde_json::from_slice(&plaintext)
+                    .map_err(|e| SecretStoreError::SerializationError(e.to_string()))?
+            }
+            None => HashMap::new(),
+        };
+
+        Ok(Self {
+            path,
+            cipher,
+            secrets: Mutex::new(secrets),
+        })
+    }
+
+    fn read_file(path: &Path) -> Result<Option<EncryptedFile>, SecretStoreError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read_to_string(path).map_err(|e| SecretStoreError::IoError(e.to_string()))?;
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| SecretStoreError::SerializationError(e.to_string()))
+    }
+
+    fn save(&self, secrets: &HashMap<String, String>) -> Result<(), SecretStoreError> {
+        let plaintext = serde_json::to_vec(secrets)
+            .map_err(|e| SecretStoreError::SerializationError(e.to_string()))?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|e| SecretStoreError::CryptoError(e.to_string()))?;
+
+        let file = EncryptedFile {
+            version: 1,
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        };
+        let json = serde_json::to_vec_pretty(&file)
+            .map_err(|e| SecretStoreError::SerializationError(e.to_string()))?;
+
+        write_private(&self.path, &json)
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn get(&self, name: &str) -> Result<Option<String>, SecretStoreError> {
+        let secrets = self
+            .secrets
+            .lock()
+            .map_err(|e| SecretStoreError::IoError(e.to_string()))?;
+        Ok(secrets.get(name).cloned())
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretStoreError> {
+        let mut secrets = self
+            .secrets
+            .lock()
+            .map_err(|e| SecretStoreError::IoError(e.to_string()))?;
+        secrets.insert(name.to_string(), value.to_string());
+        self.save(&secrets)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), SecretStoreError> {
+        let mut secrets = self
+            .secrets
+            .lock()
+            .map_err(|e| SecretStoreError::IoError(e.to_string()))?;
+        if secrets.remove(name).is_some() {
+            self.save(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// Secrets kept in the platform credential store (macOS Keychain, Windows
+/// Credential Manager, Secret Service on Linux)
+#[cfg(feature = "keyring")]
+pub struct KeyringStore {
+    service: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+
+    fn entry(&self, name: &str) -> Result<keyring::Entry, SecretStoreError> {
+        keyring::Entry::new(&self.service, name).map_err(|e| SecretStoreError::KeyringError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretStore for KeyringStore {
+    fn get(&self, name: &str) -> Result<Option<String>, SecretStoreError> {
+        match self.entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(SecretStoreError::KeyringError(e.to_string())),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretStoreError> {
+        self.entry(name)?
+            .set_password(value)
+            .map_err(|e| SecretStoreError::KeyringError(e.to_string()))
+    }
+
+    fn delete(&self, name: &str) -> Result<(), SecretStoreError> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(SecretStoreError::KeyringError(e.to_string())),
+        }
+    }
+}
+
+/// In-memory store for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+#[cfg(test)]
+impl SecretStore for MemorySecretStore {
+    fn get(&self, name: &str) -> Result<Option<String>, SecretStoreError> {
+        Ok(self.secrets.lock().unwrap().get(name).cloned())
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), SecretStoreError> {
+        self.secrets.lock().unwrap().insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), SecretStoreError> {
+        self.secrets.lock().unwrap().remove(name);
+        Ok(())
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, SecretStoreError> {
+    general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| SecretStoreError::SerializationError(e.to_string()))
+}
+
+/// Write a file readable only by the current user. Goes through a temporary
+/// file renamed into place, like settings.json, so a crash mid-write never
+/// leaves a truncated file behind.
+fn write_private(path: &Path, data: &[u8]) -> Result<(), SecretStoreError> {
+    use std::io::Write;
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+    // A leftover from an interrupted write may have other permissions
+    let _ = fs::remove_file(&tmp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let result = options
+        .open(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.map_err(|e| SecretStoreError::IoError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_key_file_store_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let key_path = temp_dir.path().join("secrets.key");
+
+        let store = EncryptedFileStore::with_key_file(path.clone(), key_path.clone()).unwrap();
+        store.set("anthropic_api_key", "sk-ant-secret").unwrap();
+        assert!(key_path.exists());
+
+        // Nothing readable on disk
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("sk-ant-secret"));
+
+        let reopened = EncryptedFileStore::with_key_file(path, key_path).unwrap();
+        assert_eq!(
+            reopened.get("anthropic_api_key").unwrap().as_deref(),
+            Some("sk-ant-secret")
+        );
+
+        reopened.delete("anthropic_api_key").unwrap();
+        assert_eq!(reopened.get("anthropic_api_key").unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_secrets_file_is_private_and_replaced_whole() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("secrets.enc");
+        let key_path = temp_dir.path().join("secrets.key");
+
+        let store = EncryptedFileStore::with_key_file(path.clone(), key_path.clone()).unwrap();
+        store.set("anthropic_api_key", "sk-ant-secret").unwrap();
+        store.set("openai_api_key", "sk-openai").unwrap();
+
+        for file in [&path, &key_path] {
+            assert_eq!(fs::metadata(file).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let mut files: Vec<_> = fs::read_dir(temp_dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        files.sort();
+        assert_eq!(files, ["secrets.enc", "secrets.key"]);
+    }
+}
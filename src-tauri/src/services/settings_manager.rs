@@ -1,7 +1,16 @@
-use crate::commands::settings::AppSettings;
+use crate::commands::settings::{AppSettings, BackupSettings, ProviderSettings, RetentionSettings};
+use crate::services::secret_store::SecretStore;
+use crate::services::settings_policy::{self, Policy, PolicyInfo};
+use crate::services::settings_validator::{self, FieldError};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
 use std::fs;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug, thiserror::Error)]
 #[allow(clippy::enum_variant_names)]
@@ -14,55 +23,587 @@ pub enum SettingsError {
 
     #[error("Failed to acquire settings lock: {0}")]
     LockError(String),
+
+    #[error("Failed to access secret store: {0}")]
+    SecretError(String),
+
+    #[error("Failed to watch settings file: {0}")]
+    WatchError(#[from] notify::Error),
+
+    #[error("Unknown provider: {0}")]
+    UnknownProvider(String),
+
+    #[error("Invalid settings: {}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    Invalid(Vec<FieldError>),
+
+    #[error("Locked settings: {}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    Locked(Vec<FieldError>),
+}
+
+/// Editors often save in several steps; wait this long after a change to
+/// `settings.json` before reloading it
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// One changed section of the settings, with its value before and after
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "section", rename_all = "camelCase", rename_all_fields = "camelCase")]
+#[allow(clippy::large_enum_variant)]
+pub enum SettingsChange {
+    DefaultProvider { old: String, new: String },
+    /// `old` is `None` for an added provider, `new` for a removed one
+    Provider { id: String, old: Option<ProviderSettings>, new: Option<ProviderSettings> },
+    Theme { old: String, new: String },
+    Shortcut { old: String, new: String },
+    ContextDetection { old: bool, new: bool },
+    Screenshots { old: bool, new: bool },
+    Backup { old: BackupSettings, new: BackupSettings },
+    Retention { old: RetentionSettings, new: RetentionSettings },
+}
+
+impl SettingsChange {
+    /// Sections that differ between `old` and `new`
+    pub fn between(old: &AppSettings, new: &AppSettings) -> Vec<SettingsChange> {
+        let mut changes = Vec::new();
+        if old.default_provider != new.default_provider {
+            changes.push(SettingsChange::DefaultProvider {
+                old: old.default_provider.clone(),
+                new: new.default_provider.clone(),
+            });
+        }
+        let ids: BTreeSet<&String> = old.providers.keys().chain(new.providers.keys()).collect();
+        for id in ids {
+            let (before, after) = (old.provider(id), new.provider(id));
+            if before != after {
+                changes.push(SettingsChange::Provider {
+                    id: id.clone(),
+                    old: before.cloned(),
+                    new: after.cloned(),
+                });
+            }
+        }
+        if old.theme != new.theme {
+            changes.push(SettingsChange::Theme { old: old.theme.clone(), new: new.theme.clone() });
+        }
+        if old.shortcut != new.shortcut {
+            changes.push(SettingsChange::Shortcut {
+                old: old.shortcut.clone(),
+                new: new.shortcut.clone(),
+            });
+        }
+        if old.enable_context_detection != new.enable_context_detection {
+            changes.push(SettingsChange::ContextDetection {
+                old: old.enable_context_detection,
+                new: new.enable_context_detection,
+            });
+        }
+        if old.enable_screenshots != new.enable_screenshots {
+            changes.push(SettingsChange::Screenshots {
+                old: old.enable_screenshots,
+                new: new.enable_screenshots,
+            });
+        }
+        if old.backup != new.backup {
+            changes.push(SettingsChange::Backup { old: old.backup.clone(), new: new.backup.clone() });
+        }
+        if old.retention != new.retention {
+            changes.push(SettingsChange::Retention {
+                old: old.retention.clone(),
+                new: new.retention.clone(),
+            });
+        }
+        changes
+    }
+
+    /// Copy with API keys masked, for sending to the webview
+    pub fn masked(&self) -> SettingsChange {
+        match self {
+            SettingsChange::Provider { id, old, new } => SettingsChange::Provider {
+                id: id.clone(),
+                old: old.as_ref().map(ProviderSettings::masked),
+                new: new.as_ref().map(ProviderSettings::masked),
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+type ChangeListener = Arc<dyn Fn(&[SettingsChange]) + Send + Sync>;
+type WarningListener = Arc<dyn Fn(&SettingsWarning) + Send + Sync>;
+
+/// Version written to `settings.json`. Files without a `version` are v0.
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// A migration upgrades the raw JSON of `settings.json` from `version - 1`
+/// to `version`, before it is deserialized
+struct SettingsMigration {
+    version: u32,
+    description: &'static str,
+    up: fn(&mut Map<String, Value>),
+}
+
+const MIGRATIONS: &[SettingsMigration] = &[
+    SettingsMigration {
+        version: 1,
+        description: "fill in fields added before settings were versioned",
+        up: |settings| {
+            settings
+                .entry("enableContextDetection")
+                .or_insert(Value::Bool(true));
+        },
+    },
+    SettingsMigration {
+        version: 2,
+        description: "move provider settings into a map keyed by provider id",
+        up: |settings| {
+            let mut providers = match settings.remove("providers") {
+                Some(Value::Object(providers)) => providers,
+                _ => Map::new(),
+            };
+            for id in AppSettings::PROVIDER_IDS {
+                if let Some(provider) = settings.remove(id) {
+                    providers.entry(id).or_insert(provider);
+                }
+            }
+            settings.insert("providers".to_string(), Value::Object(providers));
+        },
+    },
+];
+
+/// Problem found while loading settings, reported to the user once the UI
+/// is up
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsWarning {
+    pub message: String,
+    /// Where the unreadable file was moved to
+    pub preserved_path: Option<String>,
 }
 
+/// Name under which a provider's API key is kept in the secret store
+fn api_key_secret(provider: &str) -> String {
+    format!("{}_api_key", provider)
+}
+
+/// Settings live in `settings.json`, except API keys which are kept in a
+/// `SecretStore`. In memory `AppSettings` holds the real keys; they are
+/// stripped whenever settings are written to disk. An administrator's
+/// `Policy` is applied on top, so locked values end up in the file too.
 pub struct SettingsManager {
-    settings_path: PathBuf,
+    /// Path and store change when the user switches profile
+    settings_path: Mutex<PathBuf>,
     settings: Arc<Mutex<AppSettings>>,
+    secrets: Mutex<Arc<dyn SecretStore>>,
+    policy: Option<Policy>,
+    load_warning: Mutex<Option<SettingsWarning>>,
+    change_listeners: Mutex<Vec<ChangeListener>>,
+    warning_listeners: Mutex<Vec<WarningListener>>,
+    /// Contents of our own last write, so the watcher can ignore it
+    last_written: Mutex<Option<String>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
 }
 
 impl SettingsManager {
-    /// Create a new SettingsManager and load settings from file (or create defaults)
-    pub fn new(settings_path: PathBuf) -> Result<Self, SettingsError> {
+    /// Create a new SettingsManager and load settings from file (or create
+    /// defaults), enforcing the system policy if there is one
+    pub fn new(settings_path: PathBuf, secrets: Arc<dyn SecretStore>) -> Result<Self, SettingsError> {
+        Self::with_policy(settings_path, secrets, Self::system_policy())
+    }
+
+    pub fn with_policy(
+        settings_path: PathBuf,
+        secrets: Arc<dyn SecretStore>,
+        policy: Option<Policy>,
+    ) -> Result<Self, SettingsError> {
         println!("⚙️  Initializing settings from: {:?}", settings_path);
+        let (settings, load_warning) = Self::load(&settings_path, secrets.as_ref(), policy.as_ref());
+
+        let last_written = fs::read_to_string(&settings_path).ok();
+        Ok(Self {
+            settings_path: Mutex::new(settings_path),
+            settings: Arc::new(Mutex::new(settings)),
+            secrets: Mutex::new(secrets),
+            policy,
+            load_warning: Mutex::new(load_warning),
+            change_listeners: Mutex::new(Vec::new()),
+            warning_listeners: Mutex::new(Vec::new()),
+            last_written: Mutex::new(last_written),
+            watcher: Mutex::new(None),
+        })
+    }
+
+    /// Switch to another profile's settings file and secret store.
+    /// Subscribers hear about every section that differs, and a running
+    /// watcher follows the new file.
+    pub fn switch_to(self: &Arc<Self>, settings_path: PathBuf, secrets: Arc<dyn SecretStore>) -> Result<(), SettingsError> {
+        println!("⚙️  Switching settings to: {:?}", settings_path);
+        let (settings, load_warning) = Self::load(&settings_path, secrets.as_ref(), self.policy.as_ref());
 
+        let last_written = fs::read_to_string(&settings_path).ok();
+        *self.settings_path.lock().unwrap() = settings_path;
+        *self.secrets.lock().unwrap() = secrets;
+        *self.last_written.lock().unwrap() = last_written;
+        *self.load_warning.lock().unwrap() = None;
+        if let Some(warning) = load_warning {
+            self.warn(&warning);
+        }
+
+        if self.watcher.lock().unwrap().is_some() {
+            self.watch()?;
+        }
+        self.replace(settings, false)
+    }
+
+    /// Remove the API keys of the settings at `settings_path` from
+    /// `secrets`, e.g. before deleting a profile
+    pub fn delete_secrets(settings_path: &Path, secrets: &dyn SecretStore) -> Result<(), SettingsError> {
+        let mut ids: BTreeSet<String> = AppSettings::PROVIDER_IDS.iter().map(|id| id.to_string()).collect();
+        if let Ok((settings, _)) = Self::load_from_disk(settings_path) {
+            ids.extend(settings.providers.into_keys());
+        }
+        for id in ids {
+            secrets
+                .delete(&api_key_secret(&id))
+                .map_err(|e| SettingsError::SecretError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Read the policy file from its system location. An unreadable policy
+    /// is reported and ignored.
+    fn system_policy() -> Option<Policy> {
+        let path = settings_policy::policy_path();
+        match Policy::load(&path) {
+            Ok(Some(policy)) => {
+                println!("   🏢 Enforcing settings policy from {:?}", path);
+                for field in policy.unknown_fields() {
+                    eprintln!("   ⚠️  Policy locks unknown setting {}", field);
+                }
+                Some(policy)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("   ❌ Ignoring settings policy {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Read settings and their API keys, falling back to defaults
+    fn load(
+        settings_path: &Path,
+        secrets: &dyn SecretStore,
+        policy: Option<&Policy>,
+    ) -> (AppSettings, Option<SettingsWarning>) {
         // Load settings from file if it exists, otherwise use defaults
-        let settings = if settings_path.exists() {
+        let mut load_warning = None;
+        let mut migrated = false;
+        let mut settings = if settings_path.exists() {
             println!("   Loading existing settings...");
-            match fs::read_to_string(&settings_path) {
-                Ok(data) => {
-                    match serde_json::from_str::<AppSettings>(&data) {
-                        Ok(loaded_settings) => {
-                            println!("   ✅ Settings loaded successfully");
-                            loaded_settings
-                        }
-                        Err(e) => {
-                            eprintln!("   ⚠️  Failed to parse settings, using defaults: {}", e);
-                            AppSettings::default()
-                        }
-                    }
+            match Self::load_from_disk(settings_path) {
+                Ok((loaded_settings, needs_save)) => {
+                    println!("   ✅ Settings loaded successfully");
+                    migrated = needs_save;
+                    loaded_settings
                 }
                 Err(e) => {
-                    eprintln!("   ⚠️  Failed to read settings file, using defaults: {}", e);
-                    AppSettings::default()
+                    // Keep the unreadable file rather than overwrite it with defaults
+                    eprintln!("   ⚠️  Failed to load settings, using defaults: {}", e);
+                    let preserved = Self::preserve_corrupt(settings_path);
+                    load_warning = Some(SettingsWarning {
+                        message: format!("Your settings could not be read and were reset to defaults: {}", e),
+                        preserved_path: preserved.map(|p| p.to_string_lossy().into_owned()),
+                    });
+                    Self::save_defaults(settings_path)
                 }
             }
         } else {
             println!("   No existing settings found, creating defaults");
-            let defaults = AppSettings::default();
+            Self::save_defaults(settings_path)
+        };
+
+        // One-time migration: older versions wrote API keys into settings.json
+        let has_plaintext_keys = settings.providers.values().any(|p| !p.api_key.is_empty());
+
+        if has_plaintext_keys {
+            println!("   🔐 Moving API keys from settings file into secret store...");
+            match Self::store_plaintext_keys(secrets, &settings)
+                .and_then(|_| Self::save_to_disk(settings_path, &settings))
+            {
+                Ok(_) => println!("   ✅ API keys migrated"),
+                // Leave the file untouched so the migration is retried next launch
+                Err(e) => eprintln!("   ⚠️  Failed to migrate API keys: {}", e),
+            }
+        } else if migrated {
+            // Still readable as is, so a failed save is retried next launch
+            if let Err(e) = Self::save_to_disk(settings_path, &settings) {
+                eprintln!("   ⚠️  Failed to save migrated settings: {}", e);
+            }
+        }
+
+        // Load API keys from the secret store
+        for (id, provider) in settings.providers.iter_mut() {
+            match secrets.get(&api_key_secret(id)) {
+                Ok(Some(api_key)) => provider.api_key = api_key,
+                Ok(None) => {}
+                Err(e) => eprintln!("   ⚠️  Failed to load {} API key: {}", id, e),
+            }
+        }
+
+        if let Some(policy) = policy {
+            match policy.apply(&settings) {
+                Ok(applied) => settings = applied,
+                Err(e) => eprintln!("   ⚠️  Failed to apply settings policy: {}", e),
+            }
+        }
+
+        // Kept as loaded; later updates may not add new errors but needn't fix these
+        for error in settings_validator::validate(&settings) {
+            eprintln!("   ⚠️  Invalid setting {}", error);
+        }
+
+        (settings, load_warning)
+    }
+
+    /// Call `listener` with the changed sections after every update,
+    /// including ones reloaded from disk
+    pub fn subscribe(&self, listener: impl Fn(&[SettingsChange]) + Send + Sync + 'static) {
+        if let Ok(mut listeners) = self.change_listeners.lock() {
+            listeners.push(Arc::new(listener));
+        }
+    }
+
+    /// Call `listener` when an edited `settings.json` can't be reloaded
+    pub fn subscribe_warnings(&self, listener: impl Fn(&SettingsWarning) + Send + Sync + 'static) {
+        if let Ok(mut listeners) = self.warning_listeners.lock() {
+            listeners.push(Arc::new(listener));
+        }
+    }
+
+    fn notify(&self, old: &AppSettings, new: &AppSettings) {
+        let changes = SettingsChange::between(old, new);
+        if changes.is_empty() {
+            return;
+        }
+        // Clone the list so listeners may call back into the manager
+        let listeners = self.change_listeners.lock().map(|l| l.clone()).unwrap_or_default();
+        for listener in listeners {
+            listener(&changes);
+        }
+    }
 
-            // Try to save defaults to disk
-            if let Err(e) = Self::save_to_disk(&settings_path, &defaults) {
-                eprintln!("   ⚠️  Failed to save default settings: {}", e);
+    fn settings_path(&self) -> PathBuf {
+        self.settings_path.lock().unwrap().clone()
+    }
+
+    fn warn(&self, warning: &SettingsWarning) {
+        let listeners = self.warning_listeners.lock().map(|l| l.clone()).unwrap_or_default();
+        for listener in listeners {
+            listener(warning);
+        }
+    }
+
+    /// Watch `settings.json` and reload it when it is edited outside the app
+    pub fn watch(self: &Arc<Self>) -> Result<(), SettingsError> {
+        let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+        let mut watcher = notify::recommended_watcher(tx)?;
+
+        // Watch the directory: saves that replace the file would end a watch on the file itself
+        let settings_path = self.settings_path();
+        let dir = settings_path.parent().unwrap_or(Path::new("."));
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        let file_name = settings_path.file_name().map(|n| n.to_os_string());
+        let manager = Arc::downgrade(self);
+        std::thread::spawn(move || {
+            while let Ok(event) = rx.recv() {
+                let touches_settings = event.is_ok_and(|e| {
+                    (e.kind.is_create() || e.kind.is_modify())
+                        && e.paths.iter().any(|p| p.file_name() == file_name.as_deref())
+                });
+                if !touches_settings {
+                    continue;
+                }
+
+                std::thread::sleep(RELOAD_DEBOUNCE);
+                while rx.try_recv().is_ok() {}
+
+                // Stops once the manager (and with it the watcher) is gone
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.reload_from_disk();
             }
+        });
 
-            defaults
+        if let Ok(mut current) = self.watcher.lock() {
+            *current = Some(watcher);
+        }
+        println!("   👀 Watching {:?} for changes", settings_path);
+        Ok(())
+    }
+
+    /// Pick up an external edit of `settings.json`. A file that doesn't
+    /// parse is left alone (it may be mid-edit) and current settings kept.
+    pub fn reload_from_disk(&self) {
+        let settings_path = self.settings_path();
+        let Ok(data) = fs::read_to_string(&settings_path) else {
+            return;
         };
+        if self.last_written.lock().is_ok_and(|w| w.as_deref() == Some(data.as_str())) {
+            return;
+        }
+        println!("⚙️  Settings file changed on disk, reloading...");
 
-        Ok(Self {
-            settings_path,
-            settings: Arc::new(Mutex::new(settings)),
-        })
+        let mut loaded = match Self::load_from_disk(&settings_path) {
+            Ok((loaded, _)) => loaded,
+            Err(e) => {
+                eprintln!("   ⚠️  Ignoring unreadable settings file: {}", e);
+                self.warn(&SettingsWarning {
+                    message: format!("Edited settings file could not be read, keeping current settings: {}", e),
+                    preserved_path: None,
+                });
+                return;
+            }
+        };
+
+        // Locked fields edited in the file are quietly put back
+        loaded = match self.enforce_policy(loaded) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("   ⚠️  Failed to apply settings policy: {}", e);
+                return;
+            }
+        };
+
+        let Ok(current) = self.get() else {
+            return;
+        };
+        let errors = settings_validator::new_errors(&current, &loaded);
+        if !errors.is_empty() {
+            let e = SettingsError::Invalid(errors);
+            eprintln!("   ⚠️  Ignoring invalid settings file: {}", e);
+            self.warn(&SettingsWarning {
+                message: format!("Edited settings file is invalid, keeping current settings: {}", e),
+                preserved_path: None,
+            });
+            return;
+        }
+
+        // The file normally has no keys; keep the stored ones unless a new one was typed in
+        let mut has_new_keys = false;
+        for (id, provider) in loaded.providers.iter_mut() {
+            if !provider.api_key.is_empty() {
+                has_new_keys = true;
+            } else if let Some(existing) = current.provider(id) {
+                provider.api_key = existing.api_key.clone();
+            }
+        }
+
+        let result = if has_new_keys {
+            // Moves the keys into the secret store and strips them from the file
+            self.update(loaded)
+        } else {
+            self.replace(loaded, false).map(|_| {
+                if let Ok(mut last_written) = self.last_written.lock() {
+                    *last_written = Some(data);
+                }
+            })
+        };
+        match result {
+            Ok(_) => println!("   ✅ Settings reloaded"),
+            Err(e) => eprintln!("   ⚠️  Failed to apply reloaded settings: {}", e),
+        }
+    }
+
+    /// Swap in new settings, optionally persisting them, and notify subscribers
+    fn replace(&self, new_settings: AppSettings, persist: bool) -> Result<(), SettingsError> {
+        let old = {
+            let mut settings = self
+                .settings
+                .lock()
+                .map_err(|e| SettingsError::LockError(e.to_string()))?;
+            std::mem::replace(&mut *settings, new_settings.clone())
+        };
+        if persist {
+            self.persist(&old, &new_settings)?;
+        }
+        self.notify(&old, &new_settings);
+        Ok(())
+    }
+
+    /// Warning from loading settings at startup, if any
+    pub fn load_warning(&self) -> Option<SettingsWarning> {
+        self.load_warning.lock().ok()?.clone()
+    }
+
+    /// Like `load_warning`, but clears it so it is only reported once
+    pub fn take_load_warning(&self) -> Option<SettingsWarning> {
+        self.load_warning.lock().ok()?.take()
+    }
+
+    fn save_defaults(path: &Path) -> AppSettings {
+        let defaults = AppSettings::default();
+
+        // Try to save defaults to disk
+        if let Err(e) = Self::save_to_disk(path, &defaults) {
+            eprintln!("   ⚠️  Failed to save default settings: {}", e);
+        }
+
+        defaults
+    }
+
+    /// Read, migrate and parse `settings.json`, returning whether the file
+    /// needs saving in the current format. Nothing is written here: an old
+    /// file may still hold API keys that must reach the secret store first.
+    fn load_from_disk(path: &Path) -> Result<(AppSettings, bool), SettingsError> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| SettingsError::SerializationError(e.to_string()))?;
+        let mut value: Value = serde_json::from_str(&data)
+            .map_err(|e| SettingsError::SerializationError(e.to_string()))?;
+        let Some(object) = value.as_object_mut() else {
+            return Err(SettingsError::SerializationError("expected a JSON object".to_string()));
+        };
+
+        let version = object
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+        if version > SETTINGS_VERSION {
+            eprintln!(
+                "   ⚠️  Settings version {} is newer than this build ({})",
+                version, SETTINGS_VERSION
+            );
+        } else if version < SETTINGS_VERSION {
+            for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+                println!(
+                    "   🔧 Migrating settings to v{}: {}",
+                    migration.version, migration.description
+                );
+                (migration.up)(object);
+            }
+            object.insert("version".to_string(), SETTINGS_VERSION.into());
+        }
+
+        let mut settings: AppSettings = serde_json::from_value(value)
+            .map_err(|e| SettingsError::SerializationError(e.to_string()))?;
+        settings.fill_builtin_providers();
+        Ok((settings, version < SETTINGS_VERSION))
+    }
+
+    /// Move an unreadable settings file to `settings.corrupt-<timestamp>.json`
+    fn preserve_corrupt(path: &Path) -> Option<PathBuf> {
+        let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+        let preserved = path.with_file_name(format!("settings.corrupt-{}.json", timestamp));
+        match fs::rename(path, &preserved) {
+            Ok(_) => {
+                println!("   💾 Kept unreadable settings as {}", preserved.display());
+                Some(preserved)
+            }
+            Err(e) => {
+                eprintln!("   ⚠️  Failed to preserve unreadable settings: {}", e);
+                None
+            }
+        }
     }
 
     /// Get current settings
@@ -74,62 +615,246 @@ impl SettingsManager {
         Ok(settings.clone())
     }
 
-    /// Update settings and save to disk
+    /// Look up the stored API key for a provider id. Returns `None` when the
+    /// provider is unknown or has no key.
+    pub fn get_api_key(&self, provider: &str) -> Result<Option<String>, SettingsError> {
+        let settings = self
+            .settings
+            .lock()
+            .map_err(|e| SettingsError::LockError(e.to_string()))?;
+
+        Ok(settings
+            .provider(provider)
+            .map(|p| p.api_key.clone())
+            .filter(|key| !key.is_empty()))
+    }
+
+    /// Locked fields and providers the policy doesn't allow
+    pub fn policy_info(&self) -> PolicyInfo {
+        self.policy.as_ref().map(Policy::info).unwrap_or_default()
+    }
+
+    /// Fields of `new_settings` that the policy doesn't let the user change
+    pub fn policy_errors(&self, new_settings: &AppSettings) -> Vec<FieldError> {
+        self.policy
+            .as_ref()
+            .map(|policy| policy.violations(new_settings))
+            .unwrap_or_default()
+    }
+
+    fn enforce_policy(&self, settings: AppSettings) -> Result<AppSettings, SettingsError> {
+        match &self.policy {
+            Some(policy) => policy
+                .apply(&settings)
+                .map_err(|e| SettingsError::SerializationError(e.to_string())),
+            None => Ok(settings),
+        }
+    }
+
+    /// Refuse changes to locked fields, then drop anything else the policy
+    /// doesn't allow
+    fn check_policy(&self, new_settings: AppSettings) -> Result<AppSettings, SettingsError> {
+        let errors = self.policy_errors(&new_settings);
+        if !errors.is_empty() {
+            return Err(SettingsError::Locked(errors));
+        }
+        self.enforce_policy(new_settings)
+    }
+
+    /// Update settings and save to disk. Settings that fail validation or
+    /// change locked fields are not applied; see `settings_validator::new_errors`.
     pub fn update(&self, new_settings: AppSettings) -> Result<(), SettingsError> {
         println!("⚙️  Updating settings...");
-
-        // Update in-memory settings
-        {
-            let mut settings = self
-                .settings
-                .lock()
-                .map_err(|e| SettingsError::LockError(e.to_string()))?;
-            *settings = new_settings.clone();
+        let new_settings = self.check_policy(new_settings).inspect_err(|_| {
+            eprintln!("   ❌ Refusing to change locked settings");
+        })?;
+        let errors = settings_validator::new_errors(&self.get()?, &new_settings);
+        if !errors.is_empty() {
+            eprintln!("   ❌ Refusing invalid settings");
+            return Err(SettingsError::Invalid(errors));
         }
 
-        // Save to disk
-        Self::save_to_disk(&self.settings_path, &new_settings)?;
+        self.replace(new_settings, true)?;
 
         println!("   ✅ Settings saved successfully");
         Ok(())
     }
 
-    /// Update a specific field and save
+    /// Update a specific field and save. Like `update`, invalid changes
+    /// are refused.
     pub fn update_field<F>(&self, update_fn: F) -> Result<(), SettingsError>
     where
         F: FnOnce(&mut AppSettings),
     {
-        let updated_settings = {
+        let (old_settings, updated_settings) = {
             let mut settings = self
                 .settings
                 .lock()
                 .map_err(|e| SettingsError::LockError(e.to_string()))?;
 
-            update_fn(&mut settings);
-            settings.clone()
+            let old_settings = settings.clone();
+            let mut updated_settings = settings.clone();
+            update_fn(&mut updated_settings);
+            let updated_settings = self.check_policy(updated_settings)?;
+            let errors = settings_validator::new_errors(&old_settings, &updated_settings);
+            if !errors.is_empty() {
+                return Err(SettingsError::Invalid(errors));
+            }
+            *settings = updated_settings.clone();
+            (old_settings, updated_settings)
         };
 
-        Self::save_to_disk(&self.settings_path, &updated_settings)?;
+        self.persist(&old_settings, &updated_settings)?;
+        self.notify(&old_settings, &updated_settings);
         Ok(())
     }
 
-    /// Save settings to disk
-    fn save_to_disk(path: &PathBuf, settings: &AppSettings) -> Result<(), SettingsError> {
-        let json = serde_json::to_string_pretty(settings)
-            .map_err(|e| SettingsError::SerializationError(e.to_string()))?;
+    /// Settings as written to `settings.json`, i.e. without API keys
+    pub fn export_json(&self) -> Result<String, SettingsError> {
+        Self::to_json(&self.get()?)
+    }
 
-        fs::write(path, json)
-            .map_err(|e| SettingsError::WriteError(e.to_string()))?;
+    /// Replace settings with ones restored from a backup. Backups don't carry
+    /// API keys, so the keys currently stored are kept, and locked fields
+    /// take the policy's values.
+    pub fn restore(&self, mut restored: AppSettings) -> Result<(), SettingsError> {
+        let current = self.get()?;
+        restored.fill_builtin_providers();
+        for (id, provider) in restored.providers.iter_mut() {
+            if let Some(existing) = current.provider(id) {
+                provider.api_key = existing.api_key.clone();
+            }
+        }
+        let restored = self.enforce_policy(restored)?;
+        self.update(restored)
+    }
+
+    /// Write API keys to the secret store, then everything else to disk.
+    /// Keys go first so a store failure never leaves them only in memory.
+    fn persist(&self, old: &AppSettings, settings: &AppSettings) -> Result<(), SettingsError> {
+        let secrets = self.secrets.lock().unwrap().clone();
+        Self::sync_secrets(secrets.as_ref(), Some(old), settings)?;
+        let json = Self::save_to_disk(&self.settings_path(), settings)?;
+        if let Ok(mut last_written) = self.last_written.lock() {
+            *last_written = Some(json);
+        }
+        Ok(())
+    }
+
+    /// Move the keys found in the settings file into the secret store. Only
+    /// writes: providers without a key in the file keep what is stored.
+    fn store_plaintext_keys(secrets: &dyn SecretStore, settings: &AppSettings) -> Result<(), SettingsError> {
+        for (id, provider) in &settings.providers {
+            if !provider.api_key.is_empty() {
+                secrets
+                    .set(&api_key_secret(id), &provider.api_key)
+                    .map_err(|e| SettingsError::SecretError(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bring the secret store in line with the keys in `settings`. Keys of
+    /// providers present in `old` but since removed are deleted.
+    fn sync_secrets(
+        secrets: &dyn SecretStore,
+        old: Option<&AppSettings>,
+        settings: &AppSettings,
+    ) -> Result<(), SettingsError> {
+        let removed = old
+            .into_iter()
+            .flat_map(|old| old.providers.keys())
+            .filter(|id| !settings.providers.contains_key(*id));
+        for id in removed {
+            let name = api_key_secret(id);
+            let stored = secrets
+                .get(&name)
+                .map_err(|e| SettingsError::SecretError(e.to_string()))?;
+            if stored.is_some() {
+                secrets
+                    .delete(&name)
+                    .map_err(|e| SettingsError::SecretError(e.to_string()))?;
+            }
+        }
+
+        for (id, provider) in &settings.providers {
+            let name = api_key_secret(id);
+            let stored = secrets
+                .get(&name)
+                .map_err(|e| SettingsError::SecretError(e.to_string()))?;
+
+            let result = if provider.api_key.is_empty() {
+                match stored {
+                    Some(_) => secrets.delete(&name),
+                    None => Ok(()),
+                }
+            } else if stored.as_deref() != Some(provider.api_key.as_str()) {
+                secrets.set(&name, &provider.api_key)
+            } else {
+                Ok(())
+            };
+            result.map_err(|e| SettingsError::SecretError(e.to_string()))?;
+        }
 
         Ok(())
     }
+
+    /// Serialize settings without API keys
+    fn to_json(settings: &AppSettings) -> Result<String, SettingsError> {
+        let mut stripped = settings.clone();
+        for provider in stripped.providers.values_mut() {
+            provider.api_key.clear();
+        }
+
+        serde_json::to_string_pretty(&stripped)
+            .map_err(|e| SettingsError::SerializationError(e.to_string()))
+    }
+
+    /// Save settings to disk, without API keys. Returns what was written.
+    fn save_to_disk(path: &Path, settings: &AppSettings) -> Result<String, SettingsError> {
+        let mut settings = settings.clone();
+        settings.version = SETTINGS_VERSION;
+        let json = Self::to_json(&settings)?;
+
+        write_atomic(path, json.as_bytes())
+            .map_err(|e| SettingsError::WriteError(e.to_string()))?;
+
+        Ok(json)
+    }
+}
+
+/// Write to a temporary file next to `path` and rename it into place, so a
+/// crash mid-write never leaves a truncated file behind
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::settings::ProviderApi;
+    use crate::services::secret_store::{MemorySecretStore, SecretStoreError};
     use std::env;
 
+    fn memory_store() -> Arc<dyn SecretStore> {
+        Arc::new(MemorySecretStore::default())
+    }
+
     #[test]
     fn test_settings_manager_creation() {
         let temp_dir = env::temp_dir();
@@ -138,7 +863,7 @@ mod tests {
         // Clean up if exists
         let _ = fs::remove_file(&settings_path);
 
-        let manager = SettingsManager::new(settings_path.clone());
+        let manager = SettingsManager::new(settings_path.clone(), memory_store());
         assert!(manager.is_ok());
 
         // Clean up
@@ -154,17 +879,444 @@ mod tests {
         let _ = fs::remove_file(&settings_path);
 
         // Create manager and update settings
-        let manager = SettingsManager::new(settings_path.clone()).unwrap();
+        let secrets = memory_store();
+        let manager = SettingsManager::new(settings_path.clone(), secrets.clone()).unwrap();
         let mut settings = manager.get().unwrap();
-        settings.anthropic.api_key = "test-key".to_string();
+        settings.provider_mut("anthropic").unwrap().api_key = "test-key".to_string();
         manager.update(settings.clone()).unwrap();
 
+        // Key is kept out of the settings file
+        let on_disk = fs::read_to_string(&settings_path).unwrap();
+        assert!(!on_disk.contains("test-key"));
+
         // Create new manager and verify persistence
-        let manager2 = SettingsManager::new(settings_path.clone()).unwrap();
+        let manager2 = SettingsManager::new(settings_path.clone(), secrets).unwrap();
         let loaded_settings = manager2.get().unwrap();
-        assert_eq!(loaded_settings.anthropic.api_key, "test-key");
+        assert_eq!(loaded_settings.providers["anthropic"].api_key, "test-key");
 
         // Clean up
         let _ = fs::remove_file(&settings_path);
     }
+
+    #[test]
+    fn test_get_api_key_by_provider_id() {
+        let temp_dir = env::temp_dir();
+        let settings_path = temp_dir.join("test_api_key_lookup.json");
+        let _ = fs::remove_file(&settings_path);
+
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        manager
+            .update_field(|s| s.provider_mut("openai").unwrap().api_key = "sk-openai-test-key".to_string())
+            .unwrap();
+
+        assert_eq!(
+            manager.get_api_key("openai").unwrap().as_deref(),
+            Some("sk-openai-test-key")
+        );
+        assert_eq!(manager.get_api_key("anthropic").unwrap(), None);
+        assert_eq!(manager.get_api_key("unknown").unwrap(), None);
+
+        let _ = fs::remove_file(&settings_path);
+    }
+
+    #[test]
+    fn test_plaintext_keys_migrate_to_secret_store() {
+        let temp_dir = env::temp_dir();
+        let settings_path = temp_dir.join("test_key_migration.json");
+        let _ = fs::remove_file(&settings_path);
+
+        // Settings file as written by older versions
+        let mut legacy = AppSettings::default();
+        legacy.provider_mut("anthropic").unwrap().api_key = "sk-ant-legacy".to_string();
+        fs::write(&settings_path, serde_json::to_string_pretty(&legacy).unwrap()).unwrap();
+
+        let secrets = memory_store();
+        let manager = SettingsManager::new(settings_path.clone(), secrets.clone()).unwrap();
+
+        assert_eq!(manager.get().unwrap().providers["anthropic"].api_key, "sk-ant-legacy");
+        assert_eq!(
+            secrets.get("anthropic_api_key").unwrap().as_deref(),
+            Some("sk-ant-legacy")
+        );
+        let on_disk = fs::read_to_string(&settings_path).unwrap();
+        assert!(!on_disk.contains("sk-ant-legacy"));
+
+        // Clearing a key removes it from the store
+        manager.update_field(|s| s.provider_mut("anthropic").unwrap().api_key.clear()).unwrap();
+        assert_eq!(secrets.get("anthropic_api_key").unwrap(), None);
+
+        let _ = fs::remove_file(&settings_path);
+    }
+
+    #[test]
+    fn test_plaintext_key_migration_keeps_other_stored_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+
+        let secrets = memory_store();
+        secrets.set("anthropic_api_key", "sk-ant-stored").unwrap();
+
+        // e.g. an old backup restored, or a key pasted into the file by hand
+        let mut legacy = AppSettings::default();
+        legacy.provider_mut("openai").unwrap().api_key = "sk-openai-plain".to_string();
+        fs::write(&settings_path, serde_json::to_string_pretty(&legacy).unwrap()).unwrap();
+
+        let manager = SettingsManager::new(settings_path, secrets.clone()).unwrap();
+        let settings = manager.get().unwrap();
+        assert_eq!(settings.providers["anthropic"].api_key, "sk-ant-stored");
+        assert_eq!(settings.providers["openai"].api_key, "sk-openai-plain");
+        assert_eq!(secrets.get("anthropic_api_key").unwrap().as_deref(), Some("sk-ant-stored"));
+        assert_eq!(secrets.get("openai_api_key").unwrap().as_deref(), Some("sk-openai-plain"));
+    }
+
+    #[test]
+    fn test_masked_settings_round_trip_keeps_stored_key() {
+        let mut stored = AppSettings::default();
+        stored.provider_mut("anthropic").unwrap().api_key = "sk-ant-REDACTED".to_string();
+
+        let masked = stored.masked();
+        assert!(masked.providers["anthropic"].has_key);
+        assert!(!masked.providers["openai"].has_key);
+        assert_ne!(masked.providers["anthropic"].api_key, stored.providers["anthropic"].api_key);
+        assert!(masked.providers["anthropic"].api_key.ends_with("1234"));
+
+        // Unchanged mask keeps the key, a new value replaces it
+        let mut incoming = masked.clone();
+        incoming.restore_masked_keys(&stored);
+        assert_eq!(incoming.providers["anthropic"].api_key, stored.providers["anthropic"].api_key);
+
+        let mut incoming = masked;
+        incoming.provider_mut("anthropic").unwrap().api_key = "sk-ant-new".to_string();
+        incoming.restore_masked_keys(&stored);
+        assert_eq!(incoming.providers["anthropic"].api_key, "sk-ant-new");
+    }
+
+    #[test]
+    fn test_restore_keeps_stored_keys() {
+        let temp_dir = env::temp_dir();
+        let settings_path = temp_dir.join("test_settings_restore.json");
+        let _ = fs::remove_file(&settings_path);
+
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        manager
+            .update_field(|s| s.provider_mut("anthropic").unwrap().api_key = "sk-ant-current".to_string())
+            .unwrap();
+
+        // Backups are taken from the key-less JSON
+        let backup: AppSettings = serde_json::from_str(&manager.export_json().unwrap()).unwrap();
+        assert!(backup.providers["anthropic"].api_key.is_empty());
+
+        let mut restored = backup;
+        restored.theme = "light".to_string();
+        manager.restore(restored).unwrap();
+
+        let settings = manager.get().unwrap();
+        assert_eq!(settings.theme, "light");
+        assert_eq!(settings.providers["anthropic"].api_key, "sk-ant-current");
+
+        let _ = fs::remove_file(&settings_path);
+    }
+
+    #[test]
+    fn test_corrupt_settings_are_preserved() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+        fs::write(&settings_path, r#"{"defaultProvider": "anthropic", "anthropic": {"apiKey": "sk-ant-"#).unwrap();
+
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        assert_eq!(manager.get().unwrap().theme, AppSettings::default().theme);
+
+        let warning = manager.take_load_warning().unwrap();
+        let preserved = PathBuf::from(warning.preserved_path.unwrap());
+        assert!(preserved.file_name().unwrap().to_string_lossy().starts_with("settings.corrupt-"));
+        assert!(fs::read_to_string(&preserved).unwrap().contains("sk-ant-"));
+        assert!(manager.take_load_warning().is_none());
+
+        // Defaults were written in place of the unreadable file
+        let on_disk: AppSettings = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(on_disk.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn test_unversioned_settings_are_migrated() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+
+        // Written before settings had a version or context detection
+        let mut legacy = serde_json::to_value(AppSettings::default()).unwrap();
+        let object = legacy.as_object_mut().unwrap();
+        object.remove("version");
+        object.remove("enableContextDetection");
+        object.insert("theme".to_string(), "light".into());
+        fs::write(&settings_path, legacy.to_string()).unwrap();
+
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        let settings = manager.get().unwrap();
+        assert_eq!(settings.theme, "light");
+        assert!(settings.enable_context_detection);
+        assert!(manager.load_warning().is_none());
+
+        let on_disk: Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(on_disk["version"], SETTINGS_VERSION);
+    }
+
+    /// Secret store whose writes always fail, like a locked keyring
+    struct FailingSecretStore;
+
+    impl SecretStore for FailingSecretStore {
+        fn get(&self, _name: &str) -> Result<Option<String>, SecretStoreError> {
+            Ok(None)
+        }
+
+        fn set(&self, _name: &str, _value: &str) -> Result<(), SecretStoreError> {
+            Err(SecretStoreError::KeyringError("locked".to_string()))
+        }
+
+        fn delete(&self, _name: &str) -> Result<(), SecretStoreError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_unversioned_file_keeps_keys_when_secret_store_fails() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+
+        // Written before settings had a version, with keys in the file
+        let mut legacy = AppSettings::default();
+        legacy.provider_mut("anthropic").unwrap().api_key = "sk-ant-legacy".to_string();
+        let mut legacy = serde_json::to_value(legacy).unwrap();
+        legacy.as_object_mut().unwrap().remove("version");
+        let original = legacy.to_string();
+        fs::write(&settings_path, &original).unwrap();
+
+        let manager = SettingsManager::new(settings_path.clone(), Arc::new(FailingSecretStore)).unwrap();
+        assert_eq!(manager.get().unwrap().providers["anthropic"].api_key, "sk-ant-legacy");
+        assert!(manager.load_warning().is_none());
+
+        // The file is left as it was so the migration is retried
+        assert_eq!(fs::read_to_string(&settings_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_save_leaves_no_temp_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        manager.update_field(|s| s.theme = "light".to_string()).unwrap();
+
+        let files: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(files, vec![std::ffi::OsString::from("settings.json")]);
+    }
+
+    #[test]
+    fn test_fixed_provider_fields_move_into_map() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+
+        // v1 kept each provider in its own top-level field
+        let mut legacy = serde_json::to_value(AppSettings::default()).unwrap();
+        let object = legacy.as_object_mut().unwrap();
+        let providers = object.remove("providers").unwrap();
+        for (id, provider) in providers.as_object().unwrap() {
+            object.insert(id.clone(), provider.clone());
+        }
+        object["openai"]["defaultModel"] = "gpt-4o".into();
+        object.remove("ollama");
+        object.insert("version".to_string(), 1.into());
+        fs::write(&settings_path, legacy.to_string()).unwrap();
+
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        let settings = manager.get().unwrap();
+        assert_eq!(settings.providers["openai"].default_model, "gpt-4o");
+        assert!(settings.provider("ollama").is_some());
+
+        let on_disk: Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert!(on_disk.get("openai").is_none());
+        assert_eq!(on_disk["providers"]["openai"]["defaultModel"], "gpt-4o");
+    }
+
+    #[test]
+    fn test_invalid_provider_settings_are_refused() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let secrets = memory_store();
+        let manager = SettingsManager::new(dir.path().join("settings.json"), secrets.clone()).unwrap();
+
+        let mut unknown_default = manager.get().unwrap();
+        unknown_default.default_provider = "nope".to_string();
+        assert!(matches!(
+            manager.update(unknown_default),
+            Err(SettingsError::Invalid(errors)) if errors[0].field == "defaultProvider"
+        ));
+
+        let mut custom = manager.get().unwrap();
+        let mut proxy = custom.providers["openai"].clone();
+        proxy.base_url = Some("https://llm.example.com/v1".to_string());
+        proxy.api_key = "sk-proxy".to_string();
+        custom.providers.insert("proxy".to_string(), proxy);
+        assert!(matches!(
+            manager.update(custom.clone()),
+            Err(SettingsError::Invalid(errors)) if errors[0].field == "providers.proxy.api"
+        ));
+        assert!(manager.get().unwrap().provider("proxy").is_none());
+
+        custom.provider_mut("proxy").unwrap().api = Some(ProviderApi::OpenAi);
+        manager.update(custom).unwrap();
+        assert_eq!(secrets.get("proxy_api_key").unwrap().as_deref(), Some("sk-proxy"));
+
+        // Removing the provider removes its key
+        let mut removed = manager.get().unwrap();
+        removed.providers.remove("proxy");
+        manager.update(removed).unwrap();
+        assert_eq!(secrets.get("proxy_api_key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_switch_to_other_profile() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let work_secrets = memory_store();
+        let manager = Arc::new(
+            SettingsManager::new(dir.path().join("work.json"), work_secrets.clone()).unwrap(),
+        );
+        manager
+            .update_field(|s| s.provider_mut("anthropic").unwrap().api_key = "sk-ant-work".to_string())
+            .unwrap();
+        let seen = recording_listener(&manager);
+
+        let personal_path = dir.path().join("personal.json");
+        manager.switch_to(personal_path.clone(), memory_store()).unwrap();
+        assert_eq!(manager.get_api_key("anthropic").unwrap(), None);
+        assert!(matches!(&seen.lock().unwrap()[..], [SettingsChange::Provider { id, .. }] if id == "anthropic"));
+
+        // Writes now go to the new profile only
+        manager.update_field(|s| s.theme = "light".to_string()).unwrap();
+        assert!(fs::read_to_string(&personal_path).unwrap().contains("light"));
+        assert!(!fs::read_to_string(dir.path().join("work.json")).unwrap().contains("light"));
+        assert_eq!(work_secrets.get("anthropic_api_key").unwrap().as_deref(), Some("sk-ant-work"));
+    }
+
+    #[test]
+    fn test_policy_locks_fields() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+        fs::write(&settings_path, r#"{"version": 2, "defaultProvider": "openai", "providers": {}, "theme": "light", "shortcut": ""}"#).unwrap();
+        let policy = Policy::from_json(
+            r#"{"allowedProviders": ["anthropic"], "settings": {"theme": "dark"}}"#,
+        )
+        .unwrap();
+        let manager = SettingsManager::with_policy(settings_path.clone(), memory_store(), Some(policy)).unwrap();
+
+        let settings = manager.get().unwrap();
+        assert_eq!(settings.theme, "dark");
+        assert_eq!(settings.default_provider, "anthropic");
+        assert_eq!(settings.providers.keys().collect::<Vec<_>>(), vec!["anthropic"]);
+        assert_eq!(manager.policy_info().locked_fields, vec!["theme"]);
+
+        let result = manager.update_field(|s| s.theme = "light".to_string());
+        assert!(matches!(result, Err(SettingsError::Locked(errors)) if errors[0].field == "theme"));
+
+        // Unlocked fields still save; built-ins filled back in are dropped again
+        let mut updated = manager.get().unwrap();
+        updated.shortcut = "Alt+Space".to_string();
+        updated.fill_builtin_providers();
+        manager.update(updated).unwrap();
+        assert_eq!(manager.get().unwrap().providers.len(), 1);
+
+        // So are locked fields edited by hand
+        let mut edited: Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        edited["theme"] = "light".into();
+        fs::write(&settings_path, edited.to_string()).unwrap();
+        manager.reload_from_disk();
+        assert_eq!(manager.get().unwrap().theme, "dark");
+    }
+
+    fn recording_listener(manager: &SettingsManager) -> Arc<Mutex<Vec<SettingsChange>>> {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        manager.subscribe(move |changes| sink.lock().unwrap().extend_from_slice(changes));
+        seen
+    }
+
+    #[test]
+    fn test_subscribers_receive_changed_sections() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = SettingsManager::new(dir.path().join("settings.json"), memory_store()).unwrap();
+        let seen = recording_listener(&manager);
+
+        manager.update_field(|s| s.shortcut = "Alt+Space".to_string()).unwrap();
+        manager.update_field(|s| s.shortcut = "Alt+Space".to_string()).unwrap();
+
+        let changes = seen.lock().unwrap().clone();
+        assert_eq!(
+            changes,
+            vec![SettingsChange::Shortcut {
+                old: "Control+Shift+Space".to_string(),
+                new: "Alt+Space".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_masked_change_hides_keys() {
+        let old = AppSettings::default();
+        let mut new = old.clone();
+        new.provider_mut("openai").unwrap().api_key = "sk-openai-secret-value".to_string();
+
+        let changes = SettingsChange::between(&old, &new);
+        assert_eq!(changes.len(), 1);
+        let json = serde_json::to_string(&changes[0].masked()).unwrap();
+        assert!(json.contains(r#""section":"provider""#));
+        assert!(!json.contains("sk-openai-secret-value"));
+    }
+
+    #[test]
+    fn test_reload_picks_up_external_edits_and_keeps_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+        let manager = SettingsManager::new(settings_path.clone(), memory_store()).unwrap();
+        manager
+            .update_field(|s| s.provider_mut("anthropic").unwrap().api_key = "sk-ant-stored".to_string())
+            .unwrap();
+        let seen = recording_listener(&manager);
+
+        // Our own write is not treated as an external edit
+        manager.reload_from_disk();
+        assert!(seen.lock().unwrap().is_empty());
+
+        let mut edited: Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        edited["theme"] = "light".into();
+        fs::write(&settings_path, edited.to_string()).unwrap();
+        manager.reload_from_disk();
+
+        let settings = manager.get().unwrap();
+        assert_eq!(settings.theme, "light");
+        assert_eq!(settings.providers["anthropic"].api_key, "sk-ant-stored");
+        assert_eq!(
+            seen.lock().unwrap().clone(),
+            vec![SettingsChange::Theme { old: "dark".to_string(), new: "light".to_string() }]
+        );
+
+        // A half-written file is ignored
+        fs::write(&settings_path, "{\"theme\": ").unwrap();
+        manager.reload_from_disk();
+        assert_eq!(manager.get().unwrap().theme, "light");
+    }
+
+    #[test]
+    fn test_watcher_reloads_edited_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings_path = dir.path().join("settings.json");
+        let manager = Arc::new(SettingsManager::new(settings_path.clone(), memory_store()).unwrap());
+        manager.watch().unwrap();
+
+        let mut edited: Value = serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        edited["enableContextDetection"] = false.into();
+        fs::write(&settings_path, edited.to_string()).unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while manager.get().unwrap().enable_context_detection && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(!manager.get().unwrap().enable_context_detection);
+    }
 }
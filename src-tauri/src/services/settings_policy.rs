@@ -0,0 +1,282 @@
+use crate::commands::settings::AppSettings;
+use crate::services::settings_validator::FieldError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Overrides the system location, e.g. for testing a policy
+const POLICY_PATH_ENV: &str = "SEEVA_POLICY_PATH";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    #[error("Failed to read policy file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid policy file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Contents of `policy.json`, deployed by an administrator:
+///
+/// ```json
+/// {
+///   "allowedProviders": ["anthropic"],
+///   "settings": { "enableContextDetection": false, "backup": { "enabled": true } }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PolicyFile {
+    /// Providers the user may configure; all others are hidden
+    allowed_providers: Option<Vec<String>>,
+    /// Partial settings, in the shape of `settings.json`, forced over the user's
+    #[serde(default)]
+    settings: Map<String, Value>,
+}
+
+/// What the frontend needs to know to disable locked controls
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyInfo {
+    /// camelCase paths of locked fields, e.g. `backup.enabled`
+    pub locked_fields: Vec<String>,
+    pub allowed_providers: Option<Vec<String>>,
+}
+
+/// Read-only settings enforced by the system administrator. Every leaf of
+/// the policy's `settings` is a locked field.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    allowed_providers: Option<Vec<String>>,
+    /// Path segments of each locked field with its enforced value
+    locked: Vec<(Vec<String>, Value)>,
+}
+
+/// System-wide location of the policy file
+pub fn policy_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(POLICY_PATH_ENV) {
+        return PathBuf::from(path);
+    }
+    if cfg!(target_os = "windows") {
+        PathBuf::from(r"C:\ProgramData\Seeva\policy.json")
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/Seeva/policy.json")
+    } else {
+        PathBuf::from("/etc/seeva/policy.json")
+    }
+}
+
+impl Policy {
+    /// Load the policy at `path`; `None` when there is no policy file
+    pub fn load(path: &Path) -> Result<Option<Policy>, PolicyError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(Some(Self::from_json(&data)?))
+    }
+
+    pub fn from_json(data: &str) -> Result<Policy, PolicyError> {
+        let file: PolicyFile = serde_json::from_str(data)?;
+        let mut locked = Vec::new();
+        collect_leaves(&mut Vec::new(), &Value::Object(file.settings), &mut locked);
+        Ok(Policy {
+            allowed_providers: file.allowed_providers,
+            locked,
+        })
+    }
+
+    pub fn info(&self) -> PolicyInfo {
+        PolicyInfo {
+            locked_fields: self.locked.iter().map(|(path, _)| path.join(".")).collect(),
+            allowed_providers: self.allowed_providers.clone(),
+        }
+    }
+
+    fn allows_provider(&self, id: &str) -> bool {
+        self.allowed_providers
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|a| a == id))
+    }
+
+    /// `settings` with locked fields set and disallowed providers removed
+    pub fn apply(&self, settings: &AppSettings) -> Result<AppSettings, PolicyError> {
+        let mut value = serde_json::to_value(settings)?;
+        for (path, enforced) in &self.locked {
+            set_path(&mut value, path, enforced.clone());
+        }
+        let mut applied: AppSettings = serde_json::from_value(value)?;
+
+        applied.providers.retain(|id, _| self.allows_provider(id));
+        if applied.provider(&applied.default_provider).is_none() {
+            if let Some(id) = applied.providers.keys().next() {
+                applied.default_provider = id.clone();
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Fields of `settings` that differ from what the policy enforces.
+    /// Disallowed built-in providers aren't reported: callers fill them in
+    /// and `apply` drops them again.
+    pub fn violations(&self, settings: &AppSettings) -> Vec<FieldError> {
+        let (Ok(before), Ok(after)) = (
+            serde_json::to_value(settings),
+            self.apply(settings).and_then(|s| Ok(serde_json::to_value(s)?)),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut errors = Vec::new();
+        for (path, _) in &self.locked {
+            if get_path(&before, path) != get_path(&after, path) {
+                errors.push(FieldError::new(path.join("."), "Locked by your organization's policy"));
+            }
+        }
+        let disallowed = settings
+            .providers
+            .keys()
+            .filter(|id| !self.allows_provider(id) && !AppSettings::PROVIDER_IDS.contains(&id.as_str()));
+        for id in disallowed {
+            errors.push(FieldError::new(
+                format!("providers.{}", id),
+                "Provider not allowed by your organization's policy",
+            ));
+        }
+        errors
+    }
+
+    /// Locked paths that don't name a settings field, e.g. from a typo in
+    /// the policy file
+    pub fn unknown_fields(&self) -> Vec<String> {
+        let Ok(applied) = self.apply(&AppSettings::default()) else {
+            return Vec::new();
+        };
+        let Ok(value) = serde_json::to_value(applied) else {
+            return Vec::new();
+        };
+        self.locked
+            .iter()
+            .filter(|(path, _)| get_path(&value, path).is_none())
+            .map(|(path, _)| path.join("."))
+            .collect()
+    }
+}
+
+fn collect_leaves(path: &mut Vec<String>, value: &Value, leaves: &mut Vec<(Vec<String>, Value)>) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                path.push(key.clone());
+                collect_leaves(path, child, leaves);
+                path.pop();
+            }
+        }
+        leaf => leaves.push((path.clone(), leaf.clone())),
+    }
+}
+
+fn get_path<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| value.get(key))
+}
+
+/// Set `path` to `new`, creating objects along the way
+fn set_path(value: &mut Value, path: &[String], new: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = value;
+    for key in parents {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    current.as_object_mut().unwrap().insert(last.clone(), new);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"{
+        "allowedProviders": ["anthropic", "openai"],
+        "settings": {
+            "enableContextDetection": false,
+            "backup": { "enabled": true, "keep": 30 },
+            "providers": { "openai": { "baseUrl": "https://proxy.example.com/v1" } }
+        }
+    }"#;
+
+    #[test]
+    fn test_apply_enforces_locked_fields() {
+        let policy = Policy::from_json(POLICY).unwrap();
+        let settings = AppSettings {
+            default_provider: "openrouter".to_string(),
+            ..Default::default()
+        };
+
+        let applied = policy.apply(&settings).unwrap();
+        assert!(!applied.enable_context_detection);
+        assert!(applied.backup.enabled);
+        assert_eq!(applied.backup.keep, 30);
+        assert_eq!(
+            applied.providers["openai"].base_url.as_deref(),
+            Some("https://proxy.example.com/v1")
+        );
+        assert_eq!(applied.providers.keys().collect::<Vec<_>>(), vec!["anthropic", "openai"]);
+        assert_eq!(applied.default_provider, "anthropic");
+        assert!(policy.violations(&applied).is_empty());
+    }
+
+    #[test]
+    fn test_violations_name_locked_fields() {
+        let policy = Policy::from_json(POLICY).unwrap();
+        let mut settings = policy.apply(&AppSettings::default()).unwrap();
+        settings.enable_context_detection = true;
+        settings.theme = "light".to_string();
+        settings.providers.insert(
+            "groq".to_string(),
+            AppSettings::default().providers["openai"].clone(),
+        );
+
+        let fields: Vec<String> = policy.violations(&settings).into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["enableContextDetection", "providers.groq"]);
+    }
+
+    #[test]
+    fn test_policy_can_disable_screenshots() {
+        let policy = Policy::from_json(r#"{ "settings": { "theme": "dark", "enableScreenshots": false } }"#).unwrap();
+        assert_eq!(
+            policy.info(),
+            PolicyInfo {
+                locked_fields: vec!["enableScreenshots".to_string(), "theme".to_string()],
+                allowed_providers: None,
+            }
+        );
+        assert!(policy.unknown_fields().is_empty());
+
+        let applied = policy.apply(&AppSettings::default()).unwrap();
+        assert!(!applied.enable_screenshots);
+
+        let mut settings = applied;
+        settings.enable_screenshots = true;
+        let fields: Vec<String> = policy.violations(&settings).into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["enableScreenshots"]);
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let policy = Policy::from_json(r#"{ "settings": { "theme": "dark", "telemetry": false } }"#).unwrap();
+        assert_eq!(policy.unknown_fields(), vec!["telemetry"]);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::commands::settings::{AppSettings, ProviderApi, ProviderSettings};
+use crate::services::ai::{anthropic, openai};
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use tauri_plugin_global_shortcut::Shortcut;
+
+const THEMES: [&str; 2] = ["dark", "light"];
+
+/// Problem with a single settings field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// camelCase path of the field, e.g. `providers.openai.maxTokens`
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub(crate) fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Check every field of `settings`; an empty list means they are valid
+pub fn validate(settings: &AppSettings) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if settings.provider(&settings.default_provider).is_none() {
+        errors.push(FieldError::new(
+            "defaultProvider",
+            format!("Unknown provider '{}'", settings.default_provider),
+        ));
+    }
+
+    // An empty shortcut means none is registered
+    if !settings.shortcut.is_empty() {
+        if let Err(e) = Shortcut::from_str(&settings.shortcut) {
+            errors.push(FieldError::new("shortcut", format!("Invalid shortcut: {}", e)));
+        }
+    }
+
+    if !THEMES.contains(&settings.theme.as_str()) {
+        errors.push(FieldError::new(
+            "theme",
+            format!("Theme must be one of {}", THEMES.join(", ")),
+        ));
+    }
+
+    for (id, provider) in &settings.providers {
+        validate_provider(id, provider, &mut errors);
+    }
+
+    if settings.backup.interval_hours == 0 {
+        errors.push(FieldError::new("backup.intervalHours", "Must be at least 1 hour"));
+    }
+    if settings.backup.keep == 0 {
+        errors.push(FieldError::new("backup.keep", "Must keep at least 1 backup"));
+    }
+
+    let retention = [
+        ("retention.deleteThreadsAfterDays", settings.retention.delete_threads_after_days),
+        ("retention.stripImagesAfterDays", settings.retention.strip_images_after_days),
+        ("retention.maxDatabaseMb", settings.retention.max_database_mb),
+        ("retention.purgeTrashAfterDays", settings.retention.purge_trash_after_days),
+    ];
+    for (field, value) in retention {
+        if value == Some(0) {
+            errors.push(FieldError::new(field, "Must be at least 1, or unset to turn the rule off"));
+        }
+    }
+
+    errors
+}
+
+/// Errors in `new` that `old` didn't already have. Settings that were
+/// invalid when loaded shouldn't block unrelated changes.
+pub fn new_errors(old: &AppSettings, new: &AppSettings) -> Vec<FieldError> {
+    let existing = validate(old);
+    validate(new)
+        .into_iter()
+        .filter(|e| !existing.contains(e))
+        .collect()
+}
+
+fn validate_provider(id: &str, provider: &ProviderSettings, errors: &mut Vec<FieldError>) {
+    let field = |name: &str| format!("providers.{}.{}", id, name);
+
+    let valid_id = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_id {
+        errors.push(FieldError::new(
+            format!("providers.{}", id),
+            "Ids may only contain lowercase letters, digits, '-' and '_'",
+        ));
+    }
+
+    let builtin = AppSettings::PROVIDER_IDS.contains(&id);
+    if !builtin && provider.api.is_none() {
+        errors.push(FieldError::new(field("api"), "User-added providers need an API"));
+    }
+    match &provider.base_url {
+        Some(url) if !(url.starts_with("https://") || url.starts_with("http://")) => {
+            errors.push(FieldError::new(
+                field("baseUrl"),
+                "Must start with http:// or https://",
+            ));
+        }
+        None if !builtin => {
+            errors.push(FieldError::new(field("baseUrl"), "User-added providers need a base URL"));
+        }
+        _ => {}
+    }
+    for (name, value) in &provider.headers {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() || HeaderValue::from_str(value).is_err() {
+            errors.push(FieldError::new(field("headers"), format!("Invalid header '{}'", name)));
+        }
+    }
+
+    if provider.default_model.trim().is_empty() {
+        errors.push(FieldError::new(field("defaultModel"), "A default model is required"));
+    }
+
+    let api = provider.api_for(id);
+    let max_temperature = match api {
+        Some(ProviderApi::Anthropic) => 1.0,
+        _ => 2.0,
+    };
+    if !(0.0..=max_temperature).contains(&provider.temperature) {
+        errors.push(FieldError::new(
+            field("temperature"),
+            format!("Must be between 0 and {}", max_temperature),
+        ));
+    }
+
+    let model_limit = match api {
+        Some(ProviderApi::Anthropic) => anthropic::max_output_tokens(&provider.default_model),
+        Some(ProviderApi::OpenAi) => openai::max_output_tokens(&provider.default_model),
+        _ => None,
+    };
+    if provider.max_tokens == 0 {
+        errors.push(FieldError::new(field("maxTokens"), "Must be at least 1"));
+    } else if let Some(limit) = model_limit.filter(|limit| provider.max_tokens > *limit) {
+        errors.push(FieldError::new(
+            field("maxTokens"),
+            format!("{} allows at most {} output tokens", provider.default_model, limit),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert_eq!(validate(&AppSettings::default()), Vec::new());
+    }
+
+    #[test]
+    fn test_reports_each_invalid_field() {
+        let mut settings = AppSettings {
+            default_provider: "missing".to_string(),
+            shortcut: "Control+Nope".to_string(),
+            ..Default::default()
+        };
+        let openai = settings.provider_mut("openai").unwrap();
+        openai.max_tokens = 0;
+        openai.temperature = 7.0;
+        settings.provider_mut("anthropic").unwrap().temperature = 1.5;
+
+        let errors = validate(&settings);
+        assert_eq!(
+            fields(&errors),
+            vec![
+                "defaultProvider",
+                "shortcut",
+                "providers.anthropic.temperature",
+                "providers.openai.temperature",
+                "providers.openai.maxTokens",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_tokens_bounded_by_model() {
+        let mut settings = AppSettings::default();
+        let anthropic = settings.provider_mut("anthropic").unwrap();
+        anthropic.default_model = "claude-opus-4-1-20250805".to_string();
+        anthropic.max_tokens = 64000;
+        assert_eq!(fields(&validate(&settings)), vec!["providers.anthropic.maxTokens"]);
+
+        // Unknown models are only checked for a positive limit
+        settings.provider_mut("anthropic").unwrap().default_model = "claude-next".to_string();
+        assert!(validate(&settings).is_empty());
+    }
+
+    #[test]
+    fn test_existing_errors_do_not_block_other_changes() {
+        let old = AppSettings {
+            theme: "solarized".to_string(),
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        new.shortcut = "Alt+Space".to_string();
+        assert!(new_errors(&old, &new).is_empty());
+
+        new.backup.keep = 0;
+        assert_eq!(fields(&new_errors(&old, &new)), vec!["backup.keep"]);
+    }
+}
@@ -1,21 +1,78 @@
 import { ChatWindow } from "./components/chat";
 import { ToastContainer } from "./components/ui";
+import { ProfileChooser, UnlockScreen } from "./components/settings";
 import { useStreamListener } from "./hooks/useStreamListener";
 import { useContextListener } from "./hooks/useContextListener";
+import { useSettingsWarningListener } from "./hooks/useSettingsWarningListener";
+import { useSettingsChangeListener } from "./hooks/useSettingsChangeListener";
+import { useProfileSwitchListener } from "./hooks/useProfileSwitchListener";
+import { useRetentionListener } from "./hooks/useRetentionListener";
+import { useEphemeralWipeListener } from "./hooks/useEphemeralWipeListener";
 import { useTheme } from "./hooks/useTheme";
-import { useEffect } from "react";
-import { shortcutAPI } from "./lib/tauri-api";
-import { useSettingsStore } from "./stores/settingsStore";
+import { useCallback, useEffect, useState } from "react";
+import { databaseAPI, profileAPI } from "./lib/tauri-api";
+import { useChatStore } from "./stores/chatStore";
+import { useUIStore } from "./stores/uiStore";
 import { check } from "@tauri-apps/plugin-updater";
 
 function App() {
   // Set up event listeners
   useStreamListener();
   useContextListener();
+  useSettingsWarningListener();
+  useSettingsChangeListener();
+  useRetentionListener();
+  useEphemeralWipeListener();
 
   // Apply theme to root element
   const { theme } = useTheme();
-  const { settings } = useSettingsStore();
+
+  // An encrypted database must be unlocked before threads can load
+  const [isUnlocked, setIsUnlocked] = useState<boolean | null>(null);
+
+  const { isProfileChooserOpen, openProfileChooser, closeProfileChooser } = useUIStore();
+
+  const checkDatabase = useCallback(() => {
+    databaseAPI
+      .status()
+      .then((status) => setIsUnlocked(status.unlocked))
+      .catch((error) => {
+        console.error('Failed to get database status:', error);
+        setIsUnlocked(true);
+      });
+  }, []);
+
+  useEffect(() => {
+    checkDatabase();
+  }, [checkDatabase]);
+
+  // Let the user pick a profile at startup once there is more than one
+  useEffect(() => {
+    profileAPI
+      .list()
+      .then((list) => {
+        if (list.profiles.length > 1) openProfileChooser();
+      })
+      .catch((error) => console.error('Failed to list profiles:', error));
+  }, [openProfileChooser]);
+
+  const handleProfileSwitched = useCallback(() => {
+    closeProfileChooser();
+    databaseAPI
+      .status()
+      .then((status) => {
+        setIsUnlocked(status.unlocked);
+        if (status.unlocked) useChatStore.getState().loadThreads();
+      })
+      .catch((error) => console.error('Failed to get database status:', error));
+  }, [closeProfileChooser]);
+
+  useProfileSwitchListener(handleProfileSwitched);
+
+  const handleUnlocked = () => {
+    setIsUnlocked(true);
+    useChatStore.getState().loadThreads();
+  };
 
   useEffect(() => {
     document.documentElement.setAttribute('data-theme', theme);
@@ -40,30 +97,20 @@ function App() {
     return () => clearTimeout(timeoutId);
   }, []);
 
-  // Register global shortcut on mount and when shortcut changes
-  // Note: Window toggle is now handled directly in Rust to avoid race conditions
-  useEffect(() => {
-    if (!settings?.shortcut) return;
+  // The global shortcut is registered by the backend at startup and
+  // re-registered whenever the setting changes
 
-    const registerShortcut = async () => {
-      try {
-        await shortcutAPI.register(settings.shortcut);
-      } catch (error) {
-        console.error('Failed to register global shortcut:', error);
-      }
-    };
-
-    registerShortcut();
-
-    // Cleanup: unregister on unmount
-    return () => {
-      shortcutAPI.unregister().catch(console.error);
-    };
-  }, [settings?.shortcut]);
+  if (isUnlocked === null) return null;
 
   return (
     <>
-      <ChatWindow />
+      {isProfileChooserOpen ? (
+        <ProfileChooser onChosen={closeProfileChooser} onCancel={closeProfileChooser} />
+      ) : isUnlocked ? (
+        <ChatWindow />
+      ) : (
+        <UnlockScreen onUnlocked={handleUnlocked} />
+      )}
       <ToastContainer />
     </>
   );
@@ -43,8 +43,11 @@ export function InputBar() {
   const providerSettings = (appSettings as any)[provider];
   const hasApiKey = providerSettings?.apiKey && providerSettings.apiKey.trim() !== '';
   const isApiKeyValidated = providerSettings?.isValidated === true;
+  const screenshotsEnabled = appSettings.enableScreenshots !== false;
 
   const handleScreenshot = async () => {
+    if (!screenshotsEnabled) return;
+
     try {
       // Show loading placeholder IMMEDIATELY (before API call)
       // This is a 1x1 transparent PNG - super tiny, instant display
@@ -235,9 +238,9 @@ export function InputBar() {
                 variant="ghost"
                 size="sm"
                 onClick={handleScreenshot}
-                disabled={isCapturingScreenshot || isCompressingScreenshot || !hasApiKey || !isApiKeyValidated}
+                disabled={!screenshotsEnabled || isCapturingScreenshot || isCompressingScreenshot || !hasApiKey || !isApiKeyValidated}
                 className="flex-shrink-0 p-2"
-                title={!hasApiKey ? 'Configure API key first' : !isApiKeyValidated ? 'Validate API key first' : 'Capture screenshot'}
+                title={!screenshotsEnabled ? 'Screenshots are disabled' : !hasApiKey ? 'Configure API key first' : !isApiKeyValidated ? 'Validate API key first' : 'Capture screenshot'}
               >
                 <Camera size={22} />
               </Button>
@@ -1,21 +1,54 @@
-import { useState, useMemo } from 'react';
-import { Message } from '../../lib/types';
+import { useState, useMemo, useEffect } from 'react';
+import { Message, ImageRef } from '../../lib/types';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
 import { Prism as SyntaxHighlighter } from 'react-syntax-highlighter';
 import { vscDarkPlus } from 'react-syntax-highlighter/dist/esm/styles/prism';
-import { Copy, Check } from 'lucide-react';
+import { Copy, Check, Play, GitBranch } from 'lucide-react';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { MessageContextChip } from './MessageContextChip';
+import { useChatStore } from '../../stores/chatStore';
+import { chatAPI } from '../../lib/tauri-api';
 
 interface MessageBubbleProps {
   message: Message;
 }
 
+const thumbnailClass =
+  'w-32 h-20 object-cover rounded-lg border border-border-subtle cursor-pointer hover:border-blue-500 transition-colors';
+
+// Fetches a stored image only once it is rendered
+function StoredImage({ imageRef, alt }: { imageRef: ImageRef; alt: string }) {
+  const [src, setSrc] = useState<string | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    chatAPI
+      .getImage(imageRef.id)
+      .then((image) => {
+        if (!cancelled) {
+          setSrc(`data:${image.mimeType};base64,${image.data}`);
+        }
+      })
+      .catch((error) => console.error('Failed to load image:', error));
+    return () => {
+      cancelled = true;
+    };
+  }, [imageRef.id]);
+
+  if (!src) {
+    return <div className={`${thumbnailClass} bg-glass-darker animate-pulse`} />;
+  }
+  return <img src={src} alt={alt} className={thumbnailClass} />;
+}
+
 export function MessageBubble({ message }: MessageBubbleProps) {
   const isUser = message.role === 'user';
   const isAssistant = message.role === 'assistant';
+  const isIncomplete = isAssistant && message.metadata?.status === 'incomplete';
+  const isTruncated = isAssistant && ['max_tokens', 'length'].includes(message.metadata?.stopReason ?? '');
   const [copied, setCopied] = useState(false);
+  const { continueMessage, forkThread, isStreaming } = useChatStore();
 
   // Parse context from message content
   const { hasContext, appName, windowTitle, cleanContent } = useMemo(() => {
@@ -148,14 +181,24 @@ export function MessageBubble({ message }: MessageBubbleProps) {
           </div>
 
           {/* Images if present - show as small thumbnails */}
-          {message.images && message.images.length > 0 && (
+          {message.imageRefs && message.imageRefs.length > 0 && (
+            <div className="mt-2 flex flex-wrap gap-2">
+              {message.imageRefs.map((imageRef, idx) => (
+                <div key={imageRef.id} className="relative group">
+                  <StoredImage imageRef={imageRef} alt={`Screenshot ${idx + 1}`} />
+                  <div className="absolute inset-0 bg-black/0 group-hover:bg-black/10 rounded-lg transition-colors pointer-events-none" />
+                </div>
+              ))}
+            </div>
+          )}
+          {!message.imageRefs && message.images && message.images.length > 0 && (
             <div className="mt-2 flex flex-wrap gap-2">
               {message.images.map((img, idx) => (
                 <div key={idx} className="relative group">
                   <img
-                    src={`data:image/png;base64,${img}`}
+                    src={`data:image/jpeg;base64,${img}`}
                     alt={`Screenshot ${idx + 1}`}
-                    className="w-32 h-20 object-cover rounded-lg border border-border-subtle cursor-pointer hover:border-blue-500 transition-colors"
+                    className={thumbnailClass}
                     onClick={() => {
                       // TODO: Open full size in modal/viewer
                       console.log('Open screenshot', idx);
@@ -177,8 +220,35 @@ export function MessageBubble({ message }: MessageBubbleProps) {
               <span>{message.metadata.model.split('-')[0]}</span>
             </>
           )}
+          {isIncomplete && (
+            <>
+              <span>•</span>
+              <span className="text-amber-500" title={message.metadata?.error}>Incomplete</span>
+            </>
+          )}
+          {!isIncomplete && isTruncated && (
+            <>
+              <span>•</span>
+              <span className="text-amber-500" title="The response reached the max tokens limit">Cut off</span>
+            </>
+          )}
         </div>
 
+        {/* Resume a response that was cut off */}
+        {(isIncomplete || isTruncated) && (
+          <button
+            onClick={() => continueMessage(message.id)}
+            disabled={isStreaming}
+            className="flex items-center gap-1.5 mt-1 px-2 py-1 text-[11px] transition-all duration-200
+                       text-gray-600 dark:text-gray-400
+                       hover:text-blue-600 hover:dark:text-blue-400 disabled:opacity-50"
+            title="Continue generating from where the response stopped"
+          >
+            <Play size={14} />
+            <span>Continue</span>
+          </button>
+        )}
+
         {/* Copy button for assistant messages */}
         {isAssistant && (
           <button
@@ -193,6 +263,20 @@ export function MessageBubble({ message }: MessageBubbleProps) {
             <span>{copied ? 'Copied' : 'Copy'}</span>
           </button>
         )}
+
+        {/* Explore a tangent in a new thread that ends with this message */}
+        <button
+          onClick={() => forkThread(message.id)}
+          disabled={isStreaming}
+          className="flex items-center gap-1.5 mt-1 px-2 py-1 text-[11px] transition-all duration-200
+                     opacity-0 group-hover:opacity-100
+                     text-gray-600 dark:text-gray-400
+                     hover:text-blue-600 hover:dark:text-blue-400 disabled:opacity-50"
+          title="Fork a new thread from this message"
+        >
+          <GitBranch size={14} />
+          <span>Fork</span>
+        </button>
       </div>
     </div>
   );
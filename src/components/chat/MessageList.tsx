@@ -8,17 +8,27 @@ import { Spinner } from '../ui';
 import { MessageSquare } from 'lucide-react';
 
 export function MessageList() {
-  const { currentThreadId, isStreaming, streamingContent, getThreadMessages } = useChatStore();
+  const {
+    currentThreadId,
+    isStreaming,
+    streamingContent,
+    streamingThreadId,
+    getThreadMessages,
+    hasMoreMessages,
+    isLoadingMessages,
+    loadOlderMessages,
+  } = useChatStore();
   const { currentScreenshot, isCapturingScreenshot, clearScreenshot, screenContext } = useUIStore();
   const messagesEndRef = useRef<HTMLDivElement>(null);
   const containerRef = useRef<HTMLDivElement>(null);
 
   const currentMessages = currentThreadId ? getThreadMessages(currentThreadId) : [];
 
-  // Auto-scroll to bottom when new messages arrive
+  // Auto-scroll to bottom when new messages arrive (not when older ones load)
+  const lastMessageId = currentMessages[currentMessages.length - 1]?.id;
   useEffect(() => {
     messagesEndRef.current?.scrollIntoView({ behavior: 'smooth' });
-  }, [currentMessages.length, streamingContent]);
+  }, [lastMessageId, streamingContent]);
 
   if (!currentThreadId) {
     return (
@@ -56,13 +66,25 @@ export function MessageList() {
           paddingBottom: (currentScreenshot || screenContext) ? '100px' : '24px' // Add padding when screenshot or context is shown
         }}
       >
+        {hasMoreMessages && (
+          <div className="flex justify-center">
+            <button
+              onClick={() => loadOlderMessages()}
+              disabled={isLoadingMessages}
+              className="text-[12px] text-tertiary hover:text-primary px-3 py-1 rounded-md hover:bg-glass-darker transition-colors disabled:opacity-50"
+            >
+              {isLoadingMessages ? 'Loading…' : 'Load earlier messages'}
+            </button>
+          </div>
+        )}
+
         {/* Existing messages */}
         {currentMessages.map((message) => (
           <MessageBubble key={message.id} message={message} />
         ))}
 
         {/* Streaming message (if any) */}
-        {isStreaming && streamingContent && (
+        {isStreaming && streamingContent && streamingThreadId === currentThreadId && (
           <div className="flex justify-start mb-3 animate-slide-in">
             <div className="flex flex-col max-w-[90%] sm:max-w-[85%] md:max-w-[75%] min-w-[250px]">
               <div className="text-[11px] text-tertiary font-medium mb-1 ml-1">
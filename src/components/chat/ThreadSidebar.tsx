@@ -1,7 +1,7 @@
 import { useState } from 'react';
 import { useChatStore } from '../../stores/chatStore';
 import { Button } from '../ui';
-import { Plus, MessageSquare, Trash2, Settings, ChevronLeft, Trash } from 'lucide-react';
+import { Plus, MessageSquare, Trash2, Settings, ChevronLeft, Trash, Pin, Archive, EyeOff } from 'lucide-react';
 import { useUIStore } from '../../stores/uiStore';
 import { ask, message } from '@tauri-apps/plugin-dialog';
 
@@ -10,7 +10,17 @@ interface ThreadSidebarProps {
 }
 
 export function ThreadSidebar({ isOverlayMode = false }: ThreadSidebarProps) {
-  const { threads, currentThreadId, setCurrentThread, createThread, deleteThread, clearAllThreads } = useChatStore();
+  const {
+    threads,
+    currentThreadId,
+    setCurrentThread,
+    createThread,
+    createEphemeralThread,
+    deleteThread,
+    clearAllThreads,
+    setThreadPinned,
+    archiveThread,
+  } = useChatStore();
   const { openSettings, isSidebarOpen, toggleSidebar } = useUIStore();
   const [isCreatingThread, setIsCreatingThread] = useState(false);
   const [isDeletingThread, setIsDeletingThread] = useState<string | null>(null);
@@ -162,7 +172,15 @@ export function ThreadSidebar({ isOverlayMode = false }: ThreadSidebarProps) {
                 <div className="flex items-start justify-between gap-2">
                   <div className="flex-1 min-w-0">
                     <div className="flex items-center gap-2">
-                      <MessageSquare size={14} className="text-secondary flex-shrink-0" />
+                      {thread.ephemeral ? (
+                        <span title="Private: not saved, wiped when the window hides">
+                          <EyeOff size={14} className="text-secondary flex-shrink-0" />
+                        </span>
+                      ) : thread.pinned ? (
+                        <Pin size={14} className="text-accent-blue flex-shrink-0" />
+                      ) : (
+                        <MessageSquare size={14} className="text-secondary flex-shrink-0" />
+                      )}
                       <h3 className="font-medium text-primary text-sm truncate">
                         {thread.name}
                       </h3>
@@ -184,6 +202,40 @@ export function ThreadSidebar({ isOverlayMode = false }: ThreadSidebarProps) {
                     </div>
                   </div>
 
+                  {thread.thumbnail && (
+                    <img
+                      src={`data:image/jpeg;base64,${thread.thumbnail}`}
+                      alt=""
+                      className="w-10 h-10 rounded object-cover flex-shrink-0"
+                    />
+                  )}
+
+                  {/* Pin and archive buttons; private threads can't be kept */}
+                  {!thread.ephemeral && (
+                    <>
+                      <button
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          setThreadPinned(thread.id, !thread.pinned);
+                        }}
+                        className="invisible pointer-events-none group-hover:visible group-hover:pointer-events-auto opacity-0 group-hover:opacity-100 p-1 hover:bg-glass-darker rounded transition-all flex-shrink-0"
+                        title={thread.pinned ? 'Unpin thread' : 'Pin thread'}
+                      >
+                        <Pin size={14} className="text-secondary" />
+                      </button>
+                      <button
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          archiveThread(thread.id);
+                        }}
+                        className="invisible pointer-events-none group-hover:visible group-hover:pointer-events-auto opacity-0 group-hover:opacity-100 p-1 hover:bg-glass-darker rounded transition-all flex-shrink-0"
+                        title="Archive thread"
+                      >
+                        <Archive size={14} className="text-secondary" />
+                      </button>
+                    </>
+                  )}
+
                   {/* Delete button */}
                   <button
                     onClick={(e) => handleDeleteThread(thread.id, e)}
@@ -201,18 +253,26 @@ export function ThreadSidebar({ isOverlayMode = false }: ThreadSidebarProps) {
       </div>
 
       {/* Footer */}
-      <div className="p-4 border-t border-border-subtle">
+      <div className="p-4 border-t border-border-subtle flex gap-2">
         <Button
           variant="primary"
           size="sm"
           onClick={handleCreateThread}
           disabled={isCreatingThread}
           isLoading={isCreatingThread}
-          className="w-full"
+          className="flex-1"
         >
           <Plus size={16} />
           New Thread
         </Button>
+        <Button
+          variant="secondary"
+          size="sm"
+          onClick={() => createEphemeralThread()}
+          title="Private conversation: kept in memory only and wiped when the window hides"
+        >
+          <EyeOff size={16} />
+        </Button>
       </div>
     </div>
   );
@@ -0,0 +1,144 @@
+import { useEffect, useState, FormEvent } from 'react';
+import { User } from 'lucide-react';
+import { Button, Input } from '../ui';
+import { profileAPI, type Profile } from '../../lib/tauri-api';
+
+interface ProfileChooserProps {
+  onChosen: (profile: Profile) => void;
+  onCancel?: () => void;
+}
+
+// Shown at startup when there is more than one profile, and from settings
+export function ProfileChooser({ onChosen, onCancel }: ProfileChooserProps) {
+  const [profiles, setProfiles] = useState<Profile[]>([]);
+  const [activeId, setActiveId] = useState<string | null>(null);
+  const [newName, setNewName] = useState('');
+  const [error, setError] = useState<string | undefined>();
+  const [isBusy, setIsBusy] = useState(false);
+
+  // Set when the chosen profile's database is encrypted
+  const [locked, setLocked] = useState<Profile | null>(null);
+  const [passphrase, setPassphrase] = useState('');
+
+  useEffect(() => {
+    profileAPI
+      .list()
+      .then((list) => {
+        setProfiles(list.profiles);
+        setActiveId(list.active);
+      })
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  const choose = async (profile: Profile, passphrase?: string) => {
+    setIsBusy(true);
+    setError(undefined);
+    try {
+      const switched = await profileAPI.switch(profile.id, passphrase);
+      onChosen(switched);
+    } catch (err) {
+      const message = String(err);
+      if (message.includes('Passphrase required')) {
+        setLocked(profile);
+      } else {
+        setError(message);
+      }
+      setIsBusy(false);
+    }
+  };
+
+  const handleCreate = async (e: FormEvent) => {
+    e.preventDefault();
+    if (!newName.trim()) return;
+
+    setError(undefined);
+    try {
+      const profile = await profileAPI.create(newName);
+      setProfiles([...profiles, profile]);
+      setNewName('');
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleUnlock = (e: FormEvent) => {
+    e.preventDefault();
+    if (locked && passphrase) choose(locked, passphrase);
+  };
+
+  if (locked) {
+    return (
+      <div className="flex h-screen items-center justify-center p-6">
+        <form onSubmit={handleUnlock} className="w-full max-w-xs space-y-4">
+          <p className="text-center text-sm text-primary">
+            Enter the passphrase for {locked.name}
+          </p>
+          <Input
+            type="password"
+            autoFocus
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            placeholder="Passphrase"
+            error={error}
+          />
+          <div className="flex gap-2">
+            <Button type="button" variant="secondary" className="flex-1" onClick={() => setLocked(null)}>
+              Back
+            </Button>
+            <Button type="submit" className="flex-1" isLoading={isBusy} disabled={!passphrase}>
+              Open
+            </Button>
+          </div>
+        </form>
+      </div>
+    );
+  }
+
+  return (
+    <div className="flex h-screen items-center justify-center p-6">
+      <div className="w-full max-w-xs space-y-4">
+        <div className="flex flex-col items-center gap-2 text-center">
+          <User className="h-6 w-6 text-secondary" />
+          <p className="text-sm text-primary">Choose a profile</p>
+        </div>
+
+        <div className="space-y-2">
+          {profiles.map((profile) => (
+            <Button
+              key={profile.id}
+              variant={profile.id === activeId ? 'primary' : 'secondary'}
+              className="w-full"
+              disabled={isBusy}
+              onClick={() => choose(profile)}
+            >
+              {profile.name}
+            </Button>
+          ))}
+        </div>
+
+        <form onSubmit={handleCreate} className="flex gap-2">
+          <Input
+            value={newName}
+            onChange={(e) => setNewName(e.target.value)}
+            placeholder="New profile"
+            className="text-sm"
+          />
+          <Button type="submit" variant="secondary" disabled={!newName.trim()}>
+            Add
+          </Button>
+        </form>
+
+        {error && <p className="text-center text-xs text-red-500">{error}</p>}
+        {onCancel && (
+          <button
+            type="button"
+            onClick={onCancel}
+            className="w-full text-xs text-tertiary hover:text-primary"
+          >
+            Cancel
+          </button>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -4,11 +4,13 @@ import { ShortcutRecorder } from '../ui/ShortcutRecorder';
 import { useUIStore } from '../../stores/uiStore';
 import { useSettingsStore } from '../../stores/settingsStore';
 import { useToast } from '../../hooks/useToast';
-import { Eye, EyeOff, ExternalLink, Loader2 } from 'lucide-react';
-import type { AppSettings, ProviderSettings } from '../../lib/tauri-api';
-import { settingsAPI } from '../../lib/tauri-api';
+import { Eye, EyeOff, ExternalLink, Loader2, Lock } from 'lucide-react';
+import type { AppSettings, PolicyInfo, ProviderSettings, RetentionSettings } from '../../lib/tauri-api';
+import { retentionAPI, settingsAPI } from '../../lib/tauri-api';
+import type { RetentionReport } from '../../lib/types';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { getAppVersion } from '../../lib/constants';
+import { TrashList } from './TrashList';
 
 const PROVIDER_MODELS = {
   anthropic: [
@@ -67,7 +69,7 @@ const PROVIDER_INFO = {
 type ProviderKey = 'anthropic' | 'openai' | 'openrouter';
 
 export function SettingsModal() {
-  const { isSettingsOpen, closeSettings } = useUIStore();
+  const { isSettingsOpen, closeSettings, openProfileChooser } = useUIStore();
   const { settings: storeSettings, updateSettings, isLoading } = useSettingsStore();
   const toast = useToast();
 
@@ -84,6 +86,7 @@ export function SettingsModal() {
     openrouter: false,
   });
   const [selectedProvider, setSelectedProvider] = useState<ProviderKey>('anthropic');
+  const [policy, setPolicy] = useState<PolicyInfo>({ lockedFields: [], allowedProviders: null });
 
   // Initialize local settings when modal opens
   useEffect(() => {
@@ -91,14 +94,37 @@ export function SettingsModal() {
       setLocalSettings(JSON.parse(JSON.stringify(storeSettings)));
       // Select the default provider tab
       setSelectedProvider((storeSettings.defaultProvider as ProviderKey) || 'anthropic');
+      settingsAPI.getPolicy().then(setPolicy).catch((error) => {
+        console.error('Failed to load settings policy:', error);
+      });
     }
   }, [isSettingsOpen]);
 
+  // Locked by the administrator's policy, directly or through a parent field
+  const isLocked = (field: string) =>
+    policy.lockedFields.some((locked) => locked === field || field.startsWith(`${locked}.`));
+
+  const visibleProviders = (['anthropic', 'openai', 'openrouter'] as ProviderKey[]).filter(
+    (provider) => !policy.allowedProviders || policy.allowedProviders.includes(provider)
+  );
+
   // Fetch app version dynamically on mount
   useEffect(() => {
     getAppVersion().then(setAppVersion);
   }, []);
 
+  // Settings update changing one provider's fields
+  const withProvider = (
+    settings: AppSettings,
+    provider: string,
+    changes: Partial<ProviderSettings>
+  ): Partial<AppSettings> => ({
+    providers: {
+      ...settings.providers,
+      [provider]: { ...settings.providers[provider], ...changes },
+    },
+  });
+
   // Auto-save helper
   const autoSave = async (updates: Partial<AppSettings>) => {
     if (!localSettings) return null;
@@ -107,6 +133,14 @@ export function SettingsModal() {
     setLocalSettings(newSettings);
 
     try {
+      // The backend refuses invalid settings; report why and keep the old values
+      const errors = await settingsAPI.validate(newSettings);
+      if (errors.length > 0) {
+        toast.error(errors.map((e) => e.message).join('\n'), 4000);
+        setLocalSettings(localSettings);
+        return null;
+      }
+
       await updateSettings(newSettings);
       // Reload from backend to ensure localSettings stays in sync
       const freshSettings = await settingsAPI.get();
@@ -128,12 +162,13 @@ export function SettingsModal() {
     if (!localSettings) return;
 
     // Disable all providers except the selected one
-    const updates: Partial<AppSettings> = {
-      defaultProvider: provider,
-      anthropic: { ...localSettings.anthropic, enabled: provider === 'anthropic' },
-      openai: { ...localSettings.openai, enabled: provider === 'openai' },
-      openrouter: { ...localSettings.openrouter, enabled: provider === 'openrouter' },
-    };
+    const providers = Object.fromEntries(
+      Object.entries(localSettings.providers).map(([id, settings]) => [
+        id,
+        { ...settings, enabled: id === provider },
+      ])
+    );
+    const updates: Partial<AppSettings> = { defaultProvider: provider, providers };
 
     await autoSave(updates);
   };
@@ -145,14 +180,7 @@ export function SettingsModal() {
   ) => {
     if (!localSettings) return;
 
-    const updates = {
-      [provider]: {
-        ...localSettings[provider],
-        [field]: value,
-      },
-    };
-
-    await autoSave(updates);
+    await autoSave(withProvider(localSettings, provider, { [field]: value }));
   };
 
   const handleApiKeyBlur = async (provider: ProviderKey, value: string) => {
@@ -167,8 +195,8 @@ export function SettingsModal() {
 
   const handleTestConnection = async (provider: ProviderKey, _isAutoValidation = false) => {
     if (!localSettings) return;
-    const providerSettings = localSettings[provider];
-    if (!providerSettings.apiKey) return;
+    const providerSettings = localSettings.providers[provider];
+    if (!providerSettings?.apiKey) return;
 
     setTestingProviders({ ...testingProviders, [provider]: true });
 
@@ -180,21 +208,11 @@ export function SettingsModal() {
 
       // Save validation state and get fresh settings
       await settingsAPI.setValidationState(provider, true);
-      const freshSettings = await autoSave({
-        [provider]: {
-          ...localSettings[provider],
-          isValidated: true,
-        },
-      });
+      const freshSettings = await autoSave(withProvider(localSettings, provider, { isValidated: true }));
 
       // Update available models using fresh settings
       if (result.availableModels && result.availableModels.length > 0 && freshSettings) {
-        await autoSave({
-          [provider]: {
-            ...freshSettings[provider],
-            defaultModel: result.defaultModel,
-          },
-        });
+        await autoSave(withProvider(freshSettings, provider, { defaultModel: result.defaultModel }));
       }
     } catch (error) {
       console.error('Connection test failed:', error);
@@ -210,6 +228,34 @@ export function SettingsModal() {
     }
   };
 
+  // An empty field turns the rule off
+  const handleRetentionChange = (field: keyof RetentionSettings, value: string) => {
+    if (!localSettings) return;
+    const days = value.trim() === '' ? null : parseInt(value, 10);
+    if (days !== null && Number.isNaN(days)) return;
+    if (localSettings.retention[field] === days) return;
+
+    autoSave({ retention: { ...localSettings.retention, [field]: days } });
+  };
+
+  const describeRetention = (report: RetentionReport) => {
+    const freed = Math.max(0, report.bytesBefore - report.bytesAfter) / (1024 * 1024);
+    const threads = report.deletedThreads.length;
+    return `${threads} conversation${threads === 1 ? '' : 's'}, ${report.strippedImages} image${
+      report.strippedImages === 1 ? '' : 's'
+    } and ${report.purgedTrash} item${report.purgedTrash === 1 ? '' : 's'} from the trash (${freed.toFixed(1)} MB)`;
+  };
+
+  const handleRetention = async (dryRun: boolean) => {
+    try {
+      const report = dryRun ? await retentionAPI.preview() : await retentionAPI.run();
+      toast.success(`${dryRun ? 'Would remove' : 'Removed'} ${describeRetention(report)}`, 4000);
+    } catch (error) {
+      console.error('Failed to apply retention rules:', error);
+      toast.error(`Cleanup failed: ${error}`, 4000);
+    }
+  };
+
   const handleOpenApiKeyUrl = async (provider: ProviderKey) => {
     try {
       await openUrl(PROVIDER_INFO[provider].keyUrl);
@@ -223,10 +269,12 @@ export function SettingsModal() {
   }
 
   const renderProviderSection = (provider: ProviderKey) => {
-    const providerSettings = localSettings[provider];
+    const providerSettings = localSettings.providers[provider];
+    if (!providerSettings) return null;
     const info = PROVIDER_INFO[provider];
     const models = PROVIDER_MODELS[provider];
     const isActive = localSettings.defaultProvider === provider;
+    const locked = (field: string) => isLocked(`providers.${provider}.${field}`);
 
     return (
       <div key={provider} className="space-y-4 p-4 rounded-lg border border-border-subtle bg-surface-secondary/30">
@@ -237,6 +285,7 @@ export function SettingsModal() {
             name="provider"
             checked={isActive}
             onChange={() => handleProviderSelect(provider)}
+            disabled={isLocked('defaultProvider')}
             className="w-4.5 h-4 text-accent-blue focus:ring-2 focus:ring-accent-blue"
           />
           <span className="text-sm font-semibold text-primary group-hover:text-accent-blue transition-colors">
@@ -258,17 +307,17 @@ export function SettingsModal() {
                   // Update local state immediately for responsive UI
                   setLocalSettings({
                     ...localSettings,
-                    [provider]: {
-                      ...providerSettings,
+                    ...withProvider(localSettings, provider, {
                       apiKey: e.target.value,
                       isValidated: false, // Reset validation when key changes
-                    },
+                    }),
                   });
                   // Reset validation state in backend
                   await settingsAPI.setValidationState(provider, false);
                 }}
                 onBlur={(e) => handleApiKeyBlur(provider, e.target.value)}
                 placeholder={`${info.keyPrefix}...`}
+                disabled={locked('apiKey')}
                 className="pr-10 text-sm"
               />
               <button
@@ -307,6 +356,7 @@ export function SettingsModal() {
             value={providerSettings.defaultModel}
             onChange={(e) => handleProviderSettingChange(provider, 'defaultModel', e.target.value)}
             options={models}
+            disabled={locked('defaultModel')}
             className="text-sm"
           />
         </div>
@@ -318,6 +368,7 @@ export function SettingsModal() {
             value={providerSettings.maxTokens.toString()}
             onChange={(e) => handleProviderSettingChange(provider, 'maxTokens', parseInt(e.target.value, 10))}
             options={MAX_TOKENS_OPTIONS}
+            disabled={locked('maxTokens')}
             className="text-sm"
           />
         </div>
@@ -341,6 +392,7 @@ export function SettingsModal() {
           <ShortcutRecorder
             value={localSettings.shortcut}
             onChange={handleShortcutChange}
+            disabled={isLocked('shortcut')}
           />
           <p className="mt-1.5 text-xs text-tertiary">
             Set a keyboard shortcut to toggle the app from anywhere
@@ -350,14 +402,18 @@ export function SettingsModal() {
         {/* Context Detection Section */}
         <div className="space-y-4">
           <div className="flex items-center justify-between py-2 px-3 rounded-lg border border-border-subtle bg-surface-secondary/30">
-            <span className="text-sm font-medium text-primary">Screen Context</span>
+            <span className="flex items-center gap-1.5 text-sm font-medium text-primary">
+              Screen Context
+              {isLocked('enableContextDetection') && <Lock size={12} className="text-tertiary" />}
+            </span>
             <button
+              disabled={isLocked('enableContextDetection')}
               onClick={() => {
                 autoSave({
                   enableContextDetection: !localSettings.enableContextDetection,
                 });
               }}
-              className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors ${
+              className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors disabled:opacity-50 disabled:cursor-not-allowed ${
                 localSettings.enableContextDetection ? 'bg-accent-blue' : 'bg-glass-light/50'
               }`}
             >
@@ -368,12 +424,69 @@ export function SettingsModal() {
           </div>
         </div>
 
+        {/* Screenshots Section */}
+        <div className="space-y-4">
+          <div className="flex items-center justify-between py-2 px-3 rounded-lg border border-border-subtle bg-surface-secondary/30">
+            <span className="flex items-center gap-1.5 text-sm font-medium text-primary">
+              Screenshots
+              {isLocked('enableScreenshots') && <Lock size={12} className="text-tertiary" />}
+            </span>
+            <button
+              disabled={isLocked('enableScreenshots')}
+              onClick={() => {
+                autoSave({
+                  enableScreenshots: !localSettings.enableScreenshots,
+                });
+              }}
+              className={`relative inline-flex h-6 w-11 items-center rounded-full transition-colors disabled:opacity-50 disabled:cursor-not-allowed ${
+                localSettings.enableScreenshots ? 'bg-accent-blue' : 'bg-glass-light/50'
+              }`}
+            >
+              <span className={`inline-block h-4 w-4 transform rounded-full bg-white shadow-sm transition-transform ${
+                localSettings.enableScreenshots ? 'translate-x-6' : 'translate-x-1'
+              }`} />
+            </button>
+          </div>
+        </div>
+
+        {/* Data Retention Section */}
+        <div className="space-y-3 p-3 rounded-lg border border-border-subtle bg-surface-secondary/30">
+          <span className="text-sm font-medium text-primary">Data Retention</span>
+          {([
+            ['deleteThreadsAfterDays', 'Delete conversations untouched for (days)'],
+            ['stripImagesAfterDays', 'Remove screenshots older than (days)'],
+            ['maxDatabaseMb', 'Limit storage to (MB)'],
+            ['purgeTrashAfterDays', 'Empty trash after (days)'],
+          ] as [keyof RetentionSettings, string][]).map(([field, label]) => (
+            <Input
+              key={field}
+              type="number"
+              min={1}
+              label={label}
+              defaultValue={localSettings.retention[field] ?? ''}
+              onBlur={(e) => handleRetentionChange(field, e.target.value)}
+              placeholder="Off"
+              disabled={isLocked(`retention.${field}`)}
+              className="text-sm"
+            />
+          ))}
+          <div className="flex gap-2">
+            <Button variant="secondary" className="text-xs" onClick={() => handleRetention(true)}>
+              Preview
+            </Button>
+            <Button variant="secondary" className="text-xs" onClick={() => handleRetention(false)}>
+              Clean up now
+            </Button>
+          </div>
+          <TrashList />
+        </div>
+
         {/* AI Providers Section */}
         <div className="space-y-4">
 
           {/* Provider Tabs */}
           <div className="flex gap-2 border-b border-border-subtle">
-            {(['anthropic', 'openai', 'openrouter'] as ProviderKey[]).map((provider) => {
+            {visibleProviders.map((provider) => {
               const isActive = localSettings.defaultProvider === provider;
               return (
                 <button
@@ -401,9 +514,23 @@ export function SettingsModal() {
           {renderProviderSection(selectedProvider)}
         </div>
 
+        {policy.lockedFields.length > 0 && (
+          <p className="flex items-center gap-1.5 text-xs text-tertiary">
+            <Lock size={12} />
+            Some settings are managed by your organization
+          </p>
+        )}
+
         {/* Footer with Update Checker and Version */}
         <div className="pt-4 border-t border-border-subtle flex items-center justify-between">
           <span className="text-xs text-tertiary">v{appVersion}</span>
+          <button
+            type="button"
+            onClick={openProfileChooser}
+            className="text-xs text-accent-blue hover:underline"
+          >
+            Switch profile
+          </button>
           <UpdateChecker />
         </div>
       </div>
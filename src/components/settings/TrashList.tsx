@@ -0,0 +1,88 @@
+import { useCallback, useEffect, useState } from 'react';
+import { MessageSquare, RotateCcw, Trash2 } from 'lucide-react';
+import { Button } from '../ui';
+import { trashAPI } from '../../lib/tauri-api';
+import type { TrashItem } from '../../lib/types';
+import { useChatStore } from '../../stores/chatStore';
+import { useToast } from '../../hooks/useToast';
+
+// Deleted threads and messages, restorable until the trash is purged
+export function TrashList() {
+  const [items, setItems] = useState<TrashItem[]>([]);
+  const toast = useToast();
+
+  const load = useCallback(() => {
+    trashAPI
+      .list()
+      .then(setItems)
+      .catch((error) => console.error('Failed to load trash:', error));
+  }, []);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  const handleRestore = async (item: TrashItem) => {
+    try {
+      if (item.kind === 'thread') {
+        await trashAPI.restoreThread(item.id);
+      } else {
+        await trashAPI.restoreMessage(item.id);
+      }
+      await useChatStore.getState().loadThreads();
+      load();
+    } catch (error) {
+      toast.error(`Failed to restore: ${error}`, 4000);
+    }
+  };
+
+  const handleEmpty = async () => {
+    try {
+      const purged = await trashAPI.empty();
+      toast.success(`Permanently deleted ${purged} item${purged === 1 ? '' : 's'}`, 3000);
+      load();
+    } catch (error) {
+      toast.error(`Failed to empty trash: ${error}`, 4000);
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-medium text-primary">Trash</span>
+        <Button variant="secondary" className="text-xs" onClick={handleEmpty} disabled={items.length === 0}>
+          Empty trash
+        </Button>
+      </div>
+      {items.length === 0 ? (
+        <p className="text-xs text-tertiary">Nothing in the trash</p>
+      ) : (
+        <div className="max-h-48 overflow-y-auto space-y-1">
+          {items.map((item) => (
+            <div key={item.id} className="flex items-center gap-2 p-2 rounded hover:bg-glass-darker">
+              {item.kind === 'thread' ? (
+                <MessageSquare size={14} className="text-secondary flex-shrink-0" />
+              ) : (
+                <Trash2 size={14} className="text-secondary flex-shrink-0" />
+              )}
+              <div className="flex-1 min-w-0">
+                <p className="text-xs text-primary truncate">{item.preview ?? item.threadName}</p>
+                <p className="text-xs text-tertiary truncate">
+                  {item.kind === 'message' && `${item.threadName} • `}
+                  Deleted {new Date(item.deletedAt).toLocaleDateString()}
+                </p>
+              </div>
+              <button
+                onClick={() => handleRestore(item)}
+                className="p-1 hover:bg-glass-darker rounded transition-colors flex-shrink-0"
+                title="Restore"
+              >
+                <RotateCcw size={14} className="text-secondary" />
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
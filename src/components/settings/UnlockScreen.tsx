@@ -0,0 +1,52 @@
+import { useState, FormEvent } from 'react';
+import { Lock } from 'lucide-react';
+import { Button, Input } from '../ui';
+import { databaseAPI } from '../../lib/tauri-api';
+
+interface UnlockScreenProps {
+  onUnlocked: () => void;
+}
+
+// Shown at startup while the encrypted database is still locked
+export function UnlockScreen({ onUnlocked }: UnlockScreenProps) {
+  const [passphrase, setPassphrase] = useState('');
+  const [error, setError] = useState<string | undefined>();
+  const [isUnlocking, setIsUnlocking] = useState(false);
+
+  const handleSubmit = async (e: FormEvent) => {
+    e.preventDefault();
+    if (!passphrase) return;
+
+    setIsUnlocking(true);
+    setError(undefined);
+    try {
+      await databaseAPI.unlock(passphrase);
+      onUnlocked();
+    } catch (err) {
+      setError(String(err));
+      setIsUnlocking(false);
+    }
+  };
+
+  return (
+    <div className="flex h-screen items-center justify-center p-6">
+      <form onSubmit={handleSubmit} className="w-full max-w-xs space-y-4">
+        <div className="flex flex-col items-center gap-2 text-center">
+          <Lock className="h-6 w-6 text-secondary" />
+          <p className="text-sm text-primary">Enter your passphrase to unlock your conversations</p>
+        </div>
+        <Input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          placeholder="Passphrase"
+          error={error}
+        />
+        <Button type="submit" className="w-full" isLoading={isUnlocking} disabled={!passphrase}>
+          Unlock
+        </Button>
+      </form>
+    </div>
+  );
+}
@@ -1 +1,4 @@
 export { SettingsModal } from './SettingsModal';
+export { UnlockScreen } from './UnlockScreen';
+export { ProfileChooser } from './ProfileChooser';
+export { TrashList } from './TrashList';
@@ -4,9 +4,10 @@ import { Command } from 'lucide-react';
 interface ShortcutRecorderProps {
   value: string;
   onChange: (shortcut: string) => void;
+  disabled?: boolean;
 }
 
-export function ShortcutRecorder({ value, onChange }: ShortcutRecorderProps) {
+export function ShortcutRecorder({ value, onChange, disabled = false }: ShortcutRecorderProps) {
   const [isRecording, setIsRecording] = useState(false);
   const [recordedKeys, setRecordedKeys] = useState<Set<string>>(new Set());
   const inputRef = useRef<HTMLDivElement>(null);
@@ -129,7 +130,7 @@ export function ShortcutRecorder({ value, onChange }: ShortcutRecorderProps) {
           </span>
         </div>
 
-        <div className="flex gap-2">
+        <div className={`flex gap-2 ${disabled ? 'hidden' : ''}`}>
           {!isRecording && value && (
             <button
               onClick={handleClearShortcut}
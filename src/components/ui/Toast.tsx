@@ -46,6 +46,17 @@ function Toast({ toast, onClose }: ToastProps) {
     >
       <div className="flex-shrink-0 mt-0.5">{icons[toast.type]}</div>
       <div className="flex-1 text-sm text-text-primary">{toast.message}</div>
+      {toast.action && (
+        <button
+          onClick={() => {
+            toast.action?.onClick();
+            onClose();
+          }}
+          className="flex-shrink-0 px-2 py-0.5 rounded text-sm font-medium text-accent-blue hover:bg-glass-light transition-colors"
+        >
+          {toast.action.label}
+        </button>
+      )}
       <button
         onClick={onClose}
         className="flex-shrink-0 p-1 rounded hover:bg-glass-light transition-colors text-text-secondary hover:text-text-primary"
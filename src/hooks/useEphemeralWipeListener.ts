@@ -0,0 +1,16 @@
+import { useEffect } from 'react';
+import { threadAPI } from '../lib/tauri-api';
+import { useChatStore } from '../stores/chatStore';
+
+// Drop private threads from the list once the backend has wiped them
+export function useEphemeralWipeListener() {
+  useEffect(() => {
+    const unlisten = threadAPI.onEphemeralWiped(() => {
+      useChatStore.getState().loadThreads();
+    });
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+}
@@ -0,0 +1,19 @@
+import { useEffect } from 'react';
+import { profileAPI } from '../lib/tauri-api';
+import { useChatStore } from '../stores/chatStore';
+import { useSettingsStore } from '../stores/settingsStore';
+
+// Drop the previous profile's conversations and reload from the new one
+export function useProfileSwitchListener(onSwitched: () => void) {
+  useEffect(() => {
+    const unlisten = profileAPI.onSwitched(() => {
+      useChatStore.setState({ threads: [], currentThreadId: null, messages: [] });
+      useSettingsStore.getState().loadSettings();
+      onSwitched();
+    });
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [onSwitched]);
+}
@@ -0,0 +1,16 @@
+import { useEffect } from 'react';
+import { retentionAPI } from '../lib/tauri-api';
+import { useChatStore } from '../stores/chatStore';
+
+// Reload the thread list after retention rules deleted threads or images
+export function useRetentionListener() {
+  useEffect(() => {
+    const unlisten = retentionAPI.onApplied(() => {
+      useChatStore.getState().loadThreads();
+    });
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+}
@@ -0,0 +1,16 @@
+import { useEffect } from 'react';
+import { settingsAPI } from '../lib/tauri-api';
+import { useSettingsStore } from '../stores/settingsStore';
+
+// Keep the settings store in step with the backend, including edits made to settings.json directly
+export function useSettingsChangeListener() {
+  useEffect(() => {
+    const unlisten = settingsAPI.onChanged(() => {
+      useSettingsStore.getState().loadSettings();
+    });
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+}
@@ -0,0 +1,33 @@
+import { useEffect } from 'react';
+import { settingsAPI, type SettingsWarning } from '../lib/tauri-api';
+import { useToastStore } from './useToast';
+
+// Warnings can arrive both as an event and from the startup pull; show each once
+const shown = new Set<string>();
+
+function showWarning(warning: SettingsWarning) {
+  const key = `${warning.message}|${warning.preservedPath ?? ''}`;
+  if (shown.has(key)) return;
+  shown.add(key);
+
+  const message = warning.preservedPath
+    ? `${warning.message}. The old file was kept at ${warning.preservedPath}`
+    : warning.message;
+  useToastStore.getState().addToast({ type: 'warning', message, duration: 15000 });
+}
+
+export function useSettingsWarningListener() {
+  useEffect(() => {
+    const unlisten = settingsAPI.onWarning(showWarning);
+
+    // The startup warning is emitted before the webview listens, so ask for it
+    settingsAPI
+      .takeWarning()
+      .then((warning) => warning && showWarning(warning))
+      .catch((error) => console.error('Failed to get settings warning:', error));
+
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+}
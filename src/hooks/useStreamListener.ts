@@ -7,9 +7,14 @@ export function useStreamListener() {
     let unsubscribe: (() => void) | undefined;
 
     const setupListener = async () => {
-      unsubscribe = await chatAPI.onStreamEvent((event) => {
+      unsubscribe = await chatAPI.onStreamEvent(({ requestId, threadId, event }) => {
         const store = useChatStore.getState();
 
+        // Ignore events from requests this window is no longer waiting on
+        if (requestId !== store.activeRequestId) {
+          return;
+        }
+
         switch (event.type) {
           case 'message_start':
             store.setStreaming(true);
@@ -21,14 +26,13 @@ export function useStreamListener() {
             break;
 
           case 'message_stop':
-            store.setStreaming(false);
-            // The full message will be added by the sendMessage function
+            // Sent once the assistant message has been saved
+            store.finishStream(threadId);
             break;
 
           case 'error':
             console.error('Stream error:', event.error);
-            store.setStreaming(false);
-            store.clearStreamingContent();
+            store.failStream(threadId, event.error);
             break;
         }
       });
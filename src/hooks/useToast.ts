@@ -7,6 +7,7 @@ export interface Toast {
   type: ToastType;
   message: string;
   duration?: number;
+  action?: { label: string; onClick: () => void }; // e.g. Undo
 }
 
 interface ToastStore {
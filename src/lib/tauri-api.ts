@@ -1,6 +1,25 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { Message, Thread, AIProvider } from './types';
+import type {
+  Thread,
+  ThreadFilters,
+  Folder,
+  Tag,
+  MessagePage,
+  ImageData,
+  ExportFormat,
+  ExportImageMode,
+  ImportReport,
+  BackupManifest,
+  RetentionReport,
+  TrashItem,
+  AIProvider,
+  ModelTarget,
+  SendMessageResponse,
+  SendMessageMultiResponse,
+  SearchFilters,
+  SearchHit,
+} from './types';
 
 // Thread API
 export const threadAPI = {
@@ -8,14 +27,35 @@ export const threadAPI = {
     return await invoke('create_thread', { name });
   },
 
-  list: async (): Promise<Thread[]> => {
-    return await invoke('list_threads');
+  // Private thread: never saved, wiped when the window hides
+  createEphemeral: async (name: string): Promise<Thread> => {
+    return await invoke('create_ephemeral_thread', { name });
+  },
+
+  listEphemeral: async (): Promise<Thread[]> => {
+    return await invoke('list_ephemeral_threads');
+  },
+
+  onEphemeralWiped: (callback: () => void) => {
+    return listen<number>('ephemeral-threads-wiped', () => {
+      callback();
+    });
+  },
+
+  // Pinned threads come first
+  list: async (filters?: ThreadFilters): Promise<Thread[]> => {
+    return await invoke('list_threads', { filters });
   },
 
   get: async (id: string): Promise<Thread | null> => {
     return await invoke('get_thread', { id });
   },
 
+  // New thread with the history up to and including messageId; becomes current
+  fork: async (threadId: string, messageId: string): Promise<Thread> => {
+    return await invoke('fork_thread', { threadId, messageId });
+  },
+
   switch: async (threadId: string): Promise<void> => {
     return await invoke('switch_thread', { threadId });
   },
@@ -31,6 +71,224 @@ export const threadAPI = {
   getCurrentId: async (): Promise<string | null> => {
     return await invoke('get_current_thread_id');
   },
+
+  setPinned: async (id: string, pinned: boolean): Promise<void> => {
+    return await invoke('set_thread_pinned', { id, pinned });
+  },
+
+  setArchived: async (id: string, archived: boolean): Promise<void> => {
+    return await invoke('set_thread_archived', { id, archived });
+  },
+
+  moveToFolder: async (id: string, folderId: string | null): Promise<void> => {
+    return await invoke('move_thread_to_folder', { id, folderId });
+  },
+
+  addTag: async (threadId: string, tagId: string): Promise<void> => {
+    return await invoke('add_thread_tag', { threadId, tagId });
+  },
+
+  removeTag: async (threadId: string, tagId: string): Promise<void> => {
+    return await invoke('remove_thread_tag', { threadId, tagId });
+  },
+};
+
+// Folder API
+export const folderAPI = {
+  create: async (name: string, parentId?: string): Promise<Folder> => {
+    return await invoke('create_folder', { name, parentId });
+  },
+
+  list: async (): Promise<Folder[]> => {
+    return await invoke('list_folders');
+  },
+
+  rename: async (id: string, name: string): Promise<void> => {
+    return await invoke('rename_folder', { id, name });
+  },
+
+  move: async (id: string, parentId: string | null): Promise<void> => {
+    return await invoke('move_folder', { id, parentId });
+  },
+
+  delete: async (id: string): Promise<void> => {
+    return await invoke('delete_folder', { id });
+  },
+};
+
+// Tag API
+export const tagAPI = {
+  create: async (name: string, color: string): Promise<Tag> => {
+    return await invoke('create_tag', { name, color });
+  },
+
+  list: async (): Promise<Tag[]> => {
+    return await invoke('list_tags');
+  },
+
+  update: async (tag: Tag): Promise<void> => {
+    return await invoke('update_tag', { tag });
+  },
+
+  delete: async (id: string): Promise<void> => {
+    return await invoke('delete_tag', { id });
+  },
+};
+
+// Export API. Both open a save dialog and resolve to the written path,
+// or null if the user cancelled.
+export const exportAPI = {
+  thread: async (
+    threadId: string,
+    format: ExportFormat,
+    imageMode?: ExportImageMode
+  ): Promise<string | null> => {
+    return await invoke('export_thread', { threadId, format, imageMode });
+  },
+
+  all: async (format: ExportFormat, imageMode?: ExportImageMode): Promise<string | null> => {
+    return await invoke('export_all', { format, imageMode });
+  },
+};
+
+// Import API
+export const importAPI = {
+  conversations: async (path?: string): Promise<ImportReport | null> => {
+    return await invoke('import_conversations', { path });
+  },
+};
+
+// Database encryption API
+export interface DatabaseStatus {
+  encrypted: boolean;
+  unlocked: boolean; // Thread and chat calls fail until this is true
+}
+
+export const databaseAPI = {
+  status: async (): Promise<DatabaseStatus> => {
+    return await invoke('get_database_status');
+  },
+
+  unlock: async (passphrase: string): Promise<void> => {
+    return await invoke('unlock_database', { passphrase });
+  },
+
+  encrypt: async (passphrase: string): Promise<void> => {
+    return await invoke('encrypt_database', { passphrase });
+  },
+
+  changePassphrase: async (currentPassphrase: string, newPassphrase: string): Promise<void> => {
+    return await invoke('change_database_passphrase', { currentPassphrase, newPassphrase });
+  },
+};
+
+// Profiles: separate settings, conversations and API keys
+export interface Profile {
+  id: string;
+  name: string;
+  createdAt: number;
+}
+
+export interface ProfileList {
+  profiles: Profile[];
+  active: string; // Id of the active profile
+}
+
+export const profileAPI = {
+  list: async (): Promise<ProfileList> => {
+    return await invoke('list_profiles');
+  },
+
+  create: async (name: string): Promise<Profile> => {
+    return await invoke('create_profile', { name });
+  },
+
+  rename: async (id: string, name: string): Promise<Profile> => {
+    return await invoke('rename_profile', { id, name });
+  },
+
+  delete: async (id: string): Promise<void> => {
+    return await invoke('delete_profile', { id });
+  },
+
+  // A passphrase is needed for a profile with an encrypted database
+  switch: async (id: string, passphrase?: string): Promise<Profile> => {
+    return await invoke('switch_profile', { id, passphrase });
+  },
+
+  // Threads and settings should be reloaded when this fires
+  onSwitched: (callback: (profile: Profile) => void) => {
+    return listen<Profile>('profile-switched', (event) => {
+      callback(event.payload);
+    });
+  },
+};
+
+// Backup API
+export const backupAPI = {
+  // Without a path a dialog is shown; null means it was cancelled
+  create: async (path?: string): Promise<string | null> => {
+    return await invoke('create_backup', { path });
+  },
+
+  restore: async (path?: string): Promise<BackupManifest | null> => {
+    return await invoke('restore_backup', { path });
+  },
+
+  // Threads and settings should be reloaded when this fires
+  onRestored: (callback: (manifest: BackupManifest) => void) => {
+    return listen<BackupManifest>('backup-restored', (event) => {
+      callback(event.payload);
+    });
+  },
+};
+
+// Retention API
+export const retentionAPI = {
+  // What the current rules would remove, without removing anything
+  preview: async (): Promise<RetentionReport> => {
+    return await invoke('preview_retention');
+  },
+
+  run: async (): Promise<RetentionReport> => {
+    return await invoke('run_retention');
+  },
+
+  // Threads should be reloaded when this fires
+  onApplied: (callback: (report: RetentionReport) => void) => {
+    return listen<RetentionReport>('retention-applied', (event) => {
+      callback(event.payload);
+    });
+  },
+};
+
+// Trash API
+export const trashAPI = {
+  // Most recently deleted first
+  list: async (): Promise<TrashItem[]> => {
+    return await invoke('list_trash');
+  },
+
+  restoreThread: async (id: string): Promise<void> => {
+    return await invoke('restore_thread', { id });
+  },
+
+  // Also restores the message's thread if that was deleted
+  restoreMessage: async (id: string): Promise<void> => {
+    return await invoke('restore_message', { id });
+  },
+
+  // Returns how many threads and messages were permanently deleted
+  empty: async (): Promise<number> => {
+    return await invoke('empty_trash');
+  },
+};
+
+// Search API
+export const searchAPI = {
+  messages: async (query: string, filters?: SearchFilters): Promise<SearchHit[]> => {
+    return await invoke('search_messages', { query, filters });
+  },
 };
 
 // Chat API
@@ -40,25 +298,62 @@ export const chatAPI = {
     content: string,
     images: string[] | null,
     provider: AIProvider,
-    apiKey: string,
     model: string,
     maxTokens?: number,
-    includeContext?: boolean
-  ): Promise<Message> => {
+    includeContext?: boolean,
+    requestId?: string
+  ): Promise<SendMessageResponse> => {
+    // The API key is looked up in the backend from settings
     return await invoke('send_message', {
       threadId,
       content,
       images,
       provider,
-      apiKey,
       model,
       maxTokens,
       includeContext,
+      requestId,
+    });
+  },
+
+  // Fan one message out to several models; each answer streams under its own requestId
+  sendMessageMulti: async (
+    threadId: string,
+    content: string,
+    images: string[] | null,
+    targets: ModelTarget[]
+  ): Promise<SendMessageMultiResponse> => {
+    return await invoke('send_message_multi', {
+      threadId,
+      content,
+      images,
+      targets,
     });
   },
 
-  getMessages: async (threadId: string): Promise<Message[]> => {
-    return await invoke('get_messages', { threadId });
+  continueMessage: async (
+    messageId: string,
+    provider: AIProvider,
+    model: string,
+    maxTokens?: number,
+    requestId?: string
+  ): Promise<SendMessageResponse> => {
+    return await invoke('continue_message', {
+      messageId,
+      provider,
+      model,
+      maxTokens,
+      requestId,
+    });
+  },
+
+  // Pass the first message id of the previous page as `before` to page back
+  getMessages: async (threadId: string, before?: string, limit?: number): Promise<MessagePage> => {
+    return await invoke('get_messages', { threadId, before, limit });
+  },
+
+  getImage: async (id: string): Promise<ImageData> => {
+    return await invoke('get_image', { id });
   },
 
   deleteMessage: async (id: string): Promise<void> => {
@@ -66,8 +361,8 @@ export const chatAPI = {
   },
 
   // Listen for streaming events
-  onStreamEvent: (callback: (event: StreamEvent) => void) => {
-    return listen<StreamEvent>('chat-stream', (event) => {
+  onStreamEvent: (callback: (payload: ChatStreamPayload) => void) => {
+    return listen<ChatStreamPayload>('chat-stream', (event) => {
       callback(event.payload);
     });
   },
@@ -76,34 +371,93 @@ export const chatAPI = {
 // Settings API
 export interface ProviderSettings {
   enabled: boolean;
-  apiKey: string;
+  apiKey: string; // Masked when read from the backend
+  hasKey?: boolean;
   defaultModel: string;
   temperature: number;
   maxTokens: number;
   isValidated: boolean;
+  api?: ProviderApi; // Required for user-added providers
+  baseUrl?: string; // Overrides the default API URL, e.g. for a proxy
+  headers?: Record<string, string>; // Extra HTTP headers sent with every request
+  models?: string[]; // Offered besides the built-in models
+}
+
+export type ProviderApi = 'anthropic' | 'openai' | 'openrouter';
+
+export interface BackupSettings {
+  enabled: boolean;
+  folder?: string | null; // Defaults to `backups` in the app data directory
+  intervalHours: number;
+  keep: number;
+}
+
+// Each rule is off when null
+export interface RetentionSettings {
+  deleteThreadsAfterDays: number | null; // Pinned threads are kept
+  stripImagesAfterDays: number | null; // Message text is kept
+  maxDatabaseMb: number | null; // Least recently updated threads go first
+  purgeTrashAfterDays: number | null; // Deleted items are kept this long
 }
 
 export interface AppSettings {
+  version: number; // settings.json format version, maintained by the backend
   defaultProvider: string;
-  anthropic: ProviderSettings;
-  openai: ProviderSettings;
-  openrouter: ProviderSettings;
-  gemini: ProviderSettings;
-  ollama: ProviderSettings;
+  providers: Record<string, ProviderSettings>; // Keyed by provider id
   theme: string;
   shortcut: string;
   enableContextDetection: boolean;
+  enableScreenshots: boolean; // Off blocks screen capture, e.g. by policy
+  backup: BackupSettings;
+  retention: RetentionSettings;
+}
+
+// Problem with one field, e.g. `providers.openai.maxTokens`
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+// Settings enforced by an administrator's policy file
+export interface PolicyInfo {
+  lockedFields: string[]; // e.g. `backup.enabled`
+  allowedProviders: string[] | null; // null when every provider is allowed
+}
+
+export interface SettingsWarning {
+  message: string;
+  preservedPath?: string | null; // Where the unreadable settings file was moved
 }
 
+// One changed section, with its value before and after (API keys masked)
+export type SettingsChange =
+  | { section: 'defaultProvider'; old: string; new: string }
+  | { section: 'provider'; id: string; old: ProviderSettings | null; new: ProviderSettings | null } // null when added/removed
+  | { section: 'theme'; old: string; new: string }
+  | { section: 'shortcut'; old: string; new: string }
+  | { section: 'contextDetection'; old: boolean; new: boolean }
+  | { section: 'screenshots'; old: boolean; new: boolean }
+  | { section: 'backup'; old: BackupSettings; new: BackupSettings }
+  | { section: 'retention'; old: RetentionSettings; new: RetentionSettings };
+
 export const settingsAPI = {
   get: async (): Promise<AppSettings> => {
     return await invoke('get_settings');
   },
 
+  // Errors this update would introduce; empty when it can be saved
+  validate: async (settings: AppSettings): Promise<FieldError[]> => {
+    return await invoke('validate_settings', { newSettings: settings });
+  },
+
   update: async (settings: AppSettings): Promise<void> => {
     return await invoke('update_settings', { newSettings: settings });
   },
 
+  getPolicy: async (): Promise<PolicyInfo> => {
+    return await invoke('get_settings_policy');
+  },
+
   setDefaultProvider: async (provider: string): Promise<void> => {
     return await invoke('set_default_provider', { provider });
   },
@@ -112,13 +466,31 @@ export const settingsAPI = {
     return await invoke('set_api_key', { provider, apiKey });
   },
 
-  validateApiKey: async (provider: string, apiKey: string): Promise<{ valid: boolean; availableModels: string[]; defaultModel: string }> => {
+  // Omit apiKey (or pass the masked value) to validate the stored key
+  validateApiKey: async (provider: string, apiKey?: string): Promise<{ valid: boolean; availableModels: string[]; defaultModel: string }> => {
     return await invoke('validate_api_key', { provider, apiKey });
   },
 
   setValidationState: async (provider: string, isValidated: boolean): Promise<void> => {
     return await invoke('set_validation_state', { provider, isValidated });
   },
+
+  // Returns the startup warning once, then null
+  takeWarning: async (): Promise<SettingsWarning | null> => {
+    return await invoke('take_settings_warning');
+  },
+
+  onChanged: (callback: (changes: SettingsChange[]) => void) => {
+    return listen<SettingsChange[]>('settings-changed', (event) => {
+      callback(event.payload);
+    });
+  },
+
+  onWarning: (callback: (warning: SettingsWarning) => void) => {
+    return listen<SettingsWarning>('settings-warning', (event) => {
+      callback(event.payload);
+    });
+  },
 };
 
 // Screenshot API
@@ -169,5 +541,13 @@ export const contextAPI = {
 export type StreamEvent =
   | { type: 'content_delta'; delta: string }
   | { type: 'message_start' }
-  | { type: 'message_stop'; usage?: { inputTokens: number; outputTokens: number } }
+  | { type: 'message_stop'; usage?: { inputTokens: number; outputTokens: number }; stop_reason?: string }
   | { type: 'error'; error: string };
+
+// Stream events are tagged with the request, thread and assistant message they belong to
+export interface ChatStreamPayload {
+  requestId: string;
+  threadId: string;
+  messageId: string;
+  event: StreamEvent;
+}
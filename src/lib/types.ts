@@ -9,16 +9,39 @@ export interface Message {
   threadId: string;
   role: MessageRole;
   content: string;
-  images?: string[]; // Base64 encoded images
+  images?: string[]; // Base64 encoded images (only on messages not yet reloaded)
+  imageRefs?: ImageRef[]; // Stored images; load data with chatAPI.getImage
   createdAt: number;
   metadata?: {
     model?: string;
     tokens?: number;
     provider?: AIProvider;
     error?: string;
+    status?: 'incomplete';
+    stopReason?: string; // e.g. 'end_turn', 'max_tokens' (Anthropic) or 'stop', 'length' (OpenAI)
+    latencyMs?: number;
+    comparisonId?: string; // Shared by sibling answers from send_message_multi
   };
 }
 
+export interface ImageRef {
+  id: string;
+  mimeType: string;
+  width?: number;
+  height?: number;
+  byteSize: number;
+}
+
+export interface ImageData extends ImageRef {
+  data: string; // Base64
+}
+
+// One page of get_messages, oldest message first
+export interface MessagePage {
+  messages: Message[];
+  hasMore: boolean;
+}
+
 export interface Thread {
   id: string;
   name: string;
@@ -26,6 +49,32 @@ export interface Thread {
   updatedAt: number;
   messageCount?: number;
   lastMessage?: string;
+  thumbnail?: string; // Base64 JPEG of the latest image in the thread
+  pinned: boolean;
+  archived: boolean;
+  folderId?: string;
+  tags?: string[]; // Tag ids
+  metadata?: Record<string, unknown>;
+  ephemeral?: boolean; // Private thread kept in memory only
+}
+
+export interface Folder {
+  id: string;
+  name: string;
+  parentId?: string;
+  createdAt: number;
+}
+
+export interface Tag {
+  id: string;
+  name: string;
+  color: string; // #rrggbb
+}
+
+export interface ThreadFilters {
+  folderId?: string; // Includes nested folders
+  tagId?: string;
+  archived?: boolean; // List archived threads instead of active ones
 }
 
 export interface ProviderConfig {
@@ -89,9 +138,103 @@ export interface SendMessageRequest {
   provider?: AIProvider;
 }
 
+// One provider/model pair for send_message_multi
+export interface ModelTarget {
+  provider: AIProvider;
+  model: string;
+  maxTokens?: number;
+  requestId?: string;
+}
+
+export interface SendMessageMultiResponse {
+  comparisonId: string;
+  threadId: string;
+  userMessageId: string;
+  responses: SendMessageResponse[];
+}
+
+// Returned by send_message once the provider accepted the request; the
+// assistant reply then streams on `chat-stream` tagged with these ids
 export interface SendMessageResponse {
-  message: Message;
-  tokensUsed?: number;
+  requestId: string;
+  threadId: string;
+  userMessageId?: string; // Absent for continue_message
+  messageId: string;
+}
+
+// Full-text search
+export interface SearchFilters {
+  role?: MessageRole;
+  provider?: AIProvider;
+  model?: string;
+  from?: number; // Unix millis
+  to?: number;
+  limit?: number;
+}
+
+export interface SearchHit {
+  messageId: string;
+  threadId: string;
+  threadName: string;
+  role: MessageRole;
+  snippet: string; // Matches wrapped in <mark>
+  createdAt: number;
+  rank: number; // Lower is better
+  provider?: AIProvider;
+  model?: string;
+}
+
+// Export
+export type ExportFormat = 'markdown' | 'json' | 'html';
+export type ExportImageMode = 'embedded' | 'sidecar'; // Markdown only
+
+// Import
+export type ImportSource = 'chatgpt' | 'claude' | 'seeva';
+export type ImportStatus = 'imported' | 'skipped' | 'failed';
+
+export interface ImportResult {
+  title: string;
+  status: ImportStatus;
+  threadId?: string; // Existing thread for skipped duplicates
+  messageCount: number;
+  error?: string;
+}
+
+export interface ImportReport {
+  source: ImportSource;
+  imported: number;
+  skipped: number;
+  failed: number;
+  results: ImportResult[];
+}
+
+// Backup
+export interface BackupManifest {
+  format: 'seeva-backup';
+  version: number;
+  createdAt: number;
+  appVersion: string;
+  schemaVersion: number;
+}
+
+// Retention: what a cleanup removed, or would remove in a dry run
+export interface RetentionReport {
+  dryRun: boolean;
+  deletedThreads: { id: string; name: string }[];
+  strippedImages: number;
+  purgedTrash: number; // Threads and messages permanently deleted from the trash
+  bytesBefore: number; // Space in use, excluding free pages
+  bytesAfter: number;
+}
+
+// A deleted thread, or a deleted message of a thread that isn't
+export interface TrashItem {
+  kind: 'thread' | 'message';
+  id: string;
+  threadId: string;
+  threadName: string;
+  preview?: string; // Start of the message content
+  deletedAt: number;
 }
 
 // UI State types
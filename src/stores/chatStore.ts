@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { Message, Thread } from '../lib/types';
-import { threadAPI, chatAPI } from '../lib/tauri-api';
+import { threadAPI, chatAPI, trashAPI } from '../lib/tauri-api';
 
 interface ChatState {
   // Threads
@@ -13,22 +13,37 @@ interface ChatState {
   isStreaming: boolean;
   streamingContent: string;
   isLoadingMessages: boolean;
+  hasMoreMessages: boolean;
+
+  // Correlation ids of the in-flight send_message request
+  activeRequestId: string | null;
+  streamingThreadId: string | null;
 
   // Actions
   loadThreads: () => Promise<void>;
   setCurrentThread: (threadId: string) => Promise<void>;
   createThread: (name: string) => Promise<void>;
+  createEphemeralThread: () => Promise<void>;
+  forkThread: (messageId: string) => Promise<void>;
   deleteThread: (threadId: string) => Promise<void>;
+  restoreThread: (threadId: string) => Promise<void>;
   clearAllThreads: () => Promise<void>;
   renameThread: (threadId: string, name: string) => Promise<void>;
+  setThreadPinned: (threadId: string, pinned: boolean) => Promise<void>;
+  archiveThread: (threadId: string) => Promise<void>;
 
   loadMessages: (threadId: string) => Promise<void>;
+  loadOlderMessages: () => Promise<void>;
   sendMessage: (content: string, images: string[] | null) => Promise<void>;
+  continueMessage: (messageId: string) => Promise<void>;
   deleteMessage: (messageId: string) => Promise<void>;
+  restoreMessage: (messageId: string) => Promise<void>;
 
   setStreaming: (isStreaming: boolean) => void;
   appendStreamingContent: (content: string) => void;
   clearStreamingContent: () => void;
+  finishStream: (threadId: string) => Promise<void>;
+  failStream: (threadId: string, error: string) => Promise<void>;
 
   // Getters
   getCurrentThread: () => Thread | undefined;
@@ -44,12 +59,16 @@ export const useChatStore = create<ChatState>((set, get) => ({
   isStreaming: false,
   streamingContent: '',
   isLoadingMessages: false,
+  hasMoreMessages: false,
+  activeRequestId: null,
+  streamingThreadId: null,
 
   // Thread actions
   loadThreads: async () => {
     set({ isLoadingThreads: true });
     try {
-      let threads = await threadAPI.list();
+      const ephemeral = await threadAPI.listEphemeral();
+      let threads = [...ephemeral, ...(await threadAPI.list())];
       let currentId = await threadAPI.getCurrentId();
 
       // If no threads exist, create a default one
@@ -133,6 +152,58 @@ export const useChatStore = create<ChatState>((set, get) => ({
     }
   },
 
+  createEphemeralThread: async () => {
+    const { useToastStore } = await import('../hooks/useToast');
+    const { useUIStore } = await import('./uiStore');
+    try {
+      const newThread = await threadAPI.createEphemeral('Private Conversation');
+      set((state) => ({
+        threads: [newThread, ...state.threads],
+        currentThreadId: newThread.id,
+        messages: [],
+      }));
+
+      const uiStore = useUIStore.getState();
+      uiStore.clearScreenshot();
+      uiStore.clearScreenContext();
+    } catch (error) {
+      console.error('Failed to create private thread:', error);
+      useToastStore.getState().addToast({
+        type: 'error',
+        message: `Failed to start private conversation: ${error}`,
+        duration: 5000
+      });
+    }
+  },
+
+  forkThread: async (messageId) => {
+    const { useToastStore } = await import('../hooks/useToast');
+    const { currentThreadId } = get();
+    if (!currentThreadId) return;
+
+    try {
+      const fork = await threadAPI.fork(currentThreadId, messageId);
+      set((state) => ({
+        threads: [fork, ...state.threads],
+        currentThreadId: fork.id,
+      }));
+      await get().loadMessages(fork.id);
+
+      useToastStore.getState().addToast({
+        type: 'success',
+        message: `Continuing in "${fork.name}"`,
+        duration: 3000
+      });
+    } catch (error) {
+      console.error('Failed to fork thread:', error);
+      useToastStore.getState().addToast({
+        type: 'error',
+        message: `Failed to fork thread: ${error}`,
+        duration: 5000
+      });
+    }
+  },
+
   deleteThread: async (threadId) => {
     const { useToastStore } = await import('../hooks/useToast');
     try {
@@ -169,10 +240,12 @@ export const useChatStore = create<ChatState>((set, get) => ({
         await get().loadMessages(newCurrentId);
       }
 
+      // Private threads are gone for good; others can be restored
       useToastStore.getState().addToast({
         type: 'success',
-        message: `Thread "${threadName}" deleted successfully`,
-        duration: 3000
+        message: `Thread "${threadName}" ${thread?.ephemeral ? 'deleted' : 'moved to trash'}`,
+        duration: 5000,
+        action: thread?.ephemeral ? undefined : { label: 'Undo', onClick: () => get().restoreThread(threadId) },
       });
     } catch (error) {
       console.error('Failed to delete thread:', error);
@@ -194,6 +267,22 @@ export const useChatStore = create<ChatState>((set, get) => ({
     }
   },
 
+  restoreThread: async (threadId) => {
+    const { useToastStore } = await import('../hooks/useToast');
+    try {
+      await trashAPI.restoreThread(threadId);
+      await get().loadThreads();
+      await get().setCurrentThread(threadId);
+    } catch (error) {
+      console.error('Failed to restore thread:', error);
+      useToastStore.getState().addToast({
+        type: 'error',
+        message: `Failed to restore thread: ${error}`,
+        duration: 5000
+      });
+    }
+  },
+
   clearAllThreads: async () => {
     const { useToastStore } = await import('../hooks/useToast');
     try {
@@ -250,18 +339,63 @@ export const useChatStore = create<ChatState>((set, get) => ({
     }
   },
 
+  setThreadPinned: async (threadId, pinned) => {
+    try {
+      await threadAPI.setPinned(threadId, pinned);
+      // Reload to get the backend's pinned-first ordering
+      set({ threads: await threadAPI.list() });
+    } catch (error) {
+      console.error('Failed to pin thread:', error);
+    }
+  },
+
+  archiveThread: async (threadId) => {
+    try {
+      await threadAPI.setArchived(threadId, true);
+      set((state) => ({
+        threads: state.threads.filter(t => t.id !== threadId),
+      }));
+    } catch (error) {
+      console.error('Failed to archive thread:', error);
+    }
+  },
+
   // Message actions
   loadMessages: async (threadId) => {
     set({ isLoadingMessages: true });
     try {
-      const messages = await chatAPI.getMessages(threadId);
-      set({ messages, isLoadingMessages: false });
+      const page = await chatAPI.getMessages(threadId);
+      set({ messages: page.messages, hasMoreMessages: page.hasMore, isLoadingMessages: false });
     } catch (error) {
       console.error('Failed to load messages:', error);
       set({ isLoadingMessages: false });
     }
   },
 
+  loadOlderMessages: async () => {
+    const { currentThreadId, messages, isLoadingMessages, hasMoreMessages } = get();
+    if (!currentThreadId || isLoadingMessages || !hasMoreMessages || messages.length === 0) {
+      return;
+    }
+
+    set({ isLoadingMessages: true });
+    try {
+      const page = await chatAPI.getMessages(currentThreadId, messages[0].id);
+      // Drop the result if the user switched threads meanwhile
+      if (get().currentThreadId !== currentThreadId) {
+        return;
+      }
+      set((state) => ({
+        messages: [...page.messages, ...state.messages],
+        hasMoreMessages: page.hasMore,
+      }));
+    } catch (error) {
+      console.error('Failed to load older messages:', error);
+    } finally {
+      set({ isLoadingMessages: false });
+    }
+  },
+
   sendMessage: async (content, images) => {
     const state = get();
     const currentThreadId = state.currentThreadId;
@@ -282,7 +416,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
     const providerSettings = (settings as any)[provider];
 
     // Check if API key is configured
-    if (!providerSettings?.apiKey || providerSettings.apiKey.trim() === '') {
+    if (!providerSettings?.hasKey) {
       const providerNames: Record<string, string> = {
         anthropic: 'Anthropic Claude',
         openai: 'OpenAI',
@@ -323,10 +457,13 @@ export const useChatStore = create<ChatState>((set, get) => ({
       return;
     }
 
+    // Shown right away; taken back again if the request can't be started
+    const pendingMessageId = `msg-${Date.now()}`;
+
     try {
       // Add user message immediately to UI
       const userMessage: Message = {
-        id: `msg-${Date.now()}`,
+        id: pendingMessageId,
         threadId: currentThreadId,
         role: 'user',
         content,
@@ -334,46 +471,29 @@ export const useChatStore = create<ChatState>((set, get) => ({
         createdAt: Date.now(),
       };
 
+      // Generate the request id up front so stream events that arrive before
+      // send_message returns can already be matched to this request
+      const requestId = crypto.randomUUID();
+
       set((state) => ({
         messages: [...state.messages, userMessage],
         isStreaming: true,
         streamingContent: '',
+        activeRequestId: requestId,
+        streamingThreadId: currentThreadId,
       }));
 
-      // Send message and get streaming response
-      const assistantMessage = await chatAPI.sendMessage(
+      // Start the request; the reply streams in through useStreamListener
+      await chatAPI.sendMessage(
         currentThreadId,
         content,
         images,
         provider as any,
-        providerSettings.apiKey,
         providerSettings.defaultModel,
         providerSettings.maxTokens,
-        true // includeContext - enables context detection
+        true, // includeContext - enables context detection
+        requestId
       );
-
-      // Add assistant message
-      set((state) => ({
-        messages: [...state.messages, assistantMessage],
-        isStreaming: false,
-        streamingContent: '',
-      }));
-
-      // Add a small delay before reloading to ensure backend has persisted the message
-      await new Promise(resolve => setTimeout(resolve, 200));
-
-      // Reload only the messages for the current thread instead of all threads
-      // This prevents race conditions where we load messages before they're persisted
-      await get().loadMessages(currentThreadId);
-
-      // Reload threads to update counts and last message (without reloading messages again)
-      try {
-        const threads = await threadAPI.list();
-        const currentId = await threadAPI.getCurrentId();
-        set({ threads, currentThreadId: currentId || threads[0]?.id || null });
-      } catch (error) {
-        console.error('Failed to reload threads after message:', error);
-      }
     } catch (error) {
       console.error('Failed to send message:', error);
 
@@ -394,21 +514,81 @@ export const useChatStore = create<ChatState>((set, get) => ({
         message: `Failed to send message: ${errorMessage}`
       });
 
-      set({ isStreaming: false, streamingContent: '' });
+      // The backend doesn't keep a message that was never sent
+      set((state) => ({
+        messages: state.messages.filter((m) => m.id !== pendingMessageId),
+        isStreaming: false,
+        streamingContent: '',
+        activeRequestId: null,
+        streamingThreadId: null,
+      }));
+    }
+  },
+
+  continueMessage: async (messageId) => {
+    const state = get();
+    const message = state.messages.find(m => m.id === messageId);
+    if (!message || state.isStreaming) return;
+
+    const settings = await import('./settingsStore').then(m => m.useSettingsStore.getState().settings);
+    const provider = message.metadata?.provider || settings.defaultProvider;
+    const providerSettings = (settings as any)[provider];
+    const requestId = crypto.randomUUID();
+
+    set({
+      isStreaming: true,
+      streamingContent: '',
+      activeRequestId: requestId,
+      streamingThreadId: message.threadId,
+    });
+
+    try {
+      await chatAPI.continueMessage(
+        messageId,
+        provider as any,
+        message.metadata?.model || providerSettings?.defaultModel,
+        providerSettings?.maxTokens,
+        requestId
+      );
+    } catch (error) {
+      console.error('Failed to continue message:', error);
+      await get().failStream(message.threadId, String(error));
     }
   },
 
   deleteMessage: async (messageId) => {
+    const { useToastStore } = await import('../hooks/useToast');
     try {
       await chatAPI.deleteMessage(messageId);
       set((state) => ({
         messages: state.messages.filter(m => m.id !== messageId),
       }));
+
+      if (!get().getCurrentThread()?.ephemeral) {
+        useToastStore.getState().addToast({
+          type: 'info',
+          message: 'Message moved to trash',
+          duration: 5000,
+          action: { label: 'Undo', onClick: () => get().restoreMessage(messageId) },
+        });
+      }
     } catch (error) {
       console.error('Failed to delete message:', error);
     }
   },
 
+  restoreMessage: async (messageId) => {
+    try {
+      await trashAPI.restoreMessage(messageId);
+      const { currentThreadId } = get();
+      if (currentThreadId) {
+        await get().loadMessages(currentThreadId);
+      }
+    } catch (error) {
+      console.error('Failed to restore message:', error);
+    }
+  },
+
   // Streaming actions
   setStreaming: (isStreaming) => {
     set({ isStreaming });
@@ -424,6 +604,39 @@ export const useChatStore = create<ChatState>((set, get) => ({
     set({ streamingContent: '' });
   },
 
+  finishStream: async (threadId) => {
+    set({ isStreaming: false, streamingContent: '', activeRequestId: null, streamingThreadId: null });
+
+    // The backend emits message_stop only after the assistant message is saved
+    if (get().currentThreadId === threadId) {
+      await get().loadMessages(threadId);
+    }
+
+    // Reload threads to update counts and last message (without reloading messages again)
+    try {
+      const threads = await threadAPI.list();
+      const currentId = await threadAPI.getCurrentId();
+      set({ threads, currentThreadId: currentId || threads[0]?.id || null });
+    } catch (error) {
+      console.error('Failed to reload threads after message:', error);
+    }
+  },
+
+  failStream: async (threadId, error) => {
+    set({ isStreaming: false, streamingContent: '', activeRequestId: null, streamingThreadId: null });
+
+    // A partial answer may have been saved as an incomplete message
+    if (get().currentThreadId === threadId) {
+      await get().loadMessages(threadId);
+    }
+
+    const { useToastStore } = await import('../hooks/useToast');
+    useToastStore.getState().addToast({
+      type: 'error',
+      message: `Failed to send message: ${error}`
+    });
+  },
+
   // Getters
   getCurrentThread: () => {
     const state = get();
@@ -18,50 +18,66 @@ interface SettingsState {
 
 // Default settings matching Rust backend structure
 const defaultSettings: AppSettings = {
+  version: 2,
   defaultProvider: 'anthropic',
-  anthropic: {
-    enabled: true,
-    apiKey: '',
-    defaultModel: 'claude-sonnet-4-5-20250929',
-    temperature: 0.7,
-    maxTokens: 64000,
-    isValidated: false,
-  },
-  openai: {
-    enabled: false,
-    apiKey: '',
-    defaultModel: 'gpt-5-mini',
-    temperature: 0.7,
-    maxTokens: 32000,
-    isValidated: false,
-  },
-  openrouter: {
-    enabled: false,
-    apiKey: '',
-    defaultModel: 'openai/gpt-5.1',
-    temperature: 0.7,
-    maxTokens: 32000,
-    isValidated: false,
-  },
-  gemini: {
-    enabled: false,
-    apiKey: '',
-    defaultModel: 'gemini-pro',
-    temperature: 0.7,
-    maxTokens: 32000,
-    isValidated: false,
-  },
-  ollama: {
-    enabled: false,
-    apiKey: '',
-    defaultModel: 'llama2',
-    temperature: 0.7,
-    maxTokens: 16000,
-    isValidated: false,
+  providers: {
+    anthropic: {
+      enabled: true,
+      apiKey: '',
+      defaultModel: 'claude-sonnet-4-5-20250929',
+      temperature: 0.7,
+      maxTokens: 64000,
+      isValidated: false,
+    },
+    openai: {
+      enabled: false,
+      apiKey: '',
+      defaultModel: 'gpt-5-mini',
+      temperature: 0.7,
+      maxTokens: 32000,
+      isValidated: false,
+    },
+    openrouter: {
+      enabled: false,
+      apiKey: '',
+      defaultModel: 'openai/gpt-5.1',
+      temperature: 0.7,
+      maxTokens: 32000,
+      isValidated: false,
+    },
+    gemini: {
+      enabled: false,
+      apiKey: '',
+      defaultModel: 'gemini-pro',
+      temperature: 0.7,
+      maxTokens: 32000,
+      isValidated: false,
+    },
+    ollama: {
+      enabled: false,
+      apiKey: '',
+      defaultModel: 'llama2',
+      temperature: 0.7,
+      maxTokens: 16000,
+      isValidated: false,
+    },
   },
   theme: 'dark',
   shortcut: 'Control+Shift+Space',
   enableContextDetection: true,
+  enableScreenshots: true,
+  backup: {
+    enabled: false,
+    folder: null,
+    intervalHours: 24,
+    keep: 7,
+  },
+  retention: {
+    deleteThreadsAfterDays: null,
+    stripImagesAfterDays: null,
+    maxDatabaseMb: null,
+    purgeTrashAfterDays: 30,
+  },
 };
 
 export const useSettingsStore = create<SettingsState>((set, get) => ({
@@ -83,7 +99,9 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   updateSettings: async (newSettings) => {
     try {
       await settingsAPI.update(newSettings);
-      set({ settings: newSettings });
+      // Reload so keys come back masked with hasKey set
+      const settings = await settingsAPI.get();
+      set({ settings });
     } catch (error) {
       console.error('Failed to update settings:', error);
     }
@@ -109,18 +127,9 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   setApiKey: async (provider, apiKey) => {
     try {
       await settingsAPI.setApiKey(provider, apiKey);
-      set((state) => {
-        if (!state.settings) return state;
-        return {
-          settings: {
-            ...state.settings,
-            [provider]: {
-              ...(state.settings as any)[provider],
-              apiKey,
-            },
-          },
-        };
-      });
+      // Reload so the store only ever holds the masked key
+      const settings = await settingsAPI.get();
+      set({ settings });
     } catch (error) {
       console.error('Failed to set API key:', error);
     }
@@ -129,7 +138,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   getProviderConfig: (provider) => {
     const state = get();
     if (!state.settings) return null;
-    return (state.settings as any)[provider];
+    return state.settings.providers[provider] ?? null;
   },
 }));
 
@@ -5,6 +5,7 @@ interface UIState {
   // Modals
   isSettingsOpen: boolean;
   isScreenshotPreviewOpen: boolean;
+  isProfileChooserOpen: boolean;
 
   // Screenshot
   currentScreenshot: string | null; // Base64 encoded image
@@ -26,6 +27,9 @@ interface UIState {
   closeSettings: () => void;
   toggleSettings: () => void;
 
+  openProfileChooser: () => void;
+  closeProfileChooser: () => void;
+
   openScreenshotPreview: (screenshot: string) => void;
   closeScreenshotPreview: () => void;
 
@@ -48,6 +52,7 @@ export const useUIStore = create<UIState>((set) => ({
   // Initial state
   isSettingsOpen: false,
   isScreenshotPreviewOpen: false,
+  isProfileChooserOpen: false,
   currentScreenshot: null,
   screenshotCache: null,
   isCapturingScreenshot: false,
@@ -61,6 +66,10 @@ export const useUIStore = create<UIState>((set) => ({
   closeSettings: () => set({ isSettingsOpen: false }),
   toggleSettings: () => set((state) => ({ isSettingsOpen: !state.isSettingsOpen })),
 
+  // Profile actions
+  openProfileChooser: () => set({ isProfileChooserOpen: true, isSettingsOpen: false }),
+  closeProfileChooser: () => set({ isProfileChooserOpen: false }),
+
   // Screenshot actions
   openScreenshotPreview: (screenshot) =>
     set({ isScreenshotPreviewOpen: true, currentScreenshot: screenshot }),